@@ -15,6 +15,7 @@
 #[stabby::import(name = "library")]
 extern "C" {
     pub fn stable_fn(v: u8) -> stabby::option::Option<()>;
+    pub static PLUGIN_VERSION: stabby::tuple::Tuple2<u32, u32>;
 }
 
 #[stabby::import(canaries = "", name = "library")]
@@ -24,6 +25,9 @@ extern "C" {
 }
 
 fn main() {
+    stabby::verify_imports().unwrap();
+    assert_eq!(stabby::abi::checked_import::imports().iter().count(), 2);
     stable_fn(5);
+    assert_eq!(PLUGIN_VERSION.0, 1);
     unsafe { unstable_fn(&[1, 2, 3, 4]) };
 }
@@ -12,6 +12,18 @@
 //   Pierre Avital, <pierre.avital@me.com>
 //
 
+#[cfg(any(unix, windows))]
+#[stabby::import(dynamic = "Library")]
+extern "C" {
+    pub fn stable_fn(v: u8) -> stabby::option::Option<()>;
+    pub static PLUGIN_VERSION: stabby::tuple::Tuple2<u32, u32>;
+}
+
+#[stabby::stabby(module = "plugin_api")]
+pub struct PluginApi {
+    pub add: extern "C" fn(u32, u32) -> u32,
+}
+
 #[cfg(any(unix, windows))]
 fn main() {
     use stabby::libloading::StabbyLibrary;
@@ -38,7 +50,74 @@ fn main() {
         let unstable_fn = lib
             .get_canaried::<extern "C" fn(&[u8])>(b"unstable_fn")
             .unwrap();
+        let version = lib
+            .get_stabbied::<&stabby::tuple::Tuple2<u32, u32>>(b"PLUGIN_VERSION")
+            .unwrap();
+        assert!(matches!(
+            lib.get_stabbied::<extern "C" fn(u16) -> stabby::option::Option<()>>(b"stable_fn"),
+            Err(stabby::libloading::LoadError::ReportMismatch(_))
+        ));
+        assert!(matches!(
+            lib.get_stabbied::<extern "C" fn(u8)>(b"missing_fn"),
+            Err(stabby::libloading::LoadError::SymbolNotFound { .. })
+        ));
+        let descriptor = lib
+            .get::<extern "C" fn() -> &'static stabby::abi::checked_import::CanaryDescriptor>(
+                b"unstable_fn_canary_descriptor_v1",
+            )
+            .unwrap();
+        assert_eq!(
+            *descriptor(),
+            stabby::abi::checked_import::CanaryDescriptor::LOCAL
+        );
+        #[cfg(target_os = "linux")]
+        {
+            let mut exports = lib
+                .exports()
+                .unwrap()
+                .into_iter()
+                .map(|export| export.name)
+                .collect::<Vec<_>>();
+            exports.sort();
+            assert_eq!(exports, ["PLUGIN_VERSION", "stable_fn"]);
+        }
+        let handle =
+            stabby::libloading::StabbyLibraryHandle::from(libloading::Library::new(path).unwrap());
+        let owned_fn = handle
+            .get_stabbied_owned::<extern "C" fn(u8) -> stabby::option::Option<()>>(b"stable_fn")
+            .unwrap();
+        drop(handle);
+        std::thread::spawn(move || owned_fn(6)).join().unwrap();
+        let reloadable = stabby::libloading::ReloadableLibrary::open(path).unwrap();
+        let reloaded_fn = reloadable
+            .current()
+            .unwrap()
+            .get_stabbied_owned::<extern "C" fn(u8) -> stabby::option::Option<()>>(b"stable_fn")
+            .unwrap();
+        assert_eq!(reloaded_fn.generation(), 1);
+        assert!(matches!(
+            reloadable.try_reload(),
+            Err(stabby::libloading::LoadError::LibraryInUse {
+                generation: 1,
+                live: 1
+            })
+        ));
+        drop(reloaded_fn);
+        assert_eq!(reloadable.try_reload().unwrap(), 2);
+        assert_eq!(reloadable.current().unwrap().generation(), 2);
+        let library = Library::load(path).unwrap();
+        library.stable_fn()(7);
+        assert_eq!(library.PLUGIN_VERSION().0, 1);
+        let plugin = lib.get_plugin::<PluginApi>().unwrap();
+        assert_eq!(plugin.name, "adder");
+        assert!(lib
+            .get::<unsafe extern "C" fn(*const ()) -> usize>(
+                stabby::abi::vtable::UNREGISTER_LIBRARY_SYMBOL.as_bytes()
+            )
+            .is_ok());
+        assert_eq!((plugin.add)(1, 2), 3);
         stable_fn(5);
+        assert_eq!(version.1, 2);
         unstable_fn(&[1, 2, 3, 4]);
     }
 }
@@ -47,3 +126,157 @@ fn main() {
 fn main() {
     panic!("This platform is not supported by this example")
 }
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use stabby::libloading::{LoadError, ReloadableLibrary, StabbyLibraryHandle};
+
+    /// The path to `examples/library`'s build, which lives next to the test binary's `deps` folder.
+    fn library_path() -> std::path::PathBuf {
+        let exe = std::env::current_exe().unwrap();
+        let dir = exe.parent().and_then(std::path::Path::parent).unwrap();
+        dir.join("liblibrary.so")
+    }
+
+    #[test]
+    fn library_in_use() {
+        unsafe {
+            let reloadable = ReloadableLibrary::open(library_path()).unwrap();
+            let handle = reloadable.current().unwrap();
+            let error = reloadable.try_reload().unwrap_err();
+            assert!(
+                matches!(
+                    error,
+                    LoadError::LibraryInUse {
+                        generation: 1,
+                        live: 1
+                    }
+                ),
+                "{error:?}"
+            );
+            assert_eq!(
+                error.to_string(),
+                "Couldn't unload generation 1 of the library: 1 values still use it"
+            );
+            assert!(std::error::Error::source(&error).is_none());
+            drop(handle);
+            assert_eq!(reloadable.try_reload().unwrap(), 2);
+        }
+    }
+
+    fn is_loaded(path: &std::path::Path) -> bool {
+        let path = std::fs::canonicalize(path).unwrap();
+        std::fs::read_to_string("/proc/self/maps")
+            .unwrap()
+            .lines()
+            .any(|line| line.ends_with(path.to_str().unwrap()))
+    }
+
+    #[test]
+    fn handle_keeps_library_alive() {
+        let path = library_path();
+        unsafe {
+            let handle = StabbyLibraryHandle::open(&path).unwrap();
+            assert_eq!(handle.strong_count(), 1);
+            let stable_fn = handle
+                .get_stabbied_owned::<extern "C" fn(u8) -> stabby::option::Option<()>>(b"stable_fn")
+                .unwrap();
+            let version = handle
+                .get_stabbied_owned::<&stabby::tuple::Tuple2<u32, u32>>(b"PLUGIN_VERSION")
+                .unwrap();
+            assert_eq!(handle.strong_count(), 3);
+            drop(handle);
+            assert_eq!(stable_fn.library().strong_count(), 3);
+            assert!(is_loaded(&path));
+            let version = std::thread::spawn(move || {
+                stable_fn(1);
+                version.map(|version| *version)
+            })
+            .join()
+            .unwrap();
+            assert_eq!(version.library().strong_count(), 2);
+            assert!(is_loaded(&path));
+            assert_eq!(*version, stabby::tuple::Tuple2(1, 2));
+        }
+    }
+
+    /// `#[link]` only makes sense on `extern` blocks: it mustn't be forwarded to the generated struct.
+    #[stabby::import(dynamic = "LinkedLibrary")]
+    #[link(name = "library")]
+    extern "C" {
+        /// Forwarded to the accessor.
+        pub static PLUGIN_VERSION: stabby::tuple::Tuple2<u32, u32>;
+    }
+
+    #[test]
+    fn dynamic_accessors() {
+        let library = unsafe { LinkedLibrary::load(library_path()) }.unwrap();
+        let version: &stabby::tuple::Tuple2<u32, u32> = library.PLUGIN_VERSION();
+        assert_eq!(*version, stabby::tuple::Tuple2(1, 2));
+        assert_eq!(library.library().strong_count(), 1);
+    }
+
+    #[test]
+    fn reload_reads_a_new_image() {
+        let path = library_path();
+        let version_of = |library: &stabby::libloading::StabbyLibraryHandle| unsafe {
+            library
+                .get_stabbied_owned::<&stabby::tuple::Tuple2<u32, u32>>(b"PLUGIN_VERSION")
+                .unwrap()
+                .map(|version| version as *const stabby::tuple::Tuple2<u32, u32>)
+        };
+        unsafe {
+            // Keeping the library open elsewhere means that opening its path again would return the same image.
+            let original = StabbyLibraryHandle::open(&path).unwrap();
+            let reloadable = ReloadableLibrary::open(&path).unwrap();
+            let first = version_of(&reloadable.current().unwrap());
+            assert_ne!(*first, *version_of(&original));
+            assert_eq!(reloadable.live(), 1);
+            assert!(matches!(
+                reloadable.try_reload(),
+                Err(LoadError::LibraryInUse { generation: 1, .. })
+            ));
+            drop(first);
+            assert_eq!(reloadable.try_reload().unwrap(), 2);
+            let second = version_of(&reloadable.current().unwrap());
+            assert_eq!(second.generation(), 2);
+            assert_ne!(*second, *version_of(&original));
+            assert_eq!(**second, stabby::tuple::Tuple2(1, 2));
+        }
+        let error =
+            unsafe { ReloadableLibrary::open(path.with_file_name("missing.so")) }.unwrap_err();
+        assert!(matches!(error, LoadError::LibraryCopy(_)), "{error:?}");
+    }
+
+    #[test]
+    fn vtables_are_unregistered() {
+        type BoxedAny = stabby::dynptr!(stabby::boxed::Box<dyn stabby::Any + Send>);
+        let reloadable = unsafe { ReloadableLibrary::open(library_path()) }.unwrap();
+        let library = reloadable.current().unwrap();
+        unsafe {
+            let registered = library
+                .get_stabbied_owned::<extern "C" fn() -> usize>(b"registered_vtables")
+                .unwrap();
+            let boxed_any = library
+                .get_stabbied_owned::<extern "C" fn(u8) -> BoxedAny>(b"boxed_any")
+                .unwrap();
+            let before = registered();
+            let value = boxed_any.clone().map(|boxed_any| boxed_any(3));
+            assert_eq!(value.stable_downcast_ref::<u8, _>(), Some(&3));
+            assert_eq!(registered(), before + 1);
+            drop(value);
+            // Each copy of `stabby` only removes the vtables of the library it's asked to.
+            let unregister = library
+                .get::<unsafe extern "C" fn(*const ()) -> usize>(
+                    stabby::abi::vtable::UNREGISTER_LIBRARY_SYMBOL.as_bytes(),
+                )
+                .unwrap();
+            let here = stabby::abi::vtable::library_of(library_path as *const ());
+            assert_eq!(unregister(here), 0);
+            let base = stabby::abi::vtable::library_of(*boxed_any as *const ());
+            assert_ne!(base, here);
+            assert_eq!(unregister(base), before + 1);
+            assert_eq!(registered(), 0);
+        }
+    }
+}
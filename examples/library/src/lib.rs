@@ -22,3 +22,28 @@ pub extern "C" fn stable_fn(v: u8) -> stabby::option::Option<()> {
 pub extern "C" fn unstable_fn(v: &[u8]) {
     println!("{v:?}")
 }
+
+#[stabby::export]
+pub static PLUGIN_VERSION: stabby::tuple::Tuple2<u32, u32> = stabby::tuple::Tuple2(1, 2);
+
+#[stabby::stabby(module = "plugin_api")]
+pub struct PluginApi {
+    pub add: extern "C" fn(u32, u32) -> u32,
+}
+extern "C" fn add(a: u32, b: u32) -> u32 {
+    a.wrapping_add(b)
+}
+#[stabby::plugin(name = "adder")]
+pub static PLUGIN: PluginApi = PluginApi { add };
+
+#[stabby::export]
+pub extern "C" fn boxed_any(
+    value: u8,
+) -> stabby::dynptr!(stabby::boxed::Box<dyn stabby::Any + Send>) {
+    stabby::boxed::Box::new(value).into()
+}
+
+#[stabby::export]
+pub extern "C" fn registered_vtables() -> usize {
+    stabby::abi::vtable::registry_stats().count
+}
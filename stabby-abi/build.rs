@@ -121,6 +121,7 @@ fn main() {
     println!(
         r#"cargo:rustc-check-cfg=cfg(stabby_check_unreachable, values(none(), "true", "false"))"#
     );
+    println!(r#"cargo:rustc-check-cfg=cfg(stabby_check_alloc, values(none(), "true", "false"))"#);
     println!(r#"cargo:rustc-check-cfg=cfg(stabby_unsafe_wakers, values(none(), "true", "false"))"#);
     println!(
         r#"cargo:rustc-check-cfg=cfg(stabby_vtables, values(none(), "vec", "btree", "no_alloc"))"#
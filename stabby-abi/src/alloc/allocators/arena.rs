@@ -0,0 +1,267 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   Pierre Avital, <pierre.avital@me.com>
+//
+
+use core::{
+    ptr::NonNull,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::alloc::{AllocationError, DefaultAllocator, IAlloc, Layout};
+
+/// A bump allocator, which serves allocations from a single region and frees them all at once.
+///
+/// Allocations are made through [`ArenaAlloc`] handles, which borrow the arena. Freeing through them is a no-op:
+/// the memory is only reclaimed by [`Arena::reset`], which requires every handle (and therefore every container
+/// built with one) to have been dropped.
+///
+/// Both the arena and its handles are ABI-stable, so a host may lend an arena to a plugin, have it build its
+/// containers in it, and reclaim them all in bulk once they have been dropped.
+#[crate::stabby]
+pub struct Arena<Alloc: IAlloc = DefaultAllocator> {
+    start: NonNull<u8>,
+    capacity: usize,
+    used: AtomicUsize,
+    alloc: Alloc,
+}
+// SAFETY: The region is owned by the arena, and only ever accessed through atomic bumps when shared.
+unsafe impl<Alloc: IAlloc + Send> Send for Arena<Alloc> {}
+// SAFETY: The region is owned by the arena, and only ever accessed through atomic bumps when shared.
+unsafe impl<Alloc: IAlloc + Send> Sync for Arena<Alloc> {}
+#[cfg(not(stabby_default_alloc = "disabled"))]
+impl Arena {
+    /// Allocates an arena of `capacity` bytes from the default allocator.
+    ///
+    /// # Errors
+    /// If the region couldn't be allocated.
+    pub fn new(capacity: usize) -> Result<Self, AllocationError> {
+        Self::new_in(capacity, DefaultAllocator::default())
+    }
+}
+impl<Alloc: IAlloc> Arena<Alloc> {
+    /// Allocates an arena of `capacity` bytes from `alloc`.
+    ///
+    /// # Errors
+    /// If the region couldn't be allocated.
+    pub fn new_in(capacity: usize, mut alloc: Alloc) -> Result<Self, AllocationError> {
+        let start = if capacity == 0 {
+            NonNull::dangling()
+        } else {
+            NonNull::new(
+                alloc
+                    .alloc(Layout {
+                        size: capacity,
+                        align: core::mem::align_of::<usize>(),
+                    })
+                    .cast(),
+            )
+            .ok_or(AllocationError())?
+        };
+        Ok(Self {
+            start,
+            capacity,
+            used: AtomicUsize::new(0),
+            alloc,
+        })
+    }
+    /// Returns a handle to allocate in the arena.
+    pub const fn allocator(&self) -> ArenaAlloc<'_, Alloc> {
+        ArenaAlloc { arena: self }
+    }
+    /// The size of the arena's region, in bytes.
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+    /// The number of bytes used by allocations (and their alignment padding) since the last reset.
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Acquire)
+    }
+    /// The number of bytes that may still be allocated before the next reset.
+    pub fn remaining(&self) -> usize {
+        self.capacity.saturating_sub(self.used())
+    }
+    /// Frees every allocation made in the arena at once, making its whole region available again.
+    ///
+    /// Taking `&mut self` guarantees that no [`ArenaAlloc`] handle, nor any container built with one, is still alive.
+    pub fn reset(&mut self) {
+        *self.used.get_mut() = 0;
+    }
+    fn bump(&self, layout: Layout) -> *mut () {
+        bump(self.start, self.capacity, &self.used, layout)
+    }
+}
+impl<Alloc: IAlloc> Drop for Arena<Alloc> {
+    fn drop(&mut self) {
+        if self.capacity != 0 {
+            // SAFETY: the region was allocated by `self.alloc` in `Arena::new_in`.
+            unsafe { self.alloc.free(self.start.as_ptr().cast()) }
+        }
+    }
+}
+impl<Alloc: IAlloc> core::fmt::Debug for Arena<Alloc> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Arena")
+            .field("capacity", &self.capacity)
+            .field("used", &self.used())
+            .finish()
+    }
+}
+
+/// Bumps `used` to allocate `layout` in the region of `capacity` bytes starting at `start`.
+pub(crate) fn bump(
+    start: NonNull<u8>,
+    capacity: usize,
+    used: &AtomicUsize,
+    layout: Layout,
+) -> *mut () {
+    if layout.size == 0 {
+        return core::ptr::null_mut();
+    }
+    let base = start.as_ptr() as usize;
+    let align = layout.align.max(1);
+    let mut current = used.load(Ordering::Acquire);
+    loop {
+        let Some((offset, end)) = base
+            .checked_add(current)
+            .and_then(|addr| addr.checked_add(align.wrapping_sub(1)))
+            .map(|addr| (addr & !align.wrapping_sub(1)).wrapping_sub(base))
+            .and_then(|offset| Some((offset, offset.checked_add(layout.size)?)))
+            .filter(|(_, end)| *end <= capacity)
+        else {
+            return core::ptr::null_mut();
+        };
+        match used.compare_exchange_weak(current, end, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => return start.as_ptr().wrapping_add(offset).cast(),
+            Err(updated) => current = updated,
+        }
+    }
+}
+/// Resizes `ptr` in place if it's the last allocation made by [`bump`] in the region.
+pub(crate) fn resize_last(
+    start: NonNull<u8>,
+    capacity: usize,
+    used: &AtomicUsize,
+    ptr: *mut (),
+    prev_size: usize,
+    new_size: usize,
+) -> bool {
+    let offset = (ptr as usize).wrapping_sub(start.as_ptr() as usize);
+    let (Some(prev_end), Some(new_end)) =
+        (offset.checked_add(prev_size), offset.checked_add(new_size))
+    else {
+        return false;
+    };
+    new_end <= capacity
+        && used
+            .compare_exchange(prev_end, new_end, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+}
+
+/// A handle to an [`Arena`], through which containers can allocate in it.
+///
+/// Freeing through this handle is a no-op: the memory is reclaimed when the arena is reset.
+#[crate::stabby]
+pub struct ArenaAlloc<'a, Alloc: IAlloc = DefaultAllocator> {
+    arena: &'a Arena<Alloc>,
+}
+impl<Alloc: IAlloc> Clone for ArenaAlloc<'_, Alloc> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<Alloc: IAlloc> Copy for ArenaAlloc<'_, Alloc> {}
+impl<'a, Alloc: IAlloc> ArenaAlloc<'a, Alloc> {
+    /// The arena this handle allocates in.
+    pub const fn arena(&self) -> &'a Arena<Alloc> {
+        self.arena
+    }
+}
+impl<Alloc: IAlloc> core::fmt::Debug for ArenaAlloc<'_, Alloc> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("ArenaAlloc").field(self.arena).finish()
+    }
+}
+impl<Alloc: IAlloc> IAlloc for ArenaAlloc<'_, Alloc> {
+    fn alloc(&mut self, layout: Layout) -> *mut () {
+        self.arena.bump(layout)
+    }
+    unsafe fn free(&mut self, _ptr: *mut ()) {}
+    unsafe fn realloc(&mut self, ptr: *mut (), prev_layout: Layout, new_size: usize) -> *mut () {
+        if new_size == 0 {
+            return core::ptr::null_mut();
+        }
+        let arena = self.arena;
+        if resize_last(
+            arena.start,
+            arena.capacity,
+            &arena.used,
+            ptr,
+            prev_layout.size,
+            new_size,
+        ) {
+            return ptr;
+        }
+        let ret = self.alloc(Layout {
+            size: new_size,
+            align: prev_layout.align,
+        });
+        if !ret.is_null() {
+            // SAFETY: both allocations are at least `min(prev_layout.size, new_size)` long, and don't overlap.
+            unsafe {
+                core::ptr::copy_nonoverlapping(
+                    ptr.cast::<u8>(),
+                    ret.cast::<u8>(),
+                    prev_layout.size.min(new_size),
+                )
+            }
+        }
+        ret
+    }
+    fn identity(&self) -> crate::alloc::AllocIdentity {
+        crate::alloc::AllocIdentity::new(
+            *b"Arena\0\0\0",
+            (self.arena as *const Arena<Alloc>).cast(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alloc::{boxed::Box, vec::Vec};
+
+    #[test]
+    fn arena() {
+        let _ = crate::AssertStable::<ArenaAlloc<'static>>(core::marker::PhantomData);
+        let mut arena = Arena::new(1024).unwrap();
+        {
+            let alloc = arena.allocator();
+            let boxed = Box::new_in(42u64, alloc);
+            assert_eq!(*boxed, 42);
+            assert_eq!(
+                (&*boxed as *const u64 as usize) % core::mem::align_of::<u64>(),
+                0
+            );
+            let mut vec = Vec::new_in(alloc);
+            for i in 0..32u32 {
+                vec.push(i);
+            }
+            assert!(vec.iter().copied().eq(0..32));
+            assert!(arena.used() > 0);
+            assert!(Vec::<u8, _>::try_with_capacity_in(2048, alloc).is_err());
+        }
+        arena.reset();
+        assert_eq!(arena.used(), 0);
+        assert_eq!(arena.remaining(), 1024);
+    }
+}
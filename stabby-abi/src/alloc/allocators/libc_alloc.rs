@@ -94,4 +94,8 @@ impl crate::alloc::IAlloc for LibcAlloc {
         }
         new_ptr.cast()
     }
+    fn identity(&self) -> crate::alloc::AllocIdentity {
+        // `libc`'s allocator is shared by every library of the process.
+        crate::alloc::AllocIdentity::new(*b"LibcAllo", core::ptr::null())
+    }
 }
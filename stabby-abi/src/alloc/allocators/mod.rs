@@ -10,6 +10,20 @@ mod rust_alloc;
 #[cfg(feature = "alloc-rs")]
 pub use rust_alloc::RustAlloc;
 
+/// A bump allocator, whose allocations are all freed at once.
+mod arena;
+pub use arena::{Arena, ArenaAlloc};
+
+/// An allocator wrapper that keeps statistics on its allocations.
+mod tracking;
+pub use tracking::{AllocStats, AllocTracker, TrackedAllocation, TrackingAlloc};
+
+#[cfg(all(feature = "libc", target_os = "linux"))]
+/// An allocator backed by a shared memory region, so that containers can be shared between processes.
+mod shm;
+#[cfg(all(feature = "libc", target_os = "linux"))]
+pub use shm::{SharedMemory, ShmAlloc, ShmError};
+
 #[cfg(stabby_default_alloc = "RustAlloc")]
 /// The default allocator, depending on which of the following is available:
 /// - RustAlloc: Rust's `GlobalAlloc`, through a vtable that ensures FFI-safety.
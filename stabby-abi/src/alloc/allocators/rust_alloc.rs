@@ -121,4 +121,9 @@ impl IAlloc for RustAlloc {
             unsafe { ptr.cast::<RustAllocPrefix>().sub(1).read() };
         (vtable.realloc)(ptr, layout, new_size)
     }
+
+    fn identity(&self) -> crate::alloc::AllocIdentity {
+        // Any `RustAlloc` can free the allocations of another, since they're freed through the vtable in their prefix.
+        crate::alloc::AllocIdentity::new(*b"RustAllo", core::ptr::null())
+    }
 }
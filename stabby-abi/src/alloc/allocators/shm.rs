@@ -0,0 +1,417 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   Pierre Avital, <pierre.avital@me.com>
+//
+
+use core::{
+    ptr::NonNull,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use super::arena::{bump, resize_last};
+use crate::alloc::{IAlloc, Layout};
+
+const MAGIC: u64 = u64::from_le_bytes(*b"stabbySH");
+
+/// The bookkeeping stored at the start of a shared memory region, so that every process mapping it shares it.
+#[crate::stabby]
+pub struct ShmHeader {
+    magic: u64,
+    /// The size of the whole region, including this header.
+    len: usize,
+    /// The number of bytes allocated after the header.
+    used: AtomicUsize,
+    /// The number of allocations that haven't been freed yet.
+    live: AtomicUsize,
+}
+impl ShmHeader {
+    /// The offset at which allocations start, leaving the header's cache line alone.
+    const DATA_OFFSET: usize = 64;
+}
+
+/// The ways creating or mapping a [`SharedMemory`] region can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShmError {
+    /// A system call failed.
+    Os {
+        /// The system call that failed.
+        operation: &'static str,
+        /// The `errno` it set.
+        errno: i32,
+    },
+    /// The region is too small to hold `stabby`'s bookkeeping.
+    TooSmall,
+    /// The file doesn't hold a region created by [`SharedMemory::create`].
+    InvalidRegion,
+}
+impl ShmError {
+    fn last_os_error(operation: &'static str) -> Self {
+        Self::Os {
+            operation,
+            // SAFETY: `__errno_location` always returns a valid pointer to the thread's `errno`.
+            errno: unsafe { *libc::__errno_location() },
+        }
+    }
+}
+impl core::fmt::Display for ShmError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Os { operation, errno } => write!(f, "{operation} failed with errno {errno}"),
+            Self::TooSmall => f.write_str("shared memory region too small"),
+            Self::InvalidRegion => f.write_str("file isn't a stabby shared memory region"),
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for ShmError {}
+
+/// A region of shared memory, backed by a `memfd` and mapped with `mmap`.
+///
+/// Containers built with its [`ShmAlloc`] allocate in the region, along with their allocator, so that another process
+/// mapping the same file at the same address (see [`SharedMemory::open`]) can read them as is, thanks to `stabby`'s
+/// layout guarantees. Objects are located in the region through their offset (see [`SharedMemory::offset_of`] and
+/// [`SharedMemory::get`]), which processes may exchange along with the file descriptor.
+///
+/// The allocator's bookkeeping lives in the region as well, so any process mapping it may allocate. Allocation is a
+/// lock-free bump: freeing only decrements the count of live allocations, and memory is reclaimed in bulk by
+/// [`SharedMemory::reset`].
+#[derive(Debug)]
+pub struct SharedMemory {
+    header: NonNull<ShmHeader>,
+    /// The length of the mapping, which is only trusted to match the header's once it's been validated.
+    len: usize,
+    fd: libc::c_int,
+}
+// SAFETY: The region's bookkeeping is only ever updated atomically.
+unsafe impl Send for SharedMemory {}
+// SAFETY: The region's bookkeeping is only ever updated atomically.
+unsafe impl Sync for SharedMemory {}
+impl SharedMemory {
+    /// Creates a new region of `len` bytes, mapped wherever the system sees fit.
+    ///
+    /// # Errors
+    /// If `len` is too small to hold the region's bookkeeping, or if a system call failed.
+    pub fn create(len: usize) -> Result<Self, ShmError> {
+        // SAFETY: no address is requested.
+        unsafe { Self::create_at(len, None) }
+    }
+    /// Creates a new region of `len` bytes, mapped at `addr` if specified.
+    ///
+    /// Mapping at a fixed address lets processes agree on it ahead of time, so that pointers stored in the region stay
+    /// valid in all of them.
+    ///
+    /// # Safety
+    /// `addr` must be page-aligned. The mapping will fail rather than replace an existing one.
+    ///
+    /// # Errors
+    /// If `len` is too small to hold the region's bookkeeping, or if a system call failed.
+    pub unsafe fn create_at(len: usize, addr: Option<NonNull<()>>) -> Result<Self, ShmError> {
+        if len < ShmHeader::DATA_OFFSET {
+            return Err(ShmError::TooSmall);
+        }
+        let size = libc::off_t::try_from(len).map_err(|_| ShmError::TooSmall)?;
+        // SAFETY: the name is nul-terminated.
+        #[allow(clippy::manual_c_str_literals)] // C-string literals require Rust 1.77
+        let fd = unsafe { libc::memfd_create(b"stabby\0".as_ptr().cast(), libc::MFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(ShmError::last_os_error("memfd_create"));
+        }
+        // SAFETY: `fd` is a valid file descriptor, which is closed on failure.
+        unsafe {
+            if libc::ftruncate(fd, size) != 0 {
+                let error = ShmError::last_os_error("ftruncate");
+                libc::close(fd);
+                return Err(error);
+            }
+            let header = match Self::map(fd, len, addr) {
+                Ok(header) => header,
+                Err(error) => {
+                    libc::close(fd);
+                    return Err(error);
+                }
+            };
+            header.as_ptr().write(ShmHeader {
+                magic: MAGIC,
+                len,
+                used: AtomicUsize::new(0),
+                live: AtomicUsize::new(0),
+            });
+            Ok(Self { header, len, fd })
+        }
+    }
+    /// Maps the region held by `fd`, which must have been created by [`SharedMemory::create`] (possibly in another
+    /// process), taking ownership of the file descriptor.
+    ///
+    /// When `addr` is the address the region is mapped at in the process that created it, containers stored in the
+    /// region can be used directly. Otherwise, only their offsets are meaningful.
+    ///
+    /// # Safety
+    /// `fd` must be a valid file descriptor, which will be closed when the region is dropped, and `addr` must be
+    /// page-aligned. The mapping will fail rather than replace an existing one.
+    ///
+    /// # Errors
+    /// If the file doesn't hold a valid region, or if a system call failed. `fd` is closed on failure.
+    pub unsafe fn open(fd: libc::c_int, addr: Option<NonNull<()>>) -> Result<Self, ShmError> {
+        // SAFETY: `fd` is valid, and is closed on failure (by dropping `this` once it's constructed).
+        unsafe {
+            let mut stat = core::mem::zeroed::<libc::stat>();
+            let len = if libc::fstat(fd, &mut stat) != 0 {
+                Err(ShmError::last_os_error("fstat"))
+            } else {
+                usize::try_from(stat.st_size)
+                    .ok()
+                    .filter(|len| *len >= ShmHeader::DATA_OFFSET)
+                    .ok_or(ShmError::InvalidRegion)
+            };
+            let (header, len) = match len.and_then(|len| Ok((Self::map(fd, len, addr)?, len))) {
+                Ok(mapped) => mapped,
+                Err(error) => {
+                    libc::close(fd);
+                    return Err(error);
+                }
+            };
+            let this = Self { header, len, fd };
+            if this.header().magic == MAGIC && this.header().len == len {
+                Ok(this)
+            } else {
+                Err(ShmError::InvalidRegion)
+            }
+        }
+    }
+    /// # Safety
+    /// `fd` must be a valid file descriptor of at least `len` bytes.
+    unsafe fn map(
+        fd: libc::c_int,
+        len: usize,
+        addr: Option<NonNull<()>>,
+    ) -> Result<NonNull<ShmHeader>, ShmError> {
+        let (addr, flags) = match addr {
+            Some(addr) => (
+                addr.as_ptr().cast(),
+                libc::MAP_SHARED | libc::MAP_FIXED_NOREPLACE,
+            ),
+            None => (core::ptr::null_mut(), libc::MAP_SHARED),
+        };
+        // SAFETY: guaranteed by the caller.
+        let ptr =
+            unsafe { libc::mmap(addr, len, libc::PROT_READ | libc::PROT_WRITE, flags, fd, 0) };
+        if ptr == libc::MAP_FAILED {
+            return Err(ShmError::last_os_error("mmap"));
+        }
+        if !addr.is_null() && ptr != addr {
+            // Kernels older than 4.17 ignore `MAP_FIXED_NOREPLACE`, treating `addr` as a hint.
+            // SAFETY: `ptr` was just mapped with this length.
+            unsafe { libc::munmap(ptr, len) };
+            return Err(ShmError::Os {
+                operation: "mmap",
+                errno: libc::EEXIST,
+            });
+        }
+        // SAFETY: `mmap` never returns null on success.
+        Ok(unsafe { NonNull::new_unchecked(ptr.cast()) })
+    }
+    const fn header(&self) -> &ShmHeader {
+        // SAFETY: the header stays mapped for as long as `self` lives.
+        unsafe { self.header.as_ref() }
+    }
+    /// The file descriptor backing the region, which may be sent to other processes to let them map it.
+    pub const fn fd(&self) -> libc::c_int {
+        self.fd
+    }
+    /// The address the region is mapped at in this process.
+    pub const fn as_ptr(&self) -> NonNull<()> {
+        self.header.cast()
+    }
+    /// The size of the region, in bytes.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+    /// Always `false`, since regions are at least large enough to hold their bookkeeping.
+    pub const fn is_empty(&self) -> bool {
+        false
+    }
+    /// The number of bytes used by allocations (and their alignment padding) since the last reset.
+    pub fn used(&self) -> usize {
+        self.header().used.load(Ordering::Acquire)
+    }
+    /// The number of allocations that haven't been freed yet, across all processes mapping the region.
+    pub fn live_allocations(&self) -> usize {
+        self.header().live.load(Ordering::Acquire)
+    }
+    /// Returns a handle to allocate in the region.
+    pub const fn allocator(&self) -> ShmAlloc<'_> {
+        ShmAlloc {
+            header: self.header(),
+        }
+    }
+    /// Returns the offset of `ptr` in the region, if it points into it.
+    pub fn offset_of<T: ?Sized>(&self, ptr: *const T) -> Option<usize> {
+        (ptr.cast::<u8>() as usize)
+            .checked_sub(self.header.as_ptr() as usize)
+            .filter(|offset| *offset < self.len())
+    }
+    /// Returns a reference to the `T` stored at `offset` in the region.
+    ///
+    /// Returns `None` if it wouldn't fit in the region, or if it would be misaligned.
+    ///
+    /// # Safety
+    /// A valid `T` must have been written at this offset. If `T` holds pointers (as containers do), the region must
+    /// be mapped at the same address as in the process that wrote it.
+    pub unsafe fn get<T>(&self, offset: usize) -> Option<&T> {
+        let end = offset.checked_add(core::mem::size_of::<T>())?;
+        if offset < ShmHeader::DATA_OFFSET || end > self.len() {
+            return None;
+        }
+        let ptr = self.header.as_ptr().cast::<u8>().wrapping_add(offset);
+        if (ptr as usize).rem_euclid(core::mem::align_of::<T>()) != 0 {
+            return None;
+        }
+        // SAFETY: the pointer is in bounds and aligned, and its validity is guaranteed by the caller.
+        Some(unsafe { &*ptr.cast::<T>() })
+    }
+    /// Frees every allocation made in the region at once.
+    ///
+    /// # Safety
+    /// No process may still be using any of the region's allocations.
+    pub unsafe fn reset(&mut self) {
+        let header = self.header();
+        header.used.store(0, Ordering::Release);
+        header.live.store(0, Ordering::Release);
+    }
+}
+impl Drop for SharedMemory {
+    fn drop(&mut self) {
+        // SAFETY: the region was mapped with this length, and `fd` is owned by `self`.
+        unsafe {
+            libc::munmap(self.header.as_ptr().cast(), self.len());
+            libc::close(self.fd);
+        }
+    }
+}
+
+/// A handle to a [`SharedMemory`] region, through which containers can allocate in it.
+///
+/// Since the allocator's bookkeeping is stored in the region, this handle is just a pointer to it: it stays valid in
+/// any process that maps the region at the same address.
+#[crate::stabby]
+#[derive(Clone, Copy)]
+pub struct ShmAlloc<'a> {
+    header: &'a ShmHeader,
+}
+impl ShmAlloc<'_> {
+    const fn start(&self) -> NonNull<u8> {
+        // SAFETY: the region is at least `DATA_OFFSET` long.
+        unsafe {
+            NonNull::new_unchecked(
+                (self.header as *const ShmHeader)
+                    .cast::<u8>()
+                    .add(ShmHeader::DATA_OFFSET)
+                    .cast_mut(),
+            )
+        }
+    }
+    const fn capacity(&self) -> usize {
+        self.header.len.saturating_sub(ShmHeader::DATA_OFFSET)
+    }
+}
+impl core::fmt::Debug for ShmAlloc<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("ShmAlloc")
+            .field(&(self.header as *const ShmHeader))
+            .finish()
+    }
+}
+impl IAlloc for ShmAlloc<'_> {
+    fn alloc(&mut self, layout: Layout) -> *mut () {
+        let ptr = bump(self.start(), self.capacity(), &self.header.used, layout);
+        if !ptr.is_null() {
+            self.header.live.fetch_add(1, Ordering::AcqRel);
+        }
+        ptr
+    }
+    unsafe fn free(&mut self, _ptr: *mut ()) {
+        self.header.live.fetch_sub(1, Ordering::AcqRel);
+    }
+    unsafe fn realloc(&mut self, ptr: *mut (), prev_layout: Layout, new_size: usize) -> *mut () {
+        if new_size == 0 {
+            return core::ptr::null_mut();
+        }
+        let (start, capacity) = (self.start(), self.capacity());
+        if resize_last(
+            start,
+            capacity,
+            &self.header.used,
+            ptr,
+            prev_layout.size,
+            new_size,
+        ) {
+            return ptr;
+        }
+        let ret = self.alloc(Layout {
+            size: new_size,
+            align: prev_layout.align,
+        });
+        if !ret.is_null() {
+            // SAFETY: both allocations are at least `min(prev_layout.size, new_size)` long, and don't overlap.
+            unsafe {
+                core::ptr::copy_nonoverlapping(
+                    ptr.cast::<u8>(),
+                    ret.cast::<u8>(),
+                    prev_layout.size.min(new_size),
+                );
+                self.free(ptr);
+            }
+        }
+        ret
+    }
+    fn identity(&self) -> crate::alloc::AllocIdentity {
+        crate::alloc::AllocIdentity::new(*b"ShmAlloc", (self.header as *const ShmHeader).cast())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alloc::{boxed::Box, vec::Vec};
+
+    #[test]
+    fn shared_memory() {
+        let _ = crate::AssertStable::<ShmAlloc<'static>>(core::marker::PhantomData);
+        let shm = SharedMemory::create(1 << 16).unwrap();
+        let mut vec = Vec::new_in(shm.allocator());
+        for i in 0..100u32 {
+            vec.push(i);
+        }
+        let boxed = Box::new_in(vec, shm.allocator());
+        let offset = shm.offset_of(&*boxed).unwrap();
+        assert_eq!(shm.live_allocations(), 2);
+        // Simulate another process by mapping the same file at the same address once this mapping is gone.
+        let addr = shm.as_ptr();
+        let fd = unsafe { libc::dup(shm.fd()) };
+        assert!(fd >= 0);
+        core::mem::forget(boxed);
+        drop(shm);
+        let shm = unsafe { SharedMemory::open(fd, Some(addr)) }.unwrap();
+        let vec = unsafe { shm.get::<Vec<u32, ShmAlloc>>(offset) }.unwrap();
+        assert!(vec.iter().copied().eq(0..100));
+        assert_eq!(shm.live_allocations(), 2);
+        // Mapping it again at the same address must fail rather than replace the existing mapping.
+        let fd = unsafe { libc::dup(shm.fd()) };
+        assert!(matches!(
+            unsafe { SharedMemory::open(fd, Some(addr)) },
+            Err(ShmError::Os {
+                operation: "mmap",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,403 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   Pierre Avital, <pierre.avital@me.com>
+//
+
+use core::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+use crate::alloc::{DefaultAllocator, IAlloc, Layout};
+
+/// The header [`TrackingAlloc`] prepends to each of its allocations.
+#[repr(C)]
+struct Header {
+    /// The offset between the start of the inner allocation and the pointer returned to the user.
+    offset: usize,
+    /// The size requested by the user.
+    size: usize,
+    prev: *mut Header,
+    next: *mut Header,
+}
+
+/// A snapshot of the statistics gathered by an [`AllocTracker`].
+#[crate::stabby]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AllocStats {
+    /// The number of allocations that haven't been freed yet.
+    pub live_allocations: usize,
+    /// The number of bytes requested by the allocations that haven't been freed yet.
+    pub live_bytes: usize,
+    /// The highest value `live_bytes` has reached.
+    pub peak_bytes: usize,
+    /// The number of allocations made since the tracker was created, including reallocations.
+    pub total_allocations: usize,
+}
+
+/// A live allocation, as listed by [`AllocTracker::for_each_allocation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TrackedAllocation {
+    /// The pointer returned by the allocator.
+    pub ptr: *const (),
+    /// The size that was requested for it.
+    pub size: usize,
+}
+
+/// The statistics shared by the [`TrackingAlloc`]s that refer to it.
+///
+/// Since it is ABI-stable and only ever updated atomically, a host may lend it to plugins (through a
+/// [`TrackingAlloc`]) to measure their memory usage separately.
+///
+/// When dropped with live allocations, it reports them as leaks on `stderr` (if the `std` feature is enabled).
+#[crate::stabby]
+pub struct AllocTracker {
+    live_allocations: AtomicUsize,
+    live_bytes: AtomicUsize,
+    peak_bytes: AtomicUsize,
+    total_allocations: AtomicUsize,
+    record: bool,
+    lock: AtomicBool,
+    head: AtomicPtr<()>,
+}
+// SAFETY: `head` is only ever accessed while holding `lock`.
+unsafe impl Send for AllocTracker {}
+// SAFETY: `head` is only ever accessed while holding `lock`.
+unsafe impl Sync for AllocTracker {}
+impl Default for AllocTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl AllocTracker {
+    /// Constructs a tracker that only counts allocations.
+    pub const fn new() -> Self {
+        Self::with_recording(false)
+    }
+    /// Constructs a tracker that also records each live allocation, so that they can be listed by
+    /// [`AllocTracker::for_each_allocation`] and reported as leaks.
+    ///
+    /// This makes allocations and frees contend on a lock.
+    pub const fn recording() -> Self {
+        Self::with_recording(true)
+    }
+    const fn with_recording(record: bool) -> Self {
+        Self {
+            live_allocations: AtomicUsize::new(0),
+            live_bytes: AtomicUsize::new(0),
+            peak_bytes: AtomicUsize::new(0),
+            total_allocations: AtomicUsize::new(0),
+            record,
+            lock: AtomicBool::new(false),
+            head: AtomicPtr::new(core::ptr::null_mut()),
+        }
+    }
+    /// Returns a snapshot of the tracker's statistics.
+    pub fn stats(&self) -> AllocStats {
+        AllocStats {
+            live_allocations: self.live_allocations.load(Ordering::Acquire),
+            live_bytes: self.live_bytes.load(Ordering::Acquire),
+            peak_bytes: self.peak_bytes.load(Ordering::Acquire),
+            total_allocations: self.total_allocations.load(Ordering::Acquire),
+        }
+    }
+    /// Whether or not the tracker records each live allocation.
+    pub const fn is_recording(&self) -> bool {
+        self.record
+    }
+    /// Calls `f` on each live allocation, if the tracker was constructed with [`AllocTracker::recording`].
+    ///
+    /// Allocations are blocked while this runs, so `f` must not allocate through this tracker.
+    pub fn for_each_allocation(&self, mut f: impl FnMut(TrackedAllocation)) {
+        if !self.record {
+            return;
+        }
+        self.locked(|| {
+            let mut header = self.head.load(Ordering::Relaxed).cast::<Header>();
+            while let Some(current) = unsafe { header.as_ref() } {
+                f(TrackedAllocation {
+                    ptr: (header as *const Header).wrapping_add(1).cast(),
+                    size: current.size,
+                });
+                header = current.next;
+            }
+        })
+    }
+    fn locked<T>(&self, f: impl FnOnce() -> T) -> T {
+        let _guard = TrackerGuard::lock(&self.lock);
+        f()
+    }
+    /// # Safety
+    /// `header` must be a valid header for a live allocation that isn't tracked yet.
+    unsafe fn track(&self, header: *mut Header) {
+        // SAFETY: guaranteed by the caller.
+        let size = unsafe { (*header).size };
+        self.live_allocations.fetch_add(1, Ordering::AcqRel);
+        self.total_allocations.fetch_add(1, Ordering::AcqRel);
+        let live = self
+            .live_bytes
+            .fetch_add(size, Ordering::AcqRel)
+            .saturating_add(size);
+        self.peak_bytes.fetch_max(live, Ordering::AcqRel);
+        if self.record {
+            self.locked(|| {
+                let head = self.head.load(Ordering::Relaxed).cast::<Header>();
+                // SAFETY: `header` is valid, and `head` is either null or a valid header, both protected by the lock.
+                unsafe {
+                    (*header).next = head;
+                    if let Some(head) = head.as_mut() {
+                        head.prev = header;
+                    }
+                }
+                self.head.store(header.cast(), Ordering::Relaxed);
+            })
+        }
+    }
+    /// # Safety
+    /// `header` must be a valid header for a live allocation that was tracked by `self`.
+    unsafe fn untrack(&self, header: *mut Header) {
+        // SAFETY: guaranteed by the caller.
+        let size = unsafe { (*header).size };
+        self.live_allocations.fetch_sub(1, Ordering::AcqRel);
+        self.live_bytes.fetch_sub(size, Ordering::AcqRel);
+        if self.record {
+            self.locked(|| {
+                // SAFETY: `header` and its neighbours are valid headers, protected by the lock.
+                unsafe {
+                    let Header { prev, next, .. } = *header;
+                    if let Some(next) = next.as_mut() {
+                        next.prev = prev;
+                    }
+                    match prev.as_mut() {
+                        Some(prev) => prev.next = next,
+                        None => self.head.store(next.cast(), Ordering::Relaxed),
+                    }
+                }
+            })
+        }
+    }
+}
+/// Holds an [`AllocTracker`]'s lock until dropped, so that it's released even if `for_each_allocation`'s callback panics.
+struct TrackerGuard<'a>(&'a AtomicBool);
+impl<'a> TrackerGuard<'a> {
+    fn lock(lock: &'a AtomicBool) -> Self {
+        while lock
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        Self(lock)
+    }
+}
+impl Drop for TrackerGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+impl core::fmt::Debug for AllocTracker {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AllocTracker")
+            .field("stats", &self.stats())
+            .field("record", &self.record)
+            .finish()
+    }
+}
+impl core::fmt::Display for AllocTracker {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let AllocStats {
+            live_allocations,
+            live_bytes,
+            peak_bytes,
+            total_allocations,
+        } = self.stats();
+        write!(f, "{live_allocations} live allocations ({live_bytes} bytes, peaked at {peak_bytes} bytes), {total_allocations} allocations in total")?;
+        let mut result = Ok(());
+        self.for_each_allocation(|TrackedAllocation { ptr, size }| {
+            if result.is_ok() {
+                result = write!(f, "\n\t{ptr:?}: {size} bytes");
+            }
+        });
+        result
+    }
+}
+impl Drop for AllocTracker {
+    fn drop(&mut self) {
+        #[cfg(feature = "std")]
+        if self.live_allocations.load(Ordering::Acquire) != 0 {
+            std::eprintln!("stabby: AllocTracker dropped with leaks: {self}");
+        }
+    }
+}
+
+/// An allocator that counts the allocations made through it in an [`AllocTracker`], delegating them to `Alloc`.
+///
+/// Each allocation is prefixed with a small header, which lets frees be tracked without knowing their layout.
+#[crate::stabby]
+pub struct TrackingAlloc<'a, Alloc: IAlloc = DefaultAllocator> {
+    inner: Alloc,
+    tracker: &'a AllocTracker,
+}
+impl<'a, Alloc: IAlloc> TrackingAlloc<'a, Alloc> {
+    /// Constructs an allocator that delegates to `inner`, counting its allocations in `tracker`.
+    pub const fn new(inner: Alloc, tracker: &'a AllocTracker) -> Self {
+        Self { inner, tracker }
+    }
+    /// The tracker this allocator reports to.
+    pub const fn tracker(&self) -> &'a AllocTracker {
+        self.tracker
+    }
+    /// The allocator this delegates to.
+    pub const fn inner(&self) -> &Alloc {
+        &self.inner
+    }
+    /// The layout of the inner allocation, and the offset at which the user's pointer starts in it.
+    fn inner_layout(layout: Layout) -> Option<(Layout, usize)> {
+        let align = layout.align.max(core::mem::align_of::<Header>());
+        let header = core::mem::size_of::<Header>();
+        let offset = header
+            .checked_add(align.wrapping_sub(1))?
+            .checked_div(align)?
+            .checked_mul(align)?;
+        Some((
+            Layout {
+                size: offset.checked_add(layout.size)?,
+                align,
+            },
+            offset,
+        ))
+    }
+}
+impl<Alloc: IAlloc + Clone> Clone for TrackingAlloc<'_, Alloc> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            tracker: self.tracker,
+        }
+    }
+}
+impl<Alloc: IAlloc + Copy> Copy for TrackingAlloc<'_, Alloc> {}
+impl<Alloc: IAlloc + core::fmt::Debug> core::fmt::Debug for TrackingAlloc<'_, Alloc> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TrackingAlloc")
+            .field("inner", &self.inner)
+            .field("tracker", self.tracker)
+            .finish()
+    }
+}
+impl<Alloc: IAlloc> IAlloc for TrackingAlloc<'_, Alloc> {
+    fn alloc(&mut self, layout: Layout) -> *mut () {
+        if layout.size == 0 {
+            return core::ptr::null_mut();
+        }
+        let Some((inner_layout, offset)) = Self::inner_layout(layout) else {
+            return core::ptr::null_mut();
+        };
+        let start = self.inner.alloc(inner_layout);
+        if start.is_null() {
+            return start;
+        }
+        // SAFETY: the inner allocation is `offset + layout.size` long, and `offset` leaves room for the header.
+        unsafe {
+            let ret = start.cast::<u8>().add(offset);
+            let header = ret.cast::<Header>().sub(1);
+            header.write(Header {
+                offset,
+                size: layout.size,
+                prev: core::ptr::null_mut(),
+                next: core::ptr::null_mut(),
+            });
+            self.tracker.track(header);
+            ret.cast()
+        }
+    }
+    unsafe fn free(&mut self, ptr: *mut ()) {
+        // SAFETY: `ptr` was allocated by `self.alloc`, which placed a header right before it.
+        unsafe {
+            let header = ptr.cast::<Header>().sub(1);
+            self.tracker.untrack(header);
+            let offset = (*header).offset;
+            self.inner.free(ptr.cast::<u8>().sub(offset).cast())
+        }
+    }
+    unsafe fn realloc(&mut self, ptr: *mut (), prev_layout: Layout, new_size: usize) -> *mut () {
+        if new_size == 0 {
+            return core::ptr::null_mut();
+        }
+        let ret = self.alloc(Layout {
+            size: new_size,
+            align: prev_layout.align,
+        });
+        if !ret.is_null() {
+            // SAFETY: `ptr` was allocated by `self.alloc`, with a header recording its size.
+            unsafe {
+                let prev_size = (*ptr.cast::<Header>().sub(1)).size;
+                core::ptr::copy_nonoverlapping(
+                    ptr.cast::<u8>(),
+                    ret.cast::<u8>(),
+                    prev_size.min(new_size),
+                );
+                self.free(ptr);
+            }
+        }
+        ret
+    }
+    fn identity(&self) -> crate::alloc::AllocIdentity {
+        crate::alloc::AllocIdentity::new(*b"Tracking", (self.tracker as *const AllocTracker).cast())
+    }
+}
+
+#[cfg(all(test, not(stabby_default_alloc = "disabled")))]
+mod tests {
+    use super::*;
+    use crate::alloc::{boxed::Box, vec::Vec};
+
+    #[test]
+    fn tracking() {
+        let _ = crate::AssertStable::<TrackingAlloc<'static>>(core::marker::PhantomData);
+        let tracker = AllocTracker::recording();
+        let alloc = TrackingAlloc::new(DefaultAllocator::default(), &tracker);
+        let boxed = Box::new_in(42u128, alloc);
+        let mut vec = Vec::new_in(alloc);
+        for i in 0..100u32 {
+            vec.push(i);
+        }
+        let stats = tracker.stats();
+        assert_eq!(stats.live_allocations, 2);
+        assert!(stats.total_allocations > 2);
+        assert!(stats.peak_bytes >= stats.live_bytes);
+        let mut sizes = Vec::new();
+        tracker.for_each_allocation(|allocation| sizes.push(allocation.size));
+        assert_eq!(sizes.len(), 2);
+        assert_eq!(sizes.iter().sum::<usize>(), stats.live_bytes);
+        assert_eq!(*boxed, 42);
+        assert!(vec.iter().copied().eq(0..100));
+        drop(boxed);
+        drop(vec);
+        let stats = tracker.stats();
+        assert_eq!((stats.live_allocations, stats.live_bytes), (0, 0));
+        tracker.for_each_allocation(|_| panic!("no allocation should be live"));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn panicking_callback_releases_the_lock() {
+        let tracker = AllocTracker::recording();
+        let alloc = TrackingAlloc::new(DefaultAllocator::default(), &tracker);
+        let boxed = Box::new_in(1u32, alloc);
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            tracker.for_each_allocation(|_| panic!("callback panicked"))
+        }));
+        assert!(panicked.is_err());
+        let other = Box::new_in(2u32, alloc);
+        assert_eq!(tracker.stats().live_allocations, 2);
+        drop((boxed, other));
+        assert_eq!(tracker.stats().live_allocations, 0);
+    }
+}
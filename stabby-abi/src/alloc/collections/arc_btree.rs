@@ -1,13 +1,38 @@
-use core::{borrow::Borrow, mem::MaybeUninit, ops::Deref, ptr::NonNull, sync::atomic::AtomicPtr};
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ops::{Bound, Deref, RangeBounds},
+    ptr::NonNull,
+    sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering as AtomicOrdering},
+};
 
 use crate::alloc::{sync::Arc, DefaultAllocator, IAlloc};
 
 /// An [`ArcBTreeSet`] that can be atomically modified.
-pub struct AtomicArcBTreeSet<T: Ord, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize>(
-    AtomicPtr<ArcBTreeSetNodeInner<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT>>,
-)
+///
+/// Reads are lock-free: [`Self::load`] returns an owned snapshot of the set, which stays valid and unchanged
+/// regardless of later modifications. Writers replace the whole set atomically, in RCU fashion: only the nodes
+/// they modify are copied, and the previous snapshot is released once no [`Self::load`] may still be acquiring it.
+///
+/// Loads are counted in one of two slots, designated by the parity of a generation counter. To release a root,
+/// a writer bumps the generation and waits for the previous slot to drain, twice: new loads count themselves
+/// in the other slot, so a writer only ever waits for the loads that were in progress when it started.
+#[crate::stabby]
+pub struct AtomicArcBTreeSet<T: Ord, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize>
 where
-    DefaultAllocator: core::default::Default + IAlloc;
+    DefaultAllocator: core::default::Default + IAlloc,
+{
+    root: AtomicPtr<ArcBTreeSetNodeInner<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT>>,
+    /// The number of [`Self::load`] calls in progress, in the slot designated by the generation they started in.
+    loading: [AtomicUsize; 2],
+    /// The generation whose parity designates the slot of `loading` that new [`Self::load`] calls count themselves in.
+    generation: AtomicUsize,
+    /// Set while a writer is releasing a root, since generations must only be bumped by one writer at a time.
+    releasing: AtomicBool,
+    marker: PhantomData<ArcBTreeSet<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT>>,
+}
 impl<T: Ord + Clone, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize> Default
     for AtomicArcBTreeSet<T, REPLACE_ON_INSERT, SPLIT_LIMIT>
 where
@@ -17,6 +42,25 @@ where
         Self::new()
     }
 }
+impl<T: Ord, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize> Drop
+    for AtomicArcBTreeSet<T, REPLACE_ON_INSERT, SPLIT_LIMIT>
+where
+    DefaultAllocator: core::default::Default + IAlloc,
+{
+    fn drop(&mut self) {
+        if let Some(root) = NonNull::new(*self.root.get_mut()) {
+            // SAFETY: the root was owned by `self`, which can't be loaded from anymore.
+            drop(unsafe {
+                core::mem::transmute::<
+                    NonNull<
+                        ArcBTreeSetNodeInner<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT>,
+                    >,
+                    ArcBTreeSetNode<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT>,
+                >(root)
+            })
+        }
+    }
+}
 impl<T: Ord + Clone, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize>
     AtomicArcBTreeSet<T, REPLACE_ON_INSERT, SPLIT_LIMIT>
 where
@@ -24,49 +68,237 @@ where
 {
     /// Constructs a new, empty set.
     pub const fn new() -> Self {
-        Self(AtomicPtr::new(core::ptr::null_mut()))
+        Self {
+            root: AtomicPtr::new(core::ptr::null_mut()),
+            loading: [AtomicUsize::new(0), AtomicUsize::new(0)],
+            generation: AtomicUsize::new(0),
+            releasing: AtomicBool::new(false),
+            marker: PhantomData,
+        }
+    }
+    /// Returns the slot of `loading` designated by `generation`.
+    const fn loading(&self, generation: usize) -> &AtomicUsize {
+        let [even, odd] = &self.loading;
+        if generation % 2 == 0 {
+            even
+        } else {
+            odd
+        }
+    }
+    /// Returns a snapshot of the set's current value.
+    ///
+    /// The snapshot shares its nodes with the set, and isn't affected by later modifications of the set.
+    pub fn load(&self) -> ArcBTreeSet<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT> {
+        let loading = loop {
+            let generation = self.generation.load(AtomicOrdering::SeqCst);
+            let loading = self.loading(generation);
+            loading.fetch_add(1, AtomicOrdering::SeqCst);
+            if self.generation.load(AtomicOrdering::SeqCst) == generation {
+                break loading;
+            }
+            // A writer may have already checked this slot: count this load in the new generation's instead.
+            loading.fetch_sub(1, AtomicOrdering::Release);
+        };
+        let set = ArcBTreeSet::copy_from_ptr(self.root.load(AtomicOrdering::SeqCst));
+        loading.fetch_sub(1, AtomicOrdering::Release);
+        set
     }
     /// Applies `f` to the current value, swapping the current value for the one returned by `f`.
     ///
     /// If the value has changed in the meantime, the result of `f` is discarded and `f` is called again on the new value.
     pub fn edit(
         &self,
-        mut f: impl FnMut(
+        f: impl FnMut(
             ArcBTreeSet<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT>,
         ) -> ArcBTreeSet<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT>,
     ) {
-        let mut current = self.0.load(core::sync::atomic::Ordering::Acquire);
+        self.update(f);
+    }
+    /// Applies `f` to a snapshot of the current value, and replaces the current value with the one returned by `f`,
+    /// returning the value it replaced.
+    ///
+    /// If the value has changed in the meantime, the result of `f` is discarded and `f` is called again on a new snapshot.
+    /// If `f` returns its argument unchanged, the set is left untouched.
+    pub fn update(
+        &self,
+        mut f: impl FnMut(
+            ArcBTreeSet<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT>,
+        ) -> ArcBTreeSet<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT>,
+    ) -> ArcBTreeSet<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT> {
         loop {
-            let new = f(ArcBTreeSet::copy_from_ptr(current));
-            let new_ptr = new.as_ptr();
-            if core::ptr::eq(current, new_ptr) {
-                return;
+            // `current` is kept alive until the exchange, so that its root can't be freed and reused by another value.
+            let current = self.load();
+            let new = f(current.clone());
+            if core::ptr::eq(current.as_ptr(), new.as_ptr()) {
+                return current;
             }
-            match self.0.compare_exchange(
-                current,
-                new_ptr,
-                core::sync::atomic::Ordering::Release,
-                core::sync::atomic::Ordering::Acquire,
-            ) {
-                // SAFETY: we've just replaced the old pointer succesfully, we can now free it.
-                Ok(old) => unsafe {
-                    core::mem::forget(new);
-                    ArcBTreeSet::take_ownership_from_ptr(old);
-                    return;
-                },
-                Err(new_old) => {
-                    current = new_old;
-                }
+            if let Ok(previous) = self.compare_and_swap(&current, new) {
+                return previous;
+            }
+        }
+    }
+    /// Replaces the current value with `new` if it is still `current` (as compared by identity of their roots).
+    ///
+    /// # Errors
+    /// If the current value isn't `current`, `new` is given back and the set is left untouched.
+    pub fn compare_and_swap(
+        &self,
+        current: &ArcBTreeSet<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT>,
+        new: ArcBTreeSet<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT>,
+    ) -> Result<
+        ArcBTreeSet<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT>,
+        ArcBTreeSet<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT>,
+    > {
+        match self.root.compare_exchange(
+            current.as_ptr(),
+            new.as_ptr(),
+            AtomicOrdering::SeqCst,
+            AtomicOrdering::SeqCst,
+        ) {
+            Ok(previous) => {
+                core::mem::forget(new);
+                // SAFETY: `previous` was owned by `self`, and has just been replaced.
+                Ok(unsafe { self.release(previous) })
+            }
+            Err(_) => Err(new),
+        }
+    }
+    /// Replaces the current value with `new`, returning the previous value.
+    pub fn swap(
+        &self,
+        new: ArcBTreeSet<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT>,
+    ) -> ArcBTreeSet<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT> {
+        let previous = self.root.swap(new.as_ptr(), AtomicOrdering::SeqCst);
+        core::mem::forget(new);
+        // SAFETY: `previous` was owned by `self`, and has just been replaced.
+        unsafe { self.release(previous) }
+    }
+    /// Replaces the current value with `new`.
+    pub fn store(&self, new: ArcBTreeSet<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT>) {
+        drop(self.swap(new))
+    }
+    /// Takes ownership of a root that has been replaced, once no [`Self::load`] may still be acquiring it.
+    ///
+    /// # Safety
+    /// `previous` must have been owned by `self`, and must have been replaced.
+    unsafe fn release(
+        &self,
+        previous: *mut ArcBTreeSetNodeInner<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT>,
+    ) -> ArcBTreeSet<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT> {
+        // A `load` that started before the replacement may have read `previous` without having incremented its reference count yet.
+        // It counted itself in the slot of the generation it started in, which may be either of them: bumping the generation
+        // steers new loads to the other slot, so that waiting for each slot in turn only waits for the loads already in progress.
+        while self
+            .releasing
+            .compare_exchange_weak(
+                false,
+                true,
+                AtomicOrdering::Acquire,
+                AtomicOrdering::Relaxed,
+            )
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        for _ in 0..2 {
+            let generation = self.generation.fetch_add(1, AtomicOrdering::SeqCst);
+            while self.loading(generation).load(AtomicOrdering::SeqCst) != 0 {
+                core::hint::spin_loop();
             }
         }
+        self.releasing.store(false, AtomicOrdering::Release);
+        // SAFETY: ensured by the caller.
+        unsafe { ArcBTreeSet::take_ownership_from_ptr(previous) }
     }
     /// Calls `f` with the current value of in the set associated with `value`.
     pub fn get<K>(&self, value: &K, f: impl FnOnce(Option<&T>))
     where
         T: PartialOrd<K>,
     {
-        let set = ArcBTreeSet::copy_from_ptr(self.0.load(core::sync::atomic::Ordering::Relaxed));
-        f(set.get(value))
+        f(self.load().get(value))
+    }
+}
+
+/// An [`ArcBTreeMap`] that can be atomically modified.
+///
+/// Reads are lock-free: [`Self::load`] returns an owned snapshot of the map, which stays valid and unchanged
+/// regardless of later modifications. Writers replace the whole map atomically, in RCU fashion: only the nodes
+/// they modify are copied, and the previous snapshot is released once no [`Self::load`] may still be acquiring it.
+///
+/// This makes it suitable to share a live configuration between a host and its plugins:
+/// readers keep working on consistent snapshots while writers publish new versions.
+#[crate::stabby]
+pub struct AtomicArcBTreeMap<K: Ord, V, const SPLIT_LIMIT: usize = { 5 }>
+where
+    DefaultAllocator: core::default::Default + IAlloc,
+{
+    set: AtomicArcBTreeSet<Entry<K, V>, true, SPLIT_LIMIT>,
+}
+impl<K: Ord + Clone, V: Clone, const SPLIT_LIMIT: usize> Default
+    for AtomicArcBTreeMap<K, V, SPLIT_LIMIT>
+where
+    DefaultAllocator: core::default::Default + IAlloc,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<K: Ord + Clone, V: Clone, const SPLIT_LIMIT: usize> AtomicArcBTreeMap<K, V, SPLIT_LIMIT>
+where
+    DefaultAllocator: core::default::Default + IAlloc,
+{
+    /// Constructs a new, empty map.
+    pub const fn new() -> Self {
+        Self {
+            set: AtomicArcBTreeSet::new(),
+        }
+    }
+    /// Returns a snapshot of the map's current value.
+    ///
+    /// The snapshot shares its nodes with the map, and isn't affected by later modifications of the map.
+    pub fn load(&self) -> ArcBTreeMap<K, V, DefaultAllocator, SPLIT_LIMIT> {
+        ArcBTreeMap(self.set.load())
+    }
+    /// Applies `f` to a snapshot of the current value, and replaces the current value with the one returned by `f`,
+    /// returning the value it replaced.
+    ///
+    /// If the value has changed in the meantime, the result of `f` is discarded and `f` is called again on a new snapshot.
+    /// If `f` returns its argument unchanged, the map is left untouched.
+    pub fn update(
+        &self,
+        mut f: impl FnMut(
+            ArcBTreeMap<K, V, DefaultAllocator, SPLIT_LIMIT>,
+        ) -> ArcBTreeMap<K, V, DefaultAllocator, SPLIT_LIMIT>,
+    ) -> ArcBTreeMap<K, V, DefaultAllocator, SPLIT_LIMIT> {
+        ArcBTreeMap(self.set.update(|set| f(ArcBTreeMap(set)).0))
+    }
+    /// Replaces the current value with `new` if it is still `current` (as compared by identity of their roots).
+    ///
+    /// # Errors
+    /// If the current value isn't `current`, `new` is given back and the map is left untouched.
+    pub fn compare_and_swap(
+        &self,
+        current: &ArcBTreeMap<K, V, DefaultAllocator, SPLIT_LIMIT>,
+        new: ArcBTreeMap<K, V, DefaultAllocator, SPLIT_LIMIT>,
+    ) -> Result<
+        ArcBTreeMap<K, V, DefaultAllocator, SPLIT_LIMIT>,
+        ArcBTreeMap<K, V, DefaultAllocator, SPLIT_LIMIT>,
+    > {
+        self.set
+            .compare_and_swap(&current.0, new.0)
+            .map(ArcBTreeMap)
+            .map_err(ArcBTreeMap)
+    }
+    /// Replaces the current value with `new`, returning the previous value.
+    pub fn swap(
+        &self,
+        new: ArcBTreeMap<K, V, DefaultAllocator, SPLIT_LIMIT>,
+    ) -> ArcBTreeMap<K, V, DefaultAllocator, SPLIT_LIMIT> {
+        ArcBTreeMap(self.set.swap(new.0))
+    }
+    /// Replaces the current value with `new`.
+    pub fn store(&self, new: ArcBTreeMap<K, V, DefaultAllocator, SPLIT_LIMIT>) {
+        self.set.store(new.0)
     }
 }
 
@@ -104,14 +336,33 @@ impl<K: Ord, V> Ord for Entry<K, V> {
         self.key.cmp(&other.key)
     }
 }
+impl<K, V> Entry<K, V> {
+    const fn as_pair(&self) -> (&K, &V) {
+        (&self.key, &self.value)
+    }
+    fn into_pair(self) -> (K, V) {
+        (self.key, self.value)
+    }
+}
 /// A shareable BTree Map based on copy-on-write semantics.
 ///
 /// When inserting a value, all shared nodes on the path to the value's slot will be cloned if shared before being mutated.
+/// The same goes for the nodes on the path to a value that is removed or mutably borrowed,
+/// as well as their siblings if the tree needs to be rebalanced: all other nodes stay shared.
 #[crate::stabby]
 #[derive(Clone)]
 pub struct ArcBTreeMap<K, V, Alloc: IAlloc = DefaultAllocator, const SPLIT_LIMIT: usize = { 5 }>(
     ArcBTreeSet<Entry<K, V>, Alloc, true, SPLIT_LIMIT>,
 );
+impl<K: Ord, V> ArcBTreeMap<K, V>
+where
+    DefaultAllocator: IAlloc,
+{
+    /// Constructs an empty map in the [`DefaultAllocator`]
+    pub const fn new() -> Self {
+        Self::from_alloc(DefaultAllocator::new())
+    }
+}
 impl<K: Ord, V, Alloc: IAlloc, const SPLIT_LIMIT: usize> ArcBTreeMap<K, V, Alloc, SPLIT_LIMIT> {
     /// Constructs a new map, using the provided allocator.
     ///
@@ -126,8 +377,41 @@ impl<K: Ord, V, Alloc: IAlloc, const SPLIT_LIMIT: usize> ArcBTreeMap<K, V, Alloc
         Self(ArcBTreeSet::from_alloc(alloc))
     }
     /// Returns the value associated to `key`
-    pub fn get<Q: Borrow<K>>(&self, key: &Q) -> Option<&V> {
-        self.0.get(key.borrow()).map(|entry| &entry.value)
+    pub fn get<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.get_key_value(key).map(|(_, value)| value)
+    }
+    /// Returns the key-value pair associated to `key`
+    pub fn get_key_value<Q: ?Sized + Ord>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+    {
+        self.0
+            .get_by(|entry| entry.key.borrow().cmp(key))
+            .map(Entry::as_pair)
+    }
+    /// Returns `true` if the map contains a value for `key`.
+    pub fn contains_key<Q: ?Sized + Ord>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.get_key_value(key).is_some()
+    }
+    /// Returns a mutable reference to the value associated to `key`.
+    ///
+    /// If some nodes on the way to `key` are shared, they will be shallowly cloned, like in [`Self::insert`].
+    /// No node is cloned if `key` isn't in the map.
+    pub fn get_mut<Q: ?Sized + Ord>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q> + Clone,
+        V: Clone,
+        Alloc: Clone,
+    {
+        self.0
+            .get_mut_by(|entry| entry.key.borrow().cmp(key))
+            .map(|entry| &mut entry.value)
     }
     /// Associates `value` to `key`,
     ///
@@ -144,11 +428,287 @@ impl<K: Ord, V, Alloc: IAlloc, const SPLIT_LIMIT: usize> ArcBTreeMap<K, V, Alloc
     {
         self.0.insert(Entry { key, value }).map(|entry| entry.value)
     }
+    /// Removes the value associated to `key` from the map, returning it.
+    ///
+    /// The nodes on the way to `key` are cloned if shared, like in [`Self::insert`].
+    /// No node is cloned if `key` isn't in the map.
+    pub fn remove<Q: ?Sized + Ord>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q> + Clone,
+        V: Clone,
+        Alloc: Clone,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+    /// Removes the key-value pair associated to `key` from the map, returning it.
+    ///
+    /// The nodes on the way to `key` are cloned if shared, like in [`Self::insert`].
+    /// No node is cloned if `key` isn't in the map.
+    pub fn remove_entry<Q: ?Sized + Ord>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q> + Clone,
+        V: Clone,
+        Alloc: Clone,
+    {
+        self.0
+            .remove_by(|entry| entry.key.borrow().cmp(key))
+            .map(Entry::into_pair)
+    }
+    /// Gets the entry corresponding to `key`, for in-place manipulation.
+    ///
+    /// The entry remembers where the key was found, so that using it doesn't need to look the key up again.
+    pub fn entry(&mut self, key: K) -> MapEntry<'_, K, V, Alloc, SPLIT_LIMIT> {
+        match self.0.position_by(|entry| entry.key.cmp(&key)) {
+            Some(position) => MapEntry::Occupied(OccupiedEntry {
+                map: self,
+                position,
+            }),
+            None => MapEntry::Vacant(VacantEntry { map: self, key }),
+        }
+    }
+    /// Returns the key-value pair with the smallest key.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.0.first().map(Entry::as_pair)
+    }
+    /// Returns the key-value pair with the greatest key.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.0.last().map(Entry::as_pair)
+    }
+    /// Removes and returns the key-value pair with the smallest key.
+    pub fn pop_first(&mut self) -> Option<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+        Alloc: Clone,
+    {
+        self.0.pop_first().map(Entry::into_pair)
+    }
+    /// Removes and returns the key-value pair with the greatest key.
+    pub fn pop_last(&mut self) -> Option<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+        Alloc: Clone,
+    {
+        self.0.pop_last().map(Entry::into_pair)
+    }
+    /// Iterates over the map's key-value pairs, sorted by key.
+    ///
+    /// Each step costs `O(1)` amortized. The iterator's [`Iterator::size_hint`] is exact, but counting the remaining
+    /// pairs visits each of the nodes they span.
+    pub fn iter(&self) -> MapIter<'_, K, V, Alloc, SPLIT_LIMIT> {
+        MapIter(self.0.iter())
+    }
+    /// Iterates over the map's keys, in order.
+    pub fn keys(&self) -> Keys<'_, K, V, Alloc, SPLIT_LIMIT> {
+        Keys(self.iter())
+    }
+    /// Iterates over the map's values, sorted by key.
+    pub fn values(&self) -> Values<'_, K, V, Alloc, SPLIT_LIMIT> {
+        Values(self.iter())
+    }
+    /// Iterates over the key-value pairs whose keys are within `range`, sorted by key.
+    ///
+    /// The iterator is empty if the range's start is greater than its end.
+    pub fn range<Q: ?Sized + Ord, R: RangeBounds<Q>>(
+        &self,
+        range: R,
+    ) -> MapIter<'_, K, V, Alloc, SPLIT_LIMIT>
+    where
+        K: Borrow<Q>,
+    {
+        MapIter(self.0.range_by(
+            |entry| is_after(entry.key.borrow(), range.start_bound()),
+            |entry| is_before(entry.key.borrow(), range.end_bound()),
+        ))
+    }
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    /// Returns `true` iff the map is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// Removes all the elements from the map.
+    pub fn clear(&mut self)
+    where
+        Alloc: Clone,
+    {
+        self.0.clear()
+    }
+    /// Removes the key-value pairs for which `f` returns `false`.
+    ///
+    /// Unlike [`std::collections::BTreeMap::retain`], `f` can't mutate the values, as that would require cloning every shared node.
+    pub fn retain(&mut self, mut f: impl FnMut(&K, &V) -> bool)
+    where
+        K: Clone,
+        V: Clone,
+        Alloc: Clone,
+    {
+        self.0.retain(|entry| f(&entry.key, &entry.value))
+    }
 }
 
-/// A shareable BTree Map based on copy-on-write semantics.
+/// A view into a single entry of an [`ArcBTreeMap`], obtained through [`ArcBTreeMap::entry`].
+pub enum MapEntry<'a, K: Ord, V, Alloc: IAlloc, const SPLIT_LIMIT: usize> {
+    /// The map contains a value for this key.
+    Occupied(OccupiedEntry<'a, K, V, Alloc, SPLIT_LIMIT>),
+    /// The map doesn't contain a value for this key.
+    Vacant(VacantEntry<'a, K, V, Alloc, SPLIT_LIMIT>),
+}
+impl<K: Ord, V, Alloc: IAlloc, const SPLIT_LIMIT: usize> MapEntry<'_, K, V, Alloc, SPLIT_LIMIT> {
+    /// Returns the entry's key.
+    pub fn key(&self) -> &K {
+        match self {
+            MapEntry::Occupied(entry) => entry.key(),
+            MapEntry::Vacant(entry) => entry.key(),
+        }
+    }
+}
+impl<'a, K: Ord + Clone, V: Clone, Alloc: IAlloc + Clone, const SPLIT_LIMIT: usize>
+    MapEntry<'a, K, V, Alloc, SPLIT_LIMIT>
+{
+    /// Inserts `default` if the entry is vacant, returning a mutable reference to the entry's value.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+    /// Inserts the result of `default` if the entry is vacant, returning a mutable reference to the entry's value.
+    pub fn or_insert_with(self, default: impl FnOnce() -> V) -> &'a mut V {
+        self.or_insert_with_key(|_| default())
+    }
+    /// Inserts the result of `default` if the entry is vacant, returning a mutable reference to the entry's value.
+    pub fn or_insert_with_key(self, default: impl FnOnce(&K) -> V) -> &'a mut V {
+        match self {
+            MapEntry::Occupied(entry) => entry.into_mut(),
+            MapEntry::Vacant(entry) => {
+                let value = default(&entry.key);
+                entry.insert(value)
+            }
+        }
+    }
+    /// Inserts [`Default::default`] if the entry is vacant, returning a mutable reference to the entry's value.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+    /// Calls `f` on the value if the entry is occupied.
+    pub fn and_modify(mut self, f: impl FnOnce(&mut V)) -> Self {
+        if let MapEntry::Occupied(entry) = &mut self {
+            f(entry.get_mut())
+        }
+        self
+    }
+}
+/// An occupied entry of an [`ArcBTreeMap`].
+pub struct OccupiedEntry<'a, K: Ord, V, Alloc: IAlloc, const SPLIT_LIMIT: usize> {
+    map: &'a mut ArcBTreeMap<K, V, Alloc, SPLIT_LIMIT>,
+    /// Where the entry was found, which stays valid as long as the map is borrowed.
+    position: Position,
+}
+impl<K: Ord, V, Alloc: IAlloc, const SPLIT_LIMIT: usize>
+    OccupiedEntry<'_, K, V, Alloc, SPLIT_LIMIT>
+{
+    fn entry(&self) -> &Entry<K, V> {
+        self.map
+            .0
+            .get_at(&self.position)
+            .expect("an entry's position stays valid while it borrows the map")
+    }
+    /// Returns the entry's key, as stored in the map.
+    pub fn key(&self) -> &K {
+        &self.entry().key
+    }
+    /// Returns a reference to the entry's value.
+    pub fn get(&self) -> &V {
+        &self.entry().value
+    }
+}
+impl<'a, K: Ord + Clone, V: Clone, Alloc: IAlloc + Clone, const SPLIT_LIMIT: usize>
+    OccupiedEntry<'a, K, V, Alloc, SPLIT_LIMIT>
+{
+    /// Returns a mutable reference to the entry's value, cloning the shared nodes on the way to it.
+    ///
+    /// # Panics
+    /// In case of allocation failure.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self
+            .map
+            .0
+            .get_mut_at(&self.position)
+            .expect("an entry's position stays valid while it borrows the map")
+            .value
+    }
+    /// Converts the entry into a mutable reference to its value, cloning the shared nodes on the way to it.
+    ///
+    /// # Panics
+    /// In case of allocation failure.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self
+            .map
+            .0
+            .get_mut_at(&self.position)
+            .expect("an entry's position stays valid while it borrows the map")
+            .value
+    }
+    /// Replaces the entry's value, returning the previous one.
+    pub fn insert(&mut self, value: V) -> V {
+        core::mem::replace(self.get_mut(), value)
+    }
+    /// Removes the entry from the map, returning its value.
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+    /// Removes the entry from the map, returning its key and value.
+    ///
+    /// # Panics
+    /// In case of allocation failure.
+    pub fn remove_entry(self) -> (K, V) {
+        self.map
+            .0
+            .remove_at(&self.position)
+            .expect("an entry's position stays valid while it borrows the map")
+            .into_pair()
+    }
+}
+/// A vacant entry of an [`ArcBTreeMap`].
+pub struct VacantEntry<'a, K: Ord, V, Alloc: IAlloc, const SPLIT_LIMIT: usize> {
+    map: &'a mut ArcBTreeMap<K, V, Alloc, SPLIT_LIMIT>,
+    key: K,
+}
+impl<K: Ord, V, Alloc: IAlloc, const SPLIT_LIMIT: usize> VacantEntry<'_, K, V, Alloc, SPLIT_LIMIT> {
+    /// Returns the entry's key.
+    pub const fn key(&self) -> &K {
+        &self.key
+    }
+    /// Takes ownership of the key.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+}
+impl<'a, K: Ord + Clone, V: Clone, Alloc: IAlloc + Clone, const SPLIT_LIMIT: usize>
+    VacantEntry<'a, K, V, Alloc, SPLIT_LIMIT>
+{
+    /// Inserts `value` in the map, returning a mutable reference to it.
+    ///
+    /// # Panics
+    /// In case of allocation failure, or if `K`'s implementation of [`Ord`] is inconsistent.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let Self { map, key } = self;
+        map.insert(key.clone(), value);
+        map.0
+            .get_mut_by(|entry| entry.key.cmp(&key))
+            .map(|entry| &mut entry.value)
+            .expect("a key that was just inserted can be found again")
+    }
+}
+
+/// A shareable BTree Set based on copy-on-write semantics.
 ///
 /// When inserting a value that's not currently present in the set, all shared nodes on the path to the value's slot will be cloned if shared before being mutated.
+/// The same goes for the nodes on the path to a value that is removed, as well as their siblings if the tree needs to be rebalanced.
 #[crate::stabby]
 pub struct ArcBTreeSet<
     T,
@@ -183,9 +743,11 @@ where
         }
     }
 }
-impl<T: Ord, Alloc: IAlloc + Default> Default for ArcBTreeSet<T, Alloc> {
+impl<T: Ord, Alloc: IAlloc + Default, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize>
+    Default for ArcBTreeSet<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+{
     fn default() -> Self {
-        Self::new_in(Alloc::default())
+        Self::from_alloc(Alloc::default())
     }
 }
 impl<T: Ord> ArcBTreeSet<T>
@@ -351,6 +913,8 @@ impl<T: Ord, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: us
 {
     const CHECK: () = if SPLIT_LIMIT % 2 == 0 {
         panic!("SPLIT_LIMIT on BTreeSet/BTreeMap must be odd (it is the number of elements at which a node will split)");
+    } else if SPLIT_LIMIT > u16::MAX as usize {
+        panic!("SPLIT_LIMIT on BTreeSet/BTreeMap must fit in a u16");
     };
     /// Constructs a new set in the provided allocator.
     ///
@@ -399,7 +963,6 @@ impl<T: Ord, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: us
             }
         }
     }
-    #[cfg(test)]
     pub(crate) fn for_each(&self, mut f: impl FnMut(&T)) {
         if let Some(this) = &self.root {
             this.for_each(&mut f)
@@ -416,45 +979,426 @@ impl<T: Ord, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: us
     pub const fn is_empty(&self) -> bool {
         self.root.is_none()
     }
-}
-use seal::*;
-mod seal {
-    use super::*;
-    #[crate::stabby]
-    /// An immutable ArcBTreeMap.
-    pub struct ArcBTreeSetNode<
-        T,
-        Alloc: IAlloc,
-        const REPLACE_ON_INSERT: bool,
-        const SPLIT_LIMIT: usize,
-    >(pub Arc<ArcBTreeSetNodeInner<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>, Alloc>);
-
-    #[crate::stabby]
-    pub struct ArcBTreeSetNodeInner<
-        T,
-        Alloc: IAlloc,
-        const REPLACE_ON_INSERT: bool,
-        const SPLIT_LIMIT: usize,
-    > {
-        pub entries:
-            [MaybeUninit<ArcBTreeSetEntry<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>>; SPLIT_LIMIT],
-        pub len: usize,
-        pub greater: Option<ArcBTreeSetNode<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>>,
+    /// Returns `true` if the set contains a value equal to `key`.
+    pub fn contains<K>(&self, key: &K) -> bool
+    where
+        T: PartialOrd<K>,
+    {
+        self.get(key).is_some()
     }
-    impl<T, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize>
-        ArcBTreeSetNodeInner<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+    /// Returns the smallest value in the set.
+    pub fn first(&self) -> Option<&T> {
+        self.root.as_ref()?.first_where(&|_| true)
+    }
+    /// Returns the greatest value in the set.
+    pub fn last(&self) -> Option<&T> {
+        self.root.as_ref()?.last_where(&|_| true)
+    }
+    /// Removes the value equal to `key` from the set, returning it.
+    ///
+    /// All shared nodes on the path to the value will be cloned before being mutated, as well as their siblings if the tree
+    /// needs to be rebalanced. No node is cloned if the value isn't in the set.
+    pub fn remove<K>(&mut self, key: &K) -> Option<T>
+    where
+        T: PartialOrd<K> + Clone,
+        Alloc: Clone,
     {
-        pub fn new(
-            entries: impl IntoIterator<
-                Item = ArcBTreeSetEntry<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>,
-            >,
-            greater: Option<ArcBTreeSetNode<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>>,
-        ) -> Self {
-            let mut this = ArcBTreeSetNodeInner {
-                entries: [(); SPLIT_LIMIT].map(|_| MaybeUninit::uninit()),
-                len: 0,
-                greater,
-            };
+        self.remove_by(|value| value.partial_cmp(key).unwrap_or(Ordering::Less))
+    }
+    /// Removes and returns the smallest value in the set.
+    pub fn pop_first(&mut self) -> Option<T>
+    where
+        T: Clone,
+        Alloc: Clone,
+    {
+        self.remove_with(&mut locate_first)
+    }
+    /// Removes and returns the greatest value in the set.
+    pub fn pop_last(&mut self) -> Option<T>
+    where
+        T: Clone,
+        Alloc: Clone,
+    {
+        self.remove_with(&mut locate_last)
+    }
+    /// Removes all the values from the set.
+    pub fn clear(&mut self)
+    where
+        Alloc: Clone,
+    {
+        if let Some(root) = self.root.take() {
+            self.alloc = MaybeUninit::new(Arc::allocator(&root.0).clone());
+        }
+    }
+    /// Removes the values for which `f` returns `false`.
+    ///
+    /// The values are visited in order, each removal costing `O(log(n))` like [`Self::remove`]: only the nodes on the
+    /// way to the removed values (and their siblings, if the tree needs to be rebalanced) are cloned if shared.
+    ///
+    /// # Panics
+    /// In case of allocation failure.
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool)
+    where
+        T: Clone,
+        Alloc: Clone,
+    {
+        let mut removed: Option<T> = None;
+        loop {
+            let root = self.root.as_ref();
+            let mut cursor = match &removed {
+                None => Cursor::first_where(root, &|_| true),
+                Some(removed) => Cursor::first_where(root, &|value| value > removed),
+            };
+            loop {
+                match cursor.front() {
+                    None => return,
+                    Some(value) if !f(value) => break,
+                    Some(_) => cursor.advance_front(),
+                }
+            }
+            // The removal reshapes the tree: the scan resumes after the removed value.
+            let position = cursor.position();
+            removed = Some(
+                self.remove_at(&position)
+                    .expect("the cursor points at a value of the set"),
+            );
+        }
+    }
+    /// Iterates over the set's values, in order.
+    ///
+    /// Each step costs `O(1)` amortized. The iterator's [`Iterator::size_hint`] is exact, but counting the remaining
+    /// values visits each of the nodes they span.
+    pub fn iter(&self) -> Iter<'_, T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT> {
+        self.range_by(|_| true, |_| true)
+    }
+    /// Iterates over the values that are within `range`, in order.
+    ///
+    /// The iterator is empty if the range's start is greater than its end.
+    pub fn range<K, R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> Iter<'_, T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+    where
+        T: PartialOrd<K>,
+    {
+        self.range_by(
+            |value| is_after(value, range.start_bound()),
+            |value| is_before(value, range.end_bound()),
+        )
+    }
+    /// Iterates over the values for which both `after_start` and `before_end` return `true`.
+    fn range_by(
+        &self,
+        after_start: impl Fn(&T) -> bool,
+        before_end: impl Fn(&T) -> bool,
+    ) -> Iter<'_, T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT> {
+        let root = self.root.as_ref();
+        let front = Cursor::first_where(root, &after_start);
+        let back = Cursor::last_where(root, &before_end);
+        match (front.front(), back.back()) {
+            (Some(first), Some(last)) if first <= last => Iter { root, front, back },
+            _ => Iter {
+                root,
+                front: Cursor::EMPTY,
+                back: Cursor::EMPTY,
+            },
+        }
+    }
+    fn get_by(&self, cmp: impl Fn(&T) -> Ordering) -> Option<&T> {
+        self.root.as_ref()?.get_by(&cmp)
+    }
+    fn get_mut_by(&mut self, cmp: impl Fn(&T) -> Ordering) -> Option<&mut T>
+    where
+        T: Clone,
+        Alloc: Clone,
+    {
+        self.get_by(&cmp)?;
+        self.root
+            .as_mut()?
+            .get_mut_with(&mut |entries, _| locate_key(entries, &cmp))
+    }
+    /// Finds where the value that `cmp` considers equal is.
+    fn position_by(&self, cmp: impl Fn(&T) -> Ordering) -> Option<Position> {
+        let mut position = Position::new();
+        let mut node = self.root.as_ref()?;
+        loop {
+            match locate_key(node.0.entries(), &cmp) {
+                Locate::Found(i) => {
+                    position.push(i);
+                    return Some(position);
+                }
+                Locate::Descend(i) => {
+                    position.push(i);
+                    node = node.0.child(i)?;
+                }
+            }
+        }
+    }
+    fn get_at(&self, position: &Position) -> Option<&T> {
+        let (last, path) = position.indices().split_last()?;
+        let mut node = self.root.as_ref()?;
+        for i in path {
+            node = node.0.child(usize::from(*i))?;
+        }
+        node.0
+            .entries()
+            .get(usize::from(*last))
+            .map(|entry| &entry.value)
+    }
+    fn get_mut_at(&mut self, position: &Position) -> Option<&mut T>
+    where
+        T: Clone,
+        Alloc: Clone,
+    {
+        self.get_at(position)?;
+        self.root.as_mut()?.get_mut_with(&mut position.locate())
+    }
+    fn remove_at(&mut self, position: &Position) -> Option<T>
+    where
+        T: Clone,
+        Alloc: Clone,
+    {
+        self.get_at(position)?;
+        self.remove_with(&mut position.locate())
+    }
+    fn remove_by(&mut self, cmp: impl Fn(&T) -> Ordering) -> Option<T>
+    where
+        T: Clone,
+        Alloc: Clone,
+    {
+        self.get_by(&cmp)?;
+        self.remove_with(&mut |entries, _| locate_key(entries, &cmp))
+    }
+    fn remove_with(
+        &mut self,
+        locate: &mut impl FnMut(
+            &[ArcBTreeSetEntry<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>],
+            bool,
+        ) -> Locate,
+    ) -> Option<T>
+    where
+        T: Clone,
+        Alloc: Clone,
+    {
+        let root = self.root.as_mut()?;
+        let removed = root.remove_with(locate);
+        if root.0.len == 0 {
+            // The root has been emptied: its only child (if any) becomes the new root.
+            let alloc = Arc::allocator(&root.0).clone();
+            let child = root.0.greater.clone();
+            if child.is_none() {
+                self.alloc = MaybeUninit::new(alloc);
+            }
+            self.root = child;
+        }
+        removed
+    }
+}
+
+/// The maximum depth of an [`ArcBTreeSet`].
+///
+/// Each node has at least 2 children, and is a separate allocation: a deeper tree couldn't fit in memory.
+const MAX_DEPTH: usize = usize::BITS as usize;
+/// Where a value is in an [`ArcBTreeSet`], which stays valid until the set is modified.
+#[derive(Clone, Copy)]
+struct Position {
+    /// The index of the child taken in each node on the way to the value, followed by the value's index in its node.
+    ///
+    /// Indices are stored as `u16`s to keep positions small, which `SPLIT_LIMIT`'s upper bound allows.
+    indices: [u16; MAX_DEPTH],
+    depth: usize,
+}
+impl Position {
+    const fn new() -> Self {
+        Self {
+            indices: [0; MAX_DEPTH],
+            depth: 0,
+        }
+    }
+    fn push(&mut self, i: usize) {
+        *self
+            .indices
+            .get_mut(self.depth)
+            .expect("an ArcBTreeSet can't be deeper than MAX_DEPTH") = i as u16;
+        self.depth = self.depth.wrapping_add(1);
+    }
+    fn get(&self, level: usize) -> Option<usize> {
+        self.indices().get(level).map(|i| usize::from(*i))
+    }
+    fn set(&mut self, level: usize, i: usize) {
+        if let Some(index) = self
+            .indices
+            .get_mut(..self.depth)
+            .and_then(|indices| indices.get_mut(level))
+        {
+            *index = i as u16;
+        }
+    }
+    fn indices(&self) -> &[u16] {
+        self.indices.get(..self.depth).unwrap_or_default()
+    }
+    /// Follows the position in [`ArcBTreeSetNode::remove_with`] and [`ArcBTreeSetNode::get_mut_with`].
+    fn locate<E>(&self) -> impl FnMut(&[E], bool) -> Locate + '_ {
+        let mut indices = self.indices().iter();
+        move |_, _| match indices.next() {
+            Some(i) if indices.len() == 0 => Locate::Found(usize::from(*i)),
+            Some(i) => Locate::Descend(usize::from(*i)),
+            // The position is exhausted: descending to a child that doesn't exist ends the search.
+            None => Locate::Descend(usize::MAX),
+        }
+    }
+}
+use seal::*;
+mod seal {
+    use super::*;
+    use crate::{
+        report::{FieldReport, LayoutReport, TyTy, TypeReport, VersionRange},
+        str::Str,
+        IStable, StableLike,
+    };
+    /// An immutable ArcBTreeMap.
+    #[repr(transparent)]
+    pub struct ArcBTreeSetNode<
+        T,
+        Alloc: IAlloc,
+        const REPLACE_ON_INSERT: bool,
+        const SPLIT_LIMIT: usize,
+    >(pub Arc<ArcBTreeSetNodeInner<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>, Alloc>);
+    const MODULE: &str = core::module_path!();
+    // SAFETY: `ArcBTreeSetNode` is a single `Arc`, which is a non-null pointer.
+    // Since a node contains nodes, deriving its report would recurse infinitely: instead, it reports the `Arc` of a mirror of
+    // its contents, in which the children are `ArcBTreeSetNodeCycle`s. `REPLACE_ON_INSERT` doesn't change the nodes' layout,
+    // but changes the meaning of inserting in them: it is reported as their version.
+    unsafe impl<
+            T: IStable,
+            Alloc: IAlloc + IStable,
+            const REPLACE_ON_INSERT: bool,
+            const SPLIT_LIMIT: usize,
+        > IStable for ArcBTreeSetNode<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+    where
+        Arc<report::ArcBTreeSetNodeInner<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>, Alloc>: IStable,
+    {
+        type Size = <NonNull<T> as IStable>::Size;
+        type Align = <NonNull<T> as IStable>::Align;
+        type ForbiddenValues = <NonNull<T> as IStable>::ForbiddenValues;
+        type UnusedBits = <NonNull<T> as IStable>::UnusedBits;
+        type HasExactlyOneNiche = <NonNull<T> as IStable>::HasExactlyOneNiche;
+        type ContainsIndirections = <NonNull<T> as IStable>::ContainsIndirections;
+        #[cfg(feature = "experimental-ctypes")]
+        type CType = <NonNull<T> as IStable>::CType;
+        const REPORT: &'static TypeReport = &TypeReport {
+            name: Str::new("ArcBTreeSetNode"),
+            module: Str::new(MODULE),
+            fields: StableLike::new(Some(&FieldReport {
+                name: Str::new("0"),
+                ty: <Arc<
+                    report::ArcBTreeSetNodeInner<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>,
+                    Alloc,
+                > as IStable>::REPORT,
+                offset: 0,
+                next_field: StableLike::new(None),
+            })),
+            layout: LayoutReport::of::<Self>(),
+            compatible_with: VersionRange::NONE,
+            is_pointer: false,
+            version: REPLACE_ON_INSERT as u32,
+            tyty: TyTy::Struct,
+        };
+        const ID: u64 = crate::report::gen_id(Self::REPORT);
+    }
+    /// Mirrors of the types a node contains, whose reports stand for theirs.
+    ///
+    /// These types are never constructed: only their reports are used.
+    #[allow(dead_code)]
+    pub mod report {
+        use super::*;
+        #[crate::stabby(module = super::MODULE)]
+        pub struct ArcBTreeSetNodeInner<
+            T,
+            Alloc: IAlloc,
+            const REPLACE_ON_INSERT: bool,
+            const SPLIT_LIMIT: usize,
+        > {
+            /// `MaybeUninit`'s report only names its contents: the entries are reported without it, which keeps their layout.
+            pub entries: [ArcBTreeSetEntry<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>; SPLIT_LIMIT],
+            pub len: usize,
+            pub greater: Option<ArcBTreeSetNodeCycle<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>>,
+        }
+        #[crate::stabby(module = super::MODULE)]
+        pub struct ArcBTreeSetEntry<
+            T,
+            Alloc: IAlloc,
+            const REPLACE_ON_INSERT: bool,
+            const SPLIT_LIMIT: usize,
+        > {
+            pub smaller: Option<ArcBTreeSetNodeCycle<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>>,
+            pub value: T,
+        }
+        /// Stands for a child node, which has the same type as the node being reported.
+        #[repr(transparent)]
+        pub struct ArcBTreeSetNodeCycle<
+            T,
+            Alloc: IAlloc,
+            const REPLACE_ON_INSERT: bool,
+            const SPLIT_LIMIT: usize,
+        >(NonNull<super::ArcBTreeSetNodeInner<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>>);
+        // SAFETY: `ArcBTreeSetNodeCycle` has the same layout as `ArcBTreeSetNode`, a non-null pointer.
+        // Its report has the name and version of the node's, without its fields, which are being reported already.
+        unsafe impl<
+                T: IStable,
+                Alloc: IAlloc + IStable,
+                const REPLACE_ON_INSERT: bool,
+                const SPLIT_LIMIT: usize,
+            > IStable for ArcBTreeSetNodeCycle<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+        {
+            type Size = <NonNull<T> as IStable>::Size;
+            type Align = <NonNull<T> as IStable>::Align;
+            type ForbiddenValues = <NonNull<T> as IStable>::ForbiddenValues;
+            type UnusedBits = <NonNull<T> as IStable>::UnusedBits;
+            type HasExactlyOneNiche = <NonNull<T> as IStable>::HasExactlyOneNiche;
+            type ContainsIndirections = <NonNull<T> as IStable>::ContainsIndirections;
+            #[cfg(feature = "experimental-ctypes")]
+            type CType = <NonNull<T> as IStable>::CType;
+            const REPORT: &'static TypeReport = &TypeReport {
+                name: Str::new("ArcBTreeSetNode"),
+                module: Str::new(MODULE),
+                fields: StableLike::new(None),
+                layout: LayoutReport::of::<Self>(),
+                compatible_with: VersionRange::NONE,
+                is_pointer: false,
+                version: REPLACE_ON_INSERT as u32,
+                tyty: TyTy::Struct,
+            };
+            const ID: u64 = crate::report::gen_id(Self::REPORT);
+        }
+    }
+
+    #[crate::stabby]
+    pub struct ArcBTreeSetNodeInner<
+        T,
+        Alloc: IAlloc,
+        const REPLACE_ON_INSERT: bool,
+        const SPLIT_LIMIT: usize,
+    > {
+        pub entries:
+            [MaybeUninit<ArcBTreeSetEntry<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>>; SPLIT_LIMIT],
+        pub len: usize,
+        pub greater: Option<ArcBTreeSetNode<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>>,
+    }
+    impl<T, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize>
+        ArcBTreeSetNodeInner<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+    {
+        pub fn new(
+            entries: impl IntoIterator<
+                Item = ArcBTreeSetEntry<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>,
+            >,
+            greater: Option<ArcBTreeSetNode<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>>,
+        ) -> Self {
+            let mut this = ArcBTreeSetNodeInner {
+                entries: [(); SPLIT_LIMIT].map(|_| MaybeUninit::uninit()),
+                len: 0,
+                greater,
+            };
             for entry in entries {
                 this.entries
                     .get_mut(this.len)
@@ -537,6 +1481,40 @@ mod seal {
             }
             acc
         }
+        /// Counts the values for which both `after_start` and `before_end` return `true`, assuming both are monotonic.
+        ///
+        /// Children that are entirely within the bounds are counted through [`Self::len`].
+        pub fn count_where(
+            &self,
+            after_start: &impl Fn(&T) -> bool,
+            before_end: &impl Fn(&T) -> bool,
+        ) -> usize {
+            let entries = self.0.entries();
+            let mut count = 0usize;
+            for i in 0..=entries.len() {
+                // The `i`th child's values are between the `i - 1`th and `i`th entries.
+                let lower = i.checked_sub(1).and_then(|i| entries.get(i));
+                let upper = entries.get(i);
+                if let Some(child) = self.0.child(i) {
+                    let before = upper.is_some_and(|upper| !after_start(&upper.value));
+                    let after = lower.is_some_and(|lower| !before_end(&lower.value));
+                    let within = lower.is_some_and(|lower| after_start(&lower.value))
+                        && upper.is_some_and(|upper| before_end(&upper.value));
+                    count = count.wrapping_add(if before || after {
+                        0
+                    } else if within {
+                        child.len()
+                    } else {
+                        child.count_where(after_start, before_end)
+                    });
+                }
+                if upper.is_some_and(|upper| after_start(&upper.value) && before_end(&upper.value))
+                {
+                    count = count.wrapping_add(1);
+                }
+            }
+            count
+        }
         pub fn get<K>(&self, key: &K) -> Option<&T>
         where
             T: PartialOrd<K>,
@@ -626,7 +1604,6 @@ mod seal {
             }
             Err(None)
         }
-        #[cfg(test)]
         pub fn for_each(&self, f: &mut impl FnMut(&T)) {
             for ArcBTreeSetEntry { value, smaller } in self.0.entries() {
                 if let Some(smaller) = smaller {
@@ -767,73 +1744,1226 @@ mod seal {
             unsafe { core::mem::transmute(self.entries.get_unchecked_mut(..self.len)) }
         }
     }
-}
-#[test]
-#[cfg(feature = "libc")]
-fn btree_insert_libc() {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-    for i in 0..if cfg!(miri) { 5 } else { 1000 } {
-        dbg!(i);
-        let mut vec =
-            crate::alloc::vec::Vec::new_in(crate::alloc::allocators::LibcAlloc::default());
-        let mut btree = ArcBTreeSet::new_in(crate::alloc::allocators::LibcAlloc::default());
-        for _ in 0..rng.gen_range(0..800) {
-            let val = rng.gen_range(0..100u8);
-            if vec.binary_search(&val).is_ok() {
-                assert_eq!(btree.insert(val), Some(val));
-            } else {
-                vec.push(val);
-                vec.sort();
-                assert_eq!(
-                    btree.insert(val),
-                    None,
-                    "The BTree contained an unexpected value: {btree:?}, {vec:?}"
-                );
+
+    #[cfg(test)]
+    impl<T: Ord, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize>
+        ArcBTreeSetNode<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+    {
+        /// Checks the node's invariants, returning its depth.
+        pub fn check(&self, is_root: bool) -> usize {
+            let entries = self.0.entries();
+            assert!(entries.len() < SPLIT_LIMIT);
+            assert!(
+                is_root || entries.len() >= SPLIT_LIMIT / 2,
+                "non-root node with {} entries",
+                entries.len()
+            );
+            assert!(entries
+                .iter()
+                .zip(entries.iter().skip(1))
+                .all(|(a, b)| a.value < b.value));
+            let mut depths =
+                (0..=entries.len()).map(|i| self.0.child(i).map_or(0, |child| child.check(false)));
+            let depth = depths.next().unwrap_or(0);
+            assert!(depths.all(|d| d == depth), "unbalanced node");
+            depth.wrapping_add(1)
+        }
+    }
+
+    /// Where [`ArcBTreeSetNode::remove_with`] should look for the value to remove in a node.
+    pub enum Locate {
+        /// The value is the entry at this index.
+        Found(usize),
+        /// The value is in the child at this index (`len` being the index of `greater`).
+        Descend(usize),
+    }
+    /// Locates the entry that `cmp` considers equal.
+    pub fn locate_key<T, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize>(
+        entries: &[ArcBTreeSetEntry<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>],
+        cmp: &impl Fn(&T) -> Ordering,
+    ) -> Locate {
+        for (i, entry) in entries.iter().enumerate() {
+            match cmp(&entry.value) {
+                Ordering::Less => {}
+                Ordering::Equal => return Locate::Found(i),
+                Ordering::Greater => return Locate::Descend(i),
             }
         }
-        vec.sort();
-        assert_eq!(vec.len(), btree.len());
-        let mut iter = vec.into_iter();
-        btree.for_each(|i| assert_eq!(Some(*i), iter.next()));
-        assert_eq!(iter.next(), None);
+        Locate::Descend(entries.len())
+    }
+    /// Locates the smallest value.
+    pub const fn locate_first<E>(_: &[E], leaf: bool) -> Locate {
+        if leaf {
+            Locate::Found(0)
+        } else {
+            Locate::Descend(0)
+        }
+    }
+    /// Locates the greatest value.
+    pub const fn locate_last<E>(entries: &[E], leaf: bool) -> Locate {
+        if leaf {
+            Locate::Found(entries.len().wrapping_sub(1))
+        } else {
+            Locate::Descend(entries.len())
+        }
     }
-}
 
-#[test]
-#[cfg(feature = "alloc-rs")]
-fn btree_insert_rs() {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-    for i in 0..if cfg!(miri) { 5 } else { 1000 } {
-        dbg!(i);
-        let mut vec =
-            crate::alloc::vec::Vec::new_in(crate::alloc::allocators::RustAlloc::default());
-        let mut btree = ArcBTreeSet::new_in(crate::alloc::allocators::RustAlloc::default());
-        for _ in 0..rng.gen_range(0..800) {
-            let val = rng.gen_range(0..100);
-            if vec.binary_search(&val).is_ok() {
-                assert_eq!(
-                    btree.insert(val),
-                    Some(val),
-                    "btree: {btree:?}, vec: {vec:?}, val: {val}"
-                );
-            } else {
-                vec.push(val);
-                vec.sort();
-                assert_eq!(
-                    btree.insert(val),
-                    None,
-                    "The BTree contained an unexpected value: {btree:?}, {vec:?}"
-                );
+    impl<T, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize>
+        ArcBTreeSetNode<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+    {
+        /// Returns the smallest value for which `pred` returns `true`, assuming `pred` is monotonic.
+        pub fn first_where(&self, pred: &impl Fn(&T) -> bool) -> Option<&T> {
+            for entry in self.0.entries() {
+                if pred(&entry.value) {
+                    return entry
+                        .smaller
+                        .as_ref()
+                        .and_then(|smaller| smaller.first_where(pred))
+                        .or(Some(&entry.value));
+                }
+            }
+            self.0.greater.as_ref()?.first_where(pred)
+        }
+        /// Returns the greatest value for which `pred` returns `true`, assuming `pred` is monotonic.
+        pub fn last_where(&self, pred: &impl Fn(&T) -> bool) -> Option<&T> {
+            let entries = self.0.entries();
+            for (i, entry) in entries.iter().enumerate().rev() {
+                if pred(&entry.value) {
+                    return self
+                        .0
+                        .child(i.wrapping_add(1))
+                        .and_then(|greater| greater.last_where(pred))
+                        .or(Some(&entry.value));
+                }
+            }
+            self.0.child(0)?.last_where(pred)
+        }
+        pub fn get_by(&self, cmp: &impl Fn(&T) -> Ordering) -> Option<&T> {
+            for entry in self.0.entries() {
+                match cmp(&entry.value) {
+                    Ordering::Equal => return Some(&entry.value),
+                    Ordering::Greater => return entry.smaller.as_ref()?.get_by(cmp),
+                    Ordering::Less => {}
+                }
+            }
+            self.0.greater.as_ref()?.get_by(cmp)
+        }
+        /// Returns the value designated by `locate`, cloning the shared nodes on the way to it.
+        pub fn get_mut_with(
+            &mut self,
+            locate: &mut impl FnMut(
+                &[ArcBTreeSetEntry<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>],
+                bool,
+            ) -> Locate,
+        ) -> Option<&mut T>
+        where
+            T: Clone,
+            Alloc: Clone,
+        {
+            let inner = Arc::make_mut(&mut self.0);
+            let leaf = inner.greater.is_none();
+            match locate(inner.entries(), leaf) {
+                Locate::Found(i) => inner.entries_mut().get_mut(i).map(|entry| &mut entry.value),
+                Locate::Descend(i) => inner.child_mut(i)?.get_mut_with(locate),
+            }
+        }
+        /// Removes the value designated by `locate`, rebalancing the nodes on the way back up.
+        ///
+        /// The node may be left with less than `SPLIT_LIMIT / 2` entries, which its parent is responsible for fixing.
+        pub fn remove_with(
+            &mut self,
+            locate: &mut impl FnMut(
+                &[ArcBTreeSetEntry<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>],
+                bool,
+            ) -> Locate,
+        ) -> Option<T>
+        where
+            T: Clone,
+            Alloc: Clone,
+        {
+            let inner = Arc::make_mut(&mut self.0);
+            let leaf = inner.greater.is_none();
+            match locate(inner.entries(), leaf) {
+                Locate::Found(i) if leaf => inner.remove_entry(i).map(|entry| entry.value),
+                Locate::Found(i) => {
+                    // Replace the value by its predecessor, which is always in a leaf.
+                    let predecessor = inner.child_mut(i)?.remove_with(&mut locate_last)?;
+                    let removed =
+                        core::mem::replace(&mut inner.entries_mut().get_mut(i)?.value, predecessor);
+                    inner.rebalance(i);
+                    Some(removed)
+                }
+                Locate::Descend(i) => {
+                    let removed = inner.child_mut(i)?.remove_with(locate)?;
+                    inner.rebalance(i);
+                    Some(removed)
+                }
             }
         }
-        vec.sort();
-        assert_eq!(vec.len(), btree.len());
-        let mut iter = vec.into_iter();
-        btree.for_each(|i| assert_eq!(Some(*i), iter.next()));
-        assert_eq!(iter.next(), None);
     }
+
+    impl<T, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize>
+        ArcBTreeSetNodeInner<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+    {
+        /// Returns the `i`th child, `len` being the index of `greater`.
+        pub fn child(
+            &self,
+            i: usize,
+        ) -> Option<&ArcBTreeSetNode<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>> {
+            match self.entries().get(i) {
+                Some(entry) => entry.smaller.as_ref(),
+                None if i == self.len => self.greater.as_ref(),
+                None => None,
+            }
+        }
+        fn child_slot_mut(
+            &mut self,
+            i: usize,
+        ) -> Option<&mut Option<ArcBTreeSetNode<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>>>
+        {
+            let Self {
+                entries,
+                len,
+                greater,
+            } = self;
+            match i.cmp(len) {
+                // SAFETY: Entries up to `self.len` are always initialized.
+                Ordering::Less => entries
+                    .get_mut(i)
+                    .map(|entry| unsafe { &mut entry.assume_init_mut().smaller }),
+                Ordering::Equal => Some(greater),
+                Ordering::Greater => None,
+            }
+        }
+        fn child_mut(
+            &mut self,
+            i: usize,
+        ) -> Option<&mut ArcBTreeSetNode<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>> {
+            self.child_slot_mut(i)?.as_mut()
+        }
+        fn child_len(&self, i: usize) -> Option<usize> {
+            self.child(i).map(|child| child.0.len)
+        }
+        /// Removes the `i`th entry, shifting the following ones.
+        #[rustversion::attr(since(1.83), const)]
+        fn remove_entry(
+            &mut self,
+            i: usize,
+        ) -> Option<ArcBTreeSetEntry<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>> {
+            if i >= self.len {
+                return None;
+            }
+            let tail = self.len.wrapping_sub(i).wrapping_sub(1);
+            // SAFETY: `i < self.len`, so the entry is initialized, and the following ones are moved to close the gap.
+            let entry = unsafe {
+                let entries = self.entries.as_mut_ptr();
+                let entry = entries.add(i).read().assume_init();
+                core::ptr::copy(entries.add(i.wrapping_add(1)), entries.add(i), tail);
+                entry
+            };
+            self.len = self.len.wrapping_sub(1);
+            Some(entry)
+        }
+        /// Inserts `entry` at index `i`, shifting the following ones, without splitting the node.
+        fn insert_entry(
+            &mut self,
+            i: usize,
+            entry: ArcBTreeSetEntry<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>,
+        ) {
+            assert!(i <= self.len && self.len < SPLIT_LIMIT);
+            // SAFETY: the node has a free slot, and `i <= self.len`.
+            unsafe {
+                let entries = self.entries.as_mut_ptr();
+                core::ptr::copy(
+                    entries.add(i),
+                    entries.add(i.wrapping_add(1)),
+                    self.len.wrapping_sub(i),
+                );
+                entries.add(i).write(MaybeUninit::new(entry));
+            }
+            self.len = self.len.wrapping_add(1);
+        }
+        /// Ensures the `i`th child has at least `SPLIT_LIMIT / 2` entries after a removal,
+        /// by borrowing an entry from one of its siblings or merging it with one.
+        fn rebalance(&mut self, i: usize)
+        where
+            T: Clone,
+            Alloc: Clone,
+        {
+            let min_len = SPLIT_LIMIT / 2;
+            if self.child_len(i).map_or(true, |len| len >= min_len) {
+                return;
+            }
+            let left = i.checked_sub(1);
+            let right = i.wrapping_add(1);
+            if left.and_then(|left| self.child_len(left)) > Some(min_len) {
+                self.rotate_right(i)
+            } else if self.child_len(right) > Some(min_len) {
+                self.rotate_left(i)
+            } else if let Some(left) = left {
+                self.merge(left)
+            } else {
+                self.merge(i)
+            }
+        }
+        /// Moves the greatest entry of the `i - 1`th child to the `i`th child, through their separator.
+        fn rotate_right(&mut self, i: usize)
+        where
+            T: Clone,
+            Alloc: Clone,
+        {
+            let left = i.wrapping_sub(1);
+            let Some(left_child) = self.child_mut(left) else {
+                return;
+            };
+            let left_child = Arc::make_mut(&mut left_child.0);
+            let Some(last) = left_child.remove_entry(left_child.len.wrapping_sub(1)) else {
+                return;
+            };
+            let moved = core::mem::replace(&mut left_child.greater, last.smaller);
+            let separator = match self.entries_mut().get_mut(left) {
+                Some(separator) => core::mem::replace(&mut separator.value, last.value),
+                None => return,
+            };
+            if let Some(child) = self.child_mut(i) {
+                Arc::make_mut(&mut child.0).insert_entry(
+                    0,
+                    ArcBTreeSetEntry {
+                        value: separator,
+                        smaller: moved,
+                    },
+                );
+            }
+        }
+        /// Moves the smallest entry of the `i + 1`th child to the `i`th child, through their separator.
+        fn rotate_left(&mut self, i: usize)
+        where
+            T: Clone,
+            Alloc: Clone,
+        {
+            let Some(right_child) = self.child_mut(i.wrapping_add(1)) else {
+                return;
+            };
+            let Some(first) = Arc::make_mut(&mut right_child.0).remove_entry(0) else {
+                return;
+            };
+            let separator = match self.entries_mut().get_mut(i) {
+                Some(separator) => core::mem::replace(&mut separator.value, first.value),
+                None => return,
+            };
+            if let Some(child) = self.child_mut(i) {
+                let child = Arc::make_mut(&mut child.0);
+                let moved = core::mem::replace(&mut child.greater, first.smaller);
+                child.insert_entry(
+                    child.len,
+                    ArcBTreeSetEntry {
+                        value: separator,
+                        smaller: moved,
+                    },
+                );
+            }
+        }
+        /// Merges the `i`th and `i + 1`th children, along with their separator.
+        fn merge(&mut self, i: usize)
+        where
+            T: Clone,
+            Alloc: Clone,
+        {
+            let Some(ArcBTreeSetEntry {
+                value: separator,
+                smaller: Some(mut left),
+            }) = self.remove_entry(i)
+            else {
+                return;
+            };
+            // The right child now sits at index `i`.
+            let Some(right) = self.child_slot_mut(i).and_then(Option::take) else {
+                return;
+            };
+            let mut right = Arc::try_into_inner(right.0).unwrap_or_else(|shared| (*shared).clone());
+            let merged = Arc::make_mut(&mut left.0);
+            let moved = core::mem::replace(&mut merged.greater, right.greater.take());
+            merged.insert_entry(
+                merged.len,
+                ArcBTreeSetEntry {
+                    value: separator,
+                    smaller: moved,
+                },
+            );
+            let right_len = core::mem::replace(&mut right.len, 0);
+            for j in 0..right_len {
+                // SAFETY: `right`'s entries were initialized up to `right_len`, and its length was set to 0 to move them out.
+                let entry = unsafe { right.entries.get_unchecked(j).assume_init_read() };
+                merged.insert_entry(merged.len, entry);
+            }
+            if let Some(slot) = self.child_slot_mut(i) {
+                *slot = Some(left);
+            }
+        }
+    }
+}
+/// Returns `true` if `value` is after the range's `start`.
+fn is_after<T: PartialOrd<K> + ?Sized, K: ?Sized>(value: &T, start: Bound<&K>) -> bool {
+    match start {
+        Bound::Included(start) => value >= start,
+        Bound::Excluded(start) => value > start,
+        Bound::Unbounded => true,
+    }
+}
+/// Returns `true` if `value` is before the range's `end`.
+fn is_before<T: PartialOrd<K> + ?Sized, K: ?Sized>(value: &T, end: Bound<&K>) -> bool {
+    match end {
+        Bound::Included(end) => value <= end,
+        Bound::Excluded(end) => value < end,
+        Bound::Unbounded => true,
+    }
+}
+
+/// A path from the root of an [`ArcBTreeSet`] to one of its values, which [`Iter`] advances in amortized constant time.
+struct Cursor<'a, T, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize> {
+    /// The nodes on the path, the index of each being stored in `position`.
+    ///
+    /// A front cursor points at the `i`th entry of its last node, and the `i`th entries of the other nodes
+    /// come next once their `i`th child is done. Back cursors mirror this with the `i - 1`th entries.
+    nodes: [Option<&'a ArcBTreeSetNode<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>>; MAX_DEPTH],
+    position: Position,
+}
+impl<T, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize> Clone
+    for Cursor<'_, T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize> Copy
+    for Cursor<'_, T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+{
+}
+impl<'a, T, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize>
+    Cursor<'a, T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+{
+    /// A cursor that doesn't point at any value.
+    const EMPTY: Self = Self {
+        nodes: [None; MAX_DEPTH],
+        position: Position::new(),
+    };
+    /// Points a front cursor at the smallest value for which `pred` returns `true`, assuming `pred` is monotonic.
+    fn first_where(
+        root: Option<&'a ArcBTreeSetNode<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>>,
+        pred: &impl Fn(&T) -> bool,
+    ) -> Self {
+        let mut this = Self::EMPTY;
+        let mut next = root;
+        while let Some(node) = next {
+            let entries = node.0.entries();
+            let i = entries
+                .iter()
+                .position(|entry| pred(&entry.value))
+                .unwrap_or(entries.len());
+            this.push(node, i);
+            next = node.0.child(i);
+        }
+        this.settle_front();
+        this
+    }
+    /// Points a back cursor at the greatest value for which `pred` returns `true`, assuming `pred` is monotonic.
+    fn last_where(
+        root: Option<&'a ArcBTreeSetNode<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>>,
+        pred: &impl Fn(&T) -> bool,
+    ) -> Self {
+        let mut this = Self::EMPTY;
+        let mut next = root;
+        while let Some(node) = next {
+            let i = node
+                .0
+                .entries()
+                .iter()
+                .rposition(|entry| pred(&entry.value))
+                .map_or(0, |i| i.wrapping_add(1));
+            this.push(node, i);
+            next = node.0.child(i);
+        }
+        this.settle_back();
+        this
+    }
+    fn push(
+        &mut self,
+        node: &'a ArcBTreeSetNode<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>,
+        i: usize,
+    ) {
+        if let Some(slot) = self.nodes.get_mut(self.position.depth) {
+            *slot = Some(node);
+        }
+        self.position.push(i);
+    }
+    fn top(
+        &self,
+    ) -> Option<(
+        &'a ArcBTreeSetNode<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>,
+        usize,
+    )> {
+        let level = self.position.depth.checked_sub(1)?;
+        Some(((*self.nodes.get(level)?)?, self.position.get(level)?))
+    }
+    fn set_top(&mut self, i: usize) {
+        let level = self.position.depth.wrapping_sub(1);
+        self.position.set(level, i);
+    }
+    #[rustversion::attr(since(1.83), const)]
+    fn pop(&mut self) {
+        self.position.depth = self.position.depth.saturating_sub(1);
+    }
+    /// Pops the nodes whose entries have all been yielded by a front cursor.
+    fn settle_front(&mut self) {
+        while let Some((node, i)) = self.top() {
+            if i < node.0.len {
+                return;
+            }
+            self.pop();
+        }
+    }
+    /// Pops the nodes whose entries have all been yielded by a back cursor.
+    fn settle_back(&mut self) {
+        while let Some((_, 0)) = self.top() {
+            self.pop();
+        }
+    }
+    /// Returns the value a front cursor points at.
+    fn front(&self) -> Option<&'a T> {
+        let (node, i) = self.top()?;
+        node.0.entries().get(i).map(|entry| &entry.value)
+    }
+    /// Returns the value a back cursor points at.
+    fn back(&self) -> Option<&'a T> {
+        let (node, i) = self.top()?;
+        node.0
+            .entries()
+            .get(i.checked_sub(1)?)
+            .map(|entry| &entry.value)
+    }
+    /// Moves a front cursor to the next value: the smallest one of the next child if any, or the next entry of an ancestor.
+    fn advance_front(&mut self) {
+        let Some((node, i)) = self.top() else {
+            return;
+        };
+        let i = i.wrapping_add(1);
+        self.set_top(i);
+        let mut next = node.0.child(i);
+        while let Some(child) = next {
+            self.push(child, 0);
+            next = child.0.child(0);
+        }
+        self.settle_front();
+    }
+    /// Moves a back cursor to the previous value: the greatest one of the previous child if any, or the previous entry of an ancestor.
+    fn advance_back(&mut self) {
+        let Some((node, i)) = self.top() else {
+            return;
+        };
+        let i = i.wrapping_sub(1);
+        self.set_top(i);
+        let mut next = node.0.child(i);
+        while let Some(child) = next {
+            let len = child.0.len;
+            self.push(child, len);
+            next = child.0.child(len);
+        }
+        self.settle_back();
+    }
+    /// Returns the position of the value a front cursor points at.
+    const fn position(&self) -> Position {
+        self.position
+    }
+}
+
+/// An iterator over the values of an [`ArcBTreeSet`], obtained through [`ArcBTreeSet::iter`] or [`ArcBTreeSet::range`].
+pub struct Iter<'a, T, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize> {
+    root: Option<&'a ArcBTreeSetNode<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>>,
+    /// Points at the next value to yield from the front, and at nothing iff the iterator is exhausted.
+    front: Cursor<'a, T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>,
+    /// Points at the next value to yield from the back, and at nothing iff the iterator is exhausted.
+    back: Cursor<'a, T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>,
+}
+impl<T, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize> Clone
+    for Iter<'_, T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+{
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root,
+            front: self.front,
+            back: self.back,
+        }
+    }
+}
+impl<'a, T: Ord, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize> Iterator
+    for Iter<'a, T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+{
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        let front = self.front.front()?;
+        if self
+            .back
+            .back()
+            .map_or(true, |back| core::ptr::eq(front, back))
+        {
+            self.front = Cursor::EMPTY;
+            self.back = Cursor::EMPTY;
+        } else {
+            self.front.advance_front();
+        }
+        Some(front)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = match (self.root, self.front.front(), self.back.back()) {
+            (Some(root), Some(front), Some(back)) => {
+                root.count_where(&|value| value >= front, &|value| value <= back)
+            }
+            _ => 0,
+        };
+        (remaining, Some(remaining))
+    }
+}
+impl<T: Ord, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize>
+    DoubleEndedIterator for Iter<'_, T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let back = self.back.back()?;
+        if self
+            .front
+            .front()
+            .map_or(true, |front| core::ptr::eq(front, back))
+        {
+            self.front = Cursor::EMPTY;
+            self.back = Cursor::EMPTY;
+        } else {
+            self.back.advance_back();
+        }
+        Some(back)
+    }
+}
+impl<T: Ord, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize>
+    core::iter::FusedIterator for Iter<'_, T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+{
+}
+
+/// An iterator over the values of an [`ArcBTreeSet`], by value.
+///
+/// Values are moved out of the nodes that aren't shared, and cloned out of the others.
+pub struct IntoIter<T, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize>(
+    ArcBTreeSet<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>,
+);
+impl<
+        T: Ord + Clone,
+        Alloc: IAlloc + Clone,
+        const REPLACE_ON_INSERT: bool,
+        const SPLIT_LIMIT: usize,
+    > Iterator for IntoIter<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+{
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_first()
+    }
+}
+impl<
+        T: Ord + Clone,
+        Alloc: IAlloc + Clone,
+        const REPLACE_ON_INSERT: bool,
+        const SPLIT_LIMIT: usize,
+    > DoubleEndedIterator for IntoIter<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.pop_last()
+    }
+}
+impl<
+        T: Ord + Clone,
+        Alloc: IAlloc + Clone,
+        const REPLACE_ON_INSERT: bool,
+        const SPLIT_LIMIT: usize,
+    > core::iter::FusedIterator for IntoIter<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+{
+}
+
+impl<
+        T: Ord + Clone,
+        Alloc: IAlloc + Clone,
+        const REPLACE_ON_INSERT: bool,
+        const SPLIT_LIMIT: usize,
+    > IntoIterator for ArcBTreeSet<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+{
+    type Item = T;
+    type IntoIter = IntoIter<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+impl<'a, T: Ord, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize>
+    IntoIterator for &'a ArcBTreeSet<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+{
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<
+        T: Ord + Clone,
+        Alloc: IAlloc + Clone,
+        const REPLACE_ON_INSERT: bool,
+        const SPLIT_LIMIT: usize,
+    > Extend<T> for ArcBTreeSet<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+impl<
+        T: Ord + Clone,
+        Alloc: IAlloc + Clone + Default,
+        const REPLACE_ON_INSERT: bool,
+        const SPLIT_LIMIT: usize,
+    > FromIterator<T> for ArcBTreeSet<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::default();
+        set.extend(iter);
+        set
+    }
+}
+impl<T: Ord, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize> PartialEq
+    for ArcBTreeSet<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+impl<T: Ord, Alloc: IAlloc, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize> Eq
+    for ArcBTreeSet<T, Alloc, REPLACE_ON_INSERT, SPLIT_LIMIT>
+{
+}
+
+/// An iterator over the key-value pairs of an [`ArcBTreeMap`], obtained through [`ArcBTreeMap::iter`] or [`ArcBTreeMap::range`].
+pub struct MapIter<'a, K, V, Alloc: IAlloc, const SPLIT_LIMIT: usize>(
+    Iter<'a, Entry<K, V>, Alloc, true, SPLIT_LIMIT>,
+);
+impl<K, V, Alloc: IAlloc, const SPLIT_LIMIT: usize> Clone
+    for MapIter<'_, K, V, Alloc, SPLIT_LIMIT>
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+impl<'a, K: Ord, V, Alloc: IAlloc, const SPLIT_LIMIT: usize> Iterator
+    for MapIter<'a, K, V, Alloc, SPLIT_LIMIT>
+{
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(Entry::as_pair)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<K: Ord, V, Alloc: IAlloc, const SPLIT_LIMIT: usize> DoubleEndedIterator
+    for MapIter<'_, K, V, Alloc, SPLIT_LIMIT>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(Entry::as_pair)
+    }
+}
+impl<K: Ord, V, Alloc: IAlloc, const SPLIT_LIMIT: usize> core::iter::FusedIterator
+    for MapIter<'_, K, V, Alloc, SPLIT_LIMIT>
+{
+}
+
+/// An iterator over the keys of an [`ArcBTreeMap`].
+pub struct Keys<'a, K, V, Alloc: IAlloc, const SPLIT_LIMIT: usize>(
+    MapIter<'a, K, V, Alloc, SPLIT_LIMIT>,
+);
+impl<'a, K: Ord, V, Alloc: IAlloc, const SPLIT_LIMIT: usize> Iterator
+    for Keys<'a, K, V, Alloc, SPLIT_LIMIT>
+{
+    type Item = &'a K;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, _)| key)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<K: Ord, V, Alloc: IAlloc, const SPLIT_LIMIT: usize> DoubleEndedIterator
+    for Keys<'_, K, V, Alloc, SPLIT_LIMIT>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(key, _)| key)
+    }
+}
+
+/// An iterator over the values of an [`ArcBTreeMap`].
+pub struct Values<'a, K, V, Alloc: IAlloc, const SPLIT_LIMIT: usize>(
+    MapIter<'a, K, V, Alloc, SPLIT_LIMIT>,
+);
+impl<'a, K: Ord, V, Alloc: IAlloc, const SPLIT_LIMIT: usize> Iterator
+    for Values<'a, K, V, Alloc, SPLIT_LIMIT>
+{
+    type Item = &'a V;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, value)| value)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<K: Ord, V, Alloc: IAlloc, const SPLIT_LIMIT: usize> DoubleEndedIterator
+    for Values<'_, K, V, Alloc, SPLIT_LIMIT>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(_, value)| value)
+    }
+}
+
+/// An iterator over the key-value pairs of an [`ArcBTreeMap`], by value.
+///
+/// Pairs are moved out of the nodes that aren't shared, and cloned out of the others.
+pub struct MapIntoIter<K, V, Alloc: IAlloc, const SPLIT_LIMIT: usize>(
+    IntoIter<Entry<K, V>, Alloc, true, SPLIT_LIMIT>,
+);
+impl<K: Ord + Clone, V: Clone, Alloc: IAlloc + Clone, const SPLIT_LIMIT: usize> Iterator
+    for MapIntoIter<K, V, Alloc, SPLIT_LIMIT>
+{
+    type Item = (K, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(Entry::into_pair)
+    }
+}
+impl<K: Ord + Clone, V: Clone, Alloc: IAlloc + Clone, const SPLIT_LIMIT: usize> DoubleEndedIterator
+    for MapIntoIter<K, V, Alloc, SPLIT_LIMIT>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(Entry::into_pair)
+    }
+}
+
+impl<K: Ord + Clone, V: Clone, Alloc: IAlloc + Clone, const SPLIT_LIMIT: usize> IntoIterator
+    for ArcBTreeMap<K, V, Alloc, SPLIT_LIMIT>
+{
+    type Item = (K, V);
+    type IntoIter = MapIntoIter<K, V, Alloc, SPLIT_LIMIT>;
+    fn into_iter(self) -> Self::IntoIter {
+        MapIntoIter(self.0.into_iter())
+    }
+}
+impl<'a, K: Ord, V, Alloc: IAlloc, const SPLIT_LIMIT: usize> IntoIterator
+    for &'a ArcBTreeMap<K, V, Alloc, SPLIT_LIMIT>
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = MapIter<'a, K, V, Alloc, SPLIT_LIMIT>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<K: Ord + Clone, V: Clone, Alloc: IAlloc + Clone, const SPLIT_LIMIT: usize> Extend<(K, V)>
+    for ArcBTreeMap<K, V, Alloc, SPLIT_LIMIT>
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+impl<K: Ord + Clone, V: Clone, Alloc: IAlloc + Clone + Default, const SPLIT_LIMIT: usize>
+    FromIterator<(K, V)> for ArcBTreeMap<K, V, Alloc, SPLIT_LIMIT>
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+impl<K: Ord, V, Alloc: IAlloc + Default, const SPLIT_LIMIT: usize> Default
+    for ArcBTreeMap<K, V, Alloc, SPLIT_LIMIT>
+{
+    fn default() -> Self {
+        Self::from_alloc(Alloc::default())
+    }
+}
+impl<K: Ord, V: PartialEq, Alloc: IAlloc, const SPLIT_LIMIT: usize> PartialEq
+    for ArcBTreeMap<K, V, Alloc, SPLIT_LIMIT>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+impl<K: Ord, V: Eq, Alloc: IAlloc, const SPLIT_LIMIT: usize> Eq
+    for ArcBTreeMap<K, V, Alloc, SPLIT_LIMIT>
+{
+}
+impl<K: Ord + core::fmt::Debug, V: core::fmt::Debug, Alloc: IAlloc, const SPLIT_LIMIT: usize>
+    core::fmt::Debug for ArcBTreeMap<K, V, Alloc, SPLIT_LIMIT>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+impl<K: Ord + Borrow<Q>, Q: ?Sized + Ord, V, Alloc: IAlloc, const SPLIT_LIMIT: usize>
+    core::ops::Index<&Q> for ArcBTreeMap<K, V, Alloc, SPLIT_LIMIT>
+{
+    type Output = V;
+    /// # Panics
+    /// If `key` isn't in the map.
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("no entry found for key")
+    }
+}
+
+#[test]
+#[cfg(feature = "libc")]
+fn btree_insert_libc() {
+    use rand::Rng;
+    let mut rng = rand::thread_rng();
+    for i in 0..if cfg!(miri) { 5 } else { 1000 } {
+        dbg!(i);
+        let mut vec =
+            crate::alloc::vec::Vec::new_in(crate::alloc::allocators::LibcAlloc::default());
+        let mut btree = ArcBTreeSet::new_in(crate::alloc::allocators::LibcAlloc::default());
+        for _ in 0..rng.gen_range(0..800) {
+            let val = rng.gen_range(0..100u8);
+            if vec.binary_search(&val).is_ok() {
+                assert_eq!(btree.insert(val), Some(val));
+            } else {
+                vec.push(val);
+                vec.sort();
+                assert_eq!(
+                    btree.insert(val),
+                    None,
+                    "The BTree contained an unexpected value: {btree:?}, {vec:?}"
+                );
+            }
+        }
+        vec.sort();
+        assert_eq!(vec.len(), btree.len());
+        let mut iter = vec.into_iter();
+        btree.for_each(|i| assert_eq!(Some(*i), iter.next()));
+        assert_eq!(iter.next(), None);
+    }
+}
+
+#[test]
+#[cfg(feature = "alloc-rs")]
+fn btree_insert_rs() {
+    use rand::Rng;
+    let mut rng = rand::thread_rng();
+    for i in 0..if cfg!(miri) { 5 } else { 1000 } {
+        dbg!(i);
+        let mut vec =
+            crate::alloc::vec::Vec::new_in(crate::alloc::allocators::RustAlloc::default());
+        let mut btree = ArcBTreeSet::new_in(crate::alloc::allocators::RustAlloc::default());
+        for _ in 0..rng.gen_range(0..800) {
+            let val = rng.gen_range(0..100);
+            if vec.binary_search(&val).is_ok() {
+                assert_eq!(
+                    btree.insert(val),
+                    Some(val),
+                    "btree: {btree:?}, vec: {vec:?}, val: {val}"
+                );
+            } else {
+                vec.push(val);
+                vec.sort();
+                assert_eq!(
+                    btree.insert(val),
+                    None,
+                    "The BTree contained an unexpected value: {btree:?}, {vec:?}"
+                );
+            }
+        }
+        vec.sort();
+        assert_eq!(vec.len(), btree.len());
+        let mut iter = vec.into_iter();
+        btree.for_each(|i| assert_eq!(Some(*i), iter.next()));
+        assert_eq!(iter.next(), None);
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn btree_map() {
+    use rand::Rng;
+    let mut rng = rand::thread_rng();
+    for _ in 0..if cfg!(miri) { 5 } else { 300 } {
+        let mut reference = std::collections::BTreeMap::new();
+        let mut map = ArcBTreeMap::<u8, u32>::new();
+        let mut snapshots = std::vec::Vec::new();
+        for _ in 0..rng.gen_range(0..500) {
+            let key = rng.gen_range(0..100u8);
+            let value = rng.gen();
+            match rng.gen_range(0..10) {
+                0..=3 => assert_eq!(map.insert(key, value), reference.insert(key, value)),
+                4..=6 => assert_eq!(map.remove(&key), reference.remove(&key)),
+                7 => assert_eq!(map.pop_first(), reference.pop_first()),
+                8 => assert_eq!(map.pop_last(), reference.pop_last()),
+                _ => snapshots.push((map.clone(), reference.clone())),
+            }
+            if let Some(root) = &map.0.root {
+                root.check(true);
+            }
+        }
+        assert_eq!(map.len(), reference.len());
+        assert!(map.iter().eq(reference.iter()));
+        assert!(map.iter().rev().eq(reference.iter().rev()));
+        assert!(map.keys().eq(reference.keys()));
+        assert!(map.values().rev().eq(reference.values().rev()));
+        let (a, b) = (rng.gen_range(0..100u8), rng.gen_range(0..100u8));
+        let (a, b) = (a.min(b), a.max(b));
+        assert!(map.range(a..b).eq(reference.range(a..b)));
+        assert!(map.range(a..=b).rev().eq(reference.range(a..=b).rev()));
+        assert!(map.range(..b).eq(reference.range(..b)));
+        assert!(map.range(a..).eq(reference.range(a..)));
+        let in_range = reference.range(a..b).count();
+        assert_eq!(map.range(a..b).size_hint(), (in_range, Some(in_range)));
+        let mut iter = map.iter();
+        let mut reference_iter = reference.iter();
+        loop {
+            assert_eq!(iter.size_hint(), reference_iter.size_hint());
+            let (pair, expected) = if rng.gen() {
+                (iter.next(), reference_iter.next())
+            } else {
+                (iter.next_back(), reference_iter.next_back())
+            };
+            assert_eq!(pair, expected);
+            if pair.is_none() {
+                break;
+            }
+        }
+        assert_eq!(map.first_key_value(), reference.first_key_value());
+        assert_eq!(map.last_key_value(), reference.last_key_value());
+        let keys: std::vec::Vec<u8> = map.keys().copied().collect();
+        let mut visited = std::vec::Vec::new();
+        map.retain(|key, _| {
+            visited.push(*key);
+            key % 3 != 0
+        });
+        reference.retain(|key, _| key % 3 != 0);
+        assert!(map.iter().eq(reference.iter()));
+        assert_eq!(visited, keys);
+        if let Some(root) = &map.0.root {
+            root.check(true);
+        }
+        for (snapshot, reference) in snapshots {
+            assert!(snapshot.iter().eq(reference.iter()));
+            assert!(snapshot.into_iter().eq(reference));
+        }
+    }
+}
+
+#[test]
+fn btree_structural_sharing() {
+    let map: ArcBTreeMap<u32, u32> = (0..1000).map(|i| (i, i)).collect();
+    let mut edited = map.clone();
+    assert_eq!(edited.remove(&0), Some(0));
+    assert_eq!(edited.remove(&0), None);
+    *edited.get_mut(&1).unwrap() = 2;
+    let (Some(original), Some(edited_root)) = (&map.0.root, &edited.0.root) else {
+        panic!("the maps shouldn't be empty")
+    };
+    assert!(!core::ptr::eq(&*original.0, &*edited_root.0));
+    let (Some(original), Some(edited_root)) = (&original.0.greater, &edited_root.0.greater) else {
+        panic!("the maps should have more than one node")
+    };
+    assert!(core::ptr::eq(&*original.0, &*edited_root.0));
+    assert_eq!(map.get(&0), Some(&0));
+    assert_eq!(map.get(&1), Some(&1));
+    assert_eq!(edited.get(&1), Some(&2));
+    assert_eq!(edited.len(), 999);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn btree_entries() {
+    let mut map = ArcBTreeMap::<std::string::String, u32>::new();
+    *map.entry("a".into()).or_default() += 1;
+    *map.entry("a".into()).or_insert(10) += 1;
+    map.entry("b".into()).and_modify(|v| *v += 1).or_insert(5);
+    map.entry("b".into()).and_modify(|v| *v += 1).or_insert(5);
+    assert_eq!(map.get("a"), Some(&2));
+    assert_eq!(map.get("b"), Some(&6));
+    match map.entry("a".into()) {
+        MapEntry::Occupied(entry) => assert_eq!(entry.remove_entry(), ("a".into(), 2)),
+        MapEntry::Vacant(_) => panic!("`a` should be in the map"),
+    }
+    assert!(!map.contains_key("a"));
+    let mut set: ArcBTreeSet<u32> = (0..100).rev().collect();
+    assert_eq!((set.first(), set.last()), (Some(&0), Some(&99)));
+    assert!(set.range(10..20).copied().eq(10..20));
+    assert_eq!(set.remove(&50), Some(50));
+    assert!(!set.contains(&50));
+    set.retain(|v| v % 2 == 0);
+    assert!(set
+        .iter()
+        .copied()
+        .eq((0..100).step_by(2).filter(|v| *v != 50)));
+    set.clear();
+    assert!(set.is_empty());
+    assert_eq!(set.pop_first(), None);
+}
+
+#[test]
+fn btree_iter() {
+    for len in 0..if cfg!(miri) { 20 } else { 300 } {
+        let set: ArcBTreeSet<u32, DefaultAllocator, false, 3> = (0..len).collect();
+        assert!(set.iter().copied().eq(0..len));
+        assert!(set.iter().rev().copied().eq((0..len).rev()));
+        let len = usize::try_from(len).unwrap();
+        let mut iter = set.iter();
+        for remaining in (0..=len).rev() {
+            assert_eq!(iter.size_hint(), (remaining, Some(remaining)));
+            if remaining % 2 == 0 {
+                iter.next();
+            } else {
+                iter.next_back();
+            }
+        }
+        assert_eq!(iter.next(), None);
+    }
+}
+
+#[test]
+fn btree_entries_inconsistent_ord() {
+    use core::sync::atomic::AtomicBool;
+    static REVERSED: AtomicBool = AtomicBool::new(false);
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct Key(u32);
+    impl PartialOrd for Key {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+    impl Ord for Key {
+        fn cmp(&self, other: &Self) -> Ordering {
+            let ordering = self.0.cmp(&other.0);
+            if REVERSED.load(AtomicOrdering::Relaxed) {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        }
+    }
+    let mut map: ArcBTreeMap<Key, u32> = (0..100).map(|i| (Key(i), i)).collect();
+    let snapshot = map.clone();
+    let MapEntry::Occupied(mut entry) = map.entry(Key(30)) else {
+        panic!("`30` should be in the map")
+    };
+    // The entry doesn't need to look its key up again, even though the keys are now sorted the wrong way.
+    REVERSED.store(true, AtomicOrdering::Relaxed);
+    *entry.get_mut() += 1;
+    assert_eq!(entry.key(), &Key(30));
+    assert_eq!(entry.get(), &31);
+    assert_eq!(entry.remove_entry(), (Key(30), 31));
+    REVERSED.store(false, AtomicOrdering::Relaxed);
+    assert_eq!(map.len(), 99);
+    assert_eq!(map.get(&Key(30)), None);
+    assert_eq!(snapshot.get(&Key(30)), Some(&30));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn btree_reports() {
+    use crate::IStable;
+    type Set<T, const REPLACE_ON_INSERT: bool, const SPLIT_LIMIT: usize> =
+        ArcBTreeSet<T, DefaultAllocator, REPLACE_ON_INSERT, SPLIT_LIMIT>;
+    let report = std::format!("{}", <Set<u8, false, 5> as IStable>::REPORT);
+    for field in ["entries", "len", "greater", "smaller", "value"] {
+        assert!(report.contains(field), "{field} is missing from {report}");
+    }
+    // The version isn't part of the ID, but is checked along with the rest of the report.
+    assert!(!<Set<u8, false, 5> as IStable>::REPORT
+        .is_compatible(<Set<u8, true, 5> as IStable>::REPORT));
+    assert_ne!(
+        <Set<u8, false, 5> as IStable>::ID,
+        <Set<u8, false, 7> as IStable>::ID
+    );
+    assert_ne!(
+        <Set<u8, false, 5> as IStable>::ID,
+        <Set<u16, false, 5> as IStable>::ID
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn atomic_btree_map() {
+    let _ = crate::AssertStable::<AtomicArcBTreeMap<u32, u32>>(core::marker::PhantomData);
+    let _ = crate::AssertStable::<AtomicArcBTreeSet<u32, false, 5>>(core::marker::PhantomData);
+    let map = AtomicArcBTreeMap::<u32, u32>::new();
+    let empty = map.load();
+    let mut first = empty.clone();
+    first.insert(0, 0);
+    assert!(map.compare_and_swap(&empty, first.clone()).is_ok());
+    let rejected = map.compare_and_swap(&empty, ArcBTreeMap::new());
+    assert!(rejected.is_err_and(|rejected| rejected.is_empty()));
+    assert_eq!(map.load(), first);
+    let writers = 4u32;
+    let increments = if cfg!(miri) { 10 } else { 1000 };
+    std::thread::scope(|scope| {
+        for writer in 0..writers {
+            let map = &map;
+            scope.spawn(move || {
+                for i in 0..increments {
+                    map.update(|mut snapshot| {
+                        *snapshot.entry(0).or_default() += 1;
+                        snapshot.insert(writer.wrapping_add(1), i);
+                        snapshot
+                    });
+                }
+            });
+        }
+        let map = &map;
+        scope.spawn(move || {
+            let mut last = 0;
+            while last < writers * increments {
+                let snapshot = map.load();
+                let current = snapshot.get(&0).copied().unwrap_or(0);
+                assert!(current >= last);
+                last = current;
+                assert_eq!(snapshot.get(&0), Some(&current));
+            }
+        });
+    });
+    let snapshot = map.load();
+    assert_eq!(snapshot.get(&0), Some(&(writers * increments)));
+    assert!(snapshot
+        .values()
+        .skip(1)
+        .all(|last| *last == increments - 1));
+    assert_eq!(first.get(&0), Some(&0));
+    assert_eq!(map.swap(ArcBTreeMap::new()), snapshot);
+    assert!(map.load().is_empty());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn atomic_btree_writers_progress() {
+    use core::sync::atomic::AtomicBool;
+    let set = AtomicArcBTreeSet::<u32, false, 5>::new();
+    let done = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                while !done.load(AtomicOrdering::Relaxed) {
+                    drop(set.load());
+                }
+            });
+        }
+        // Readers keep loading in the meantime: writers only wait for the loads they may race with.
+        for i in 0..if cfg!(miri) { 10 } else { 1000 } {
+            set.update(|mut snapshot| {
+                snapshot.insert(i);
+                snapshot
+            });
+        }
+        done.store(true, AtomicOrdering::Relaxed);
+    });
+    assert_eq!(set.load().len(), if cfg!(miri) { 10 } else { 1000 });
 }
 
 // #[test]
@@ -0,0 +1,1227 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   Pierre Avital, <pierre.avital@me.com>
+//
+
+use core::{
+    borrow::Borrow,
+    fmt::Debug,
+    hash::{BuildHasher, Hash, Hasher},
+    marker::PhantomData,
+};
+
+use super::raw_table::{RawDrain, RawIntoIter, RawIter, RawTable};
+use crate::{
+    alloc::{AllocationError, DefaultAllocator, IAlloc},
+    tuple::Tuple2,
+};
+
+/// The [`BuildHasher`] used by [`HashMap`] and [`HashSet`](super::HashSet) unless specified otherwise.
+pub type DefaultHashBuilder = SipHashBuilder;
+
+/// An ABI-stable [`BuildHasher`] for [SipHash-1-3](https://en.wikipedia.org/wiki/SipHash), the algorithm used by `std`'s
+/// `HashMap` at the time of writing.
+///
+/// Since the builder's keys are stored in the map, and the algorithm is implemented by `stabby`, every library
+/// sharing a map will hash its keys identically, as long as the keys' [`Hash`] implementations agree.
+#[crate::stabby]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SipHashBuilder {
+    k0: u64,
+    k1: u64,
+}
+impl SipHashBuilder {
+    /// Constructs a builder with fixed keys, producing the same hashes on every run.
+    ///
+    /// Prefer [`SipHashBuilder::random`] for maps whose keys may be chosen by an attacker.
+    pub const fn with_keys(k0: u64, k1: u64) -> Self {
+        Self { k0, k1 }
+    }
+    /// Constructs a builder with random keys, obtained from `std`'s `RandomState`.
+    #[cfg(feature = "std")]
+    pub fn random() -> Self {
+        let state = std::collections::hash_map::RandomState::new();
+        Self {
+            k0: state.hash_one(0u8),
+            k1: state.hash_one(1u8),
+        }
+    }
+    /// The keys of the builder.
+    pub const fn keys(&self) -> (u64, u64) {
+        (self.k0, self.k1)
+    }
+}
+impl Default for SipHashBuilder {
+    /// Uses random keys if the `std` feature is enabled, and `(0, 0)` otherwise.
+    fn default() -> Self {
+        #[cfg(feature = "std")]
+        {
+            Self::random()
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            Self::with_keys(0, 0)
+        }
+    }
+}
+impl BuildHasher for SipHashBuilder {
+    type Hasher = SipHasher13;
+    fn build_hasher(&self) -> SipHasher13 {
+        SipHasher13::new_with_keys(self.k0, self.k1)
+    }
+}
+
+/// An implementation of [SipHash-1-3](https://en.wikipedia.org/wiki/SipHash).
+#[crate::stabby]
+#[derive(Clone, Copy, Debug)]
+pub struct SipHasher13 {
+    v0: u64,
+    v1: u64,
+    v2: u64,
+    v3: u64,
+    tail: u64,
+    ntail: usize,
+    length: usize,
+}
+impl SipHasher13 {
+    /// Constructs a hasher with the provided keys.
+    pub const fn new_with_keys(k0: u64, k1: u64) -> Self {
+        Self {
+            v0: k0 ^ 0x736f6d6570736575,
+            v1: k1 ^ 0x646f72616e646f6d,
+            v2: k0 ^ 0x6c7967656e657261,
+            v3: k1 ^ 0x7465646279746573,
+            tail: 0,
+            ntail: 0,
+            length: 0,
+        }
+    }
+    #[rustversion::attr(since(1.83), const)]
+    fn round(&mut self) {
+        self.v0 = self.v0.wrapping_add(self.v1);
+        self.v1 = self.v1.rotate_left(13) ^ self.v0;
+        self.v0 = self.v0.rotate_left(32);
+        self.v2 = self.v2.wrapping_add(self.v3);
+        self.v3 = self.v3.rotate_left(16) ^ self.v2;
+        self.v0 = self.v0.wrapping_add(self.v3);
+        self.v3 = self.v3.rotate_left(21) ^ self.v0;
+        self.v2 = self.v2.wrapping_add(self.v1);
+        self.v1 = self.v1.rotate_left(17) ^ self.v2;
+        self.v2 = self.v2.rotate_left(32);
+    }
+    #[rustversion::attr(since(1.83), const)]
+    fn compress(&mut self, word: u64) {
+        self.v3 ^= word;
+        self.round();
+        self.v0 ^= word;
+    }
+}
+impl Hasher for SipHasher13 {
+    fn write(&mut self, bytes: &[u8]) {
+        self.length = self.length.wrapping_add(bytes.len());
+        let mut bytes = bytes;
+        while let Some((&byte, rest)) = bytes.split_first() {
+            if self.ntail == 0 && bytes.len() >= 8 {
+                let (head, rest) = bytes.split_at(8);
+                let mut word = [0; 8];
+                word.copy_from_slice(head);
+                self.compress(u64::from_le_bytes(word));
+                bytes = rest;
+                continue;
+            }
+            self.tail |= u64::from(byte) << (self.ntail.wrapping_mul(8));
+            self.ntail = self.ntail.wrapping_add(1);
+            if self.ntail == 8 {
+                self.compress(self.tail);
+                self.tail = 0;
+                self.ntail = 0;
+            }
+            bytes = rest;
+        }
+    }
+    fn finish(&self) -> u64 {
+        let mut this = *self;
+        let last = ((self.length as u64 & 0xff) << 56) | self.tail;
+        this.compress(last);
+        this.v2 ^= 0xff;
+        this.round();
+        this.round();
+        this.round();
+        this.v0 ^ this.v1 ^ this.v2 ^ this.v3
+    }
+}
+
+/// An ABI-stable hash map.
+///
+/// # Layout
+/// The map is a `#[repr(C)]` struct made of its table and its hasher. The table holds:
+/// - a pointer to its allocation, which starts with an [`AllocPrefix`](crate::alloc::AllocPrefix), followed by
+///   `buckets` slots of [`Tuple2<K, V>`], and `buckets + 8` control bytes,
+/// - the number of slots (`usize`), which is either 0 (in which case nothing is allocated), or a power of 2 no lower than 8,
+/// - the number of entries (`usize`),
+/// - the number of free slots that may still be filled before the table grows (`usize`),
+/// - the allocator.
+///
+/// The control byte at index `i` describes the slot at index `i`: `0xff` if it has been empty since the last rehash, `0x80`
+/// if its entry was removed, and otherwise the 7 highest bits of its key's hash. The last 8 control bytes mirror the first 8.
+///
+/// Looking up a key starts at the slot designated by the lowest bits of its hash, scanning the control bytes 8 at a time,
+/// and moves on to groups that are triangularly further (after 8, 24, 48... slots, wrapping around the table) until a group
+/// containing an empty slot is found.
+///
+/// Any library using this layout, the same hasher and the same [`Hash`] implementation for `K` may read and modify the map.
+#[crate::stabby]
+pub struct HashMap<K, V, S = DefaultHashBuilder, Alloc: IAlloc = DefaultAllocator> {
+    table: RawTable<Tuple2<K, V>, Alloc>,
+    hasher: S,
+}
+
+#[cfg(not(stabby_default_alloc = "disabled"))]
+impl<K, V> HashMap<K, V> {
+    /// Constructs an empty map with the default allocator and hasher. This doesn't allocate.
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+    /// Constructs an empty map with the default allocator and hasher, able to hold `capacity` entries without reallocating.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
+    }
+}
+#[cfg(not(stabby_default_alloc = "disabled"))]
+impl<K, V, S> HashMap<K, V, S> {
+    /// Constructs an empty map with the default allocator and `hasher`. This doesn't allocate.
+    pub const fn with_hasher(hasher: S) -> Self {
+        Self::with_hasher_in(hasher, DefaultAllocator::new())
+    }
+    /// Constructs an empty map with the default allocator and `hasher`, able to hold `capacity` entries without reallocating.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        Self::with_capacity_and_hasher_in(capacity, hasher, DefaultAllocator::new())
+    }
+}
+impl<K, V, S: Default, Alloc: IAlloc> HashMap<K, V, S, Alloc> {
+    /// Constructs an empty map in `alloc`. This doesn't allocate.
+    pub fn new_in(alloc: Alloc) -> Self {
+        Self::with_hasher_in(S::default(), alloc)
+    }
+    /// Constructs an empty map in `alloc`, able to hold `capacity` entries without reallocating.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn with_capacity_in(capacity: usize, alloc: Alloc) -> Self {
+        Self::with_capacity_and_hasher_in(capacity, S::default(), alloc)
+    }
+}
+impl<K, V, S, Alloc: IAlloc> HashMap<K, V, S, Alloc> {
+    /// Constructs an empty map in `alloc`, using `hasher` to hash its keys. This doesn't allocate.
+    pub const fn with_hasher_in(hasher: S, alloc: Alloc) -> Self {
+        Self {
+            table: RawTable::new_in(alloc),
+            hasher,
+        }
+    }
+    /// Constructs an empty map in `alloc`, able to hold `capacity` entries without reallocating.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn with_capacity_and_hasher_in(capacity: usize, hasher: S, alloc: Alloc) -> Self {
+        match Self::try_with_capacity_and_hasher_in(capacity, hasher, alloc) {
+            Ok(this) => this,
+            Err(_) => panic!("Failed to allocate a HashMap with capacity {capacity}"),
+        }
+    }
+    /// Constructs an empty map in `alloc`, able to hold `capacity` entries without reallocating.
+    ///
+    /// # Errors
+    /// Returns the hasher and the allocator if the allocator couldn't provide a sufficient allocation.
+    pub fn try_with_capacity_and_hasher_in(
+        capacity: usize,
+        hasher: S,
+        alloc: Alloc,
+    ) -> Result<Self, (S, Alloc)> {
+        match RawTable::try_with_capacity_in(capacity, alloc) {
+            Ok(table) => Ok(Self { table, hasher }),
+            Err((alloc, _)) => Err((hasher, alloc)),
+        }
+    }
+    /// The number of entries in the map.
+    pub const fn len(&self) -> usize {
+        self.table.len()
+    }
+    /// Returns `true` if the map has no entries.
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// The number of entries the map can hold without reallocating.
+    pub const fn capacity(&self) -> usize {
+        self.table.capacity()
+    }
+    /// The map's hasher.
+    pub const fn hasher(&self) -> &S {
+        &self.hasher
+    }
+    /// The map's allocator.
+    pub const fn allocator(&self) -> &Alloc {
+        self.table.allocator()
+    }
+    /// Iterates over the map's entries, in arbitrary order.
+    pub const fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            iter: self.table.raw_iter(),
+            marker: PhantomData,
+        }
+    }
+    /// Iterates mutably over the map's entries, in arbitrary order.
+    #[rustversion::attr(since(1.83), const)]
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            iter: self.table.raw_iter(),
+            marker: PhantomData,
+        }
+    }
+    /// Iterates over the map's keys, in arbitrary order.
+    pub const fn keys(&self) -> Keys<'_, K, V> {
+        Keys { iter: self.iter() }
+    }
+    /// Iterates over the map's values, in arbitrary order.
+    pub const fn values(&self) -> Values<'_, K, V> {
+        Values { iter: self.iter() }
+    }
+    /// Iterates mutably over the map's values, in arbitrary order.
+    #[rustversion::attr(since(1.83), const)]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            iter: self.iter_mut(),
+        }
+    }
+    /// Consumes the map into an iterator over its keys.
+    pub fn into_keys(self) -> IntoKeys<K, V, Alloc> {
+        IntoKeys {
+            iter: self.into_iter(),
+        }
+    }
+    /// Consumes the map into an iterator over its values.
+    pub fn into_values(self) -> IntoValues<K, V, Alloc> {
+        IntoValues {
+            iter: self.into_iter(),
+        }
+    }
+    /// Removes every entry from the map, returning them as an iterator.
+    ///
+    /// The map keeps its allocation. Entries that haven't been yielded when the iterator is dropped are dropped as well.
+    #[rustversion::attr(since(1.83), const)]
+    pub fn drain(&mut self) -> Drain<'_, K, V, Alloc> {
+        Drain {
+            iter: self.table.drain(),
+        }
+    }
+    /// Keeps only the entries for which `f` returns `true`.
+    pub fn retain(&mut self, mut f: impl FnMut(&K, &mut V) -> bool) {
+        self.table.retain(|Tuple2(k, v)| f(k, v))
+    }
+    /// Removes every entry from the map, keeping its allocation.
+    pub fn clear(&mut self) {
+        self.table.clear()
+    }
+}
+impl<K: Hash + Eq, V, S: BuildHasher, Alloc: IAlloc> HashMap<K, V, S, Alloc> {
+    fn hash<Q: Hash + ?Sized>(&self, key: &Q) -> u64 {
+        self.hasher.hash_one(key)
+    }
+    fn find<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+    {
+        self.table
+            .find(self.hash(key), |Tuple2(k, _)| k.borrow() == key)
+    }
+    /// Ensures that `additional` more entries can be inserted without reallocating.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn reserve(&mut self, additional: usize) {
+        self.try_reserve(additional).unwrap();
+    }
+    /// Ensures that `additional` more entries can be inserted without reallocating.
+    ///
+    /// # Errors
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), AllocationError> {
+        let hasher = &self.hasher;
+        self.table
+            .try_reserve(additional, |Tuple2(k, _)| hasher.hash_one(k))
+    }
+    /// Shrinks the map's allocation as much as possible.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide the new allocation.
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0)
+    }
+    /// Shrinks the map's allocation, keeping room for at least `min_capacity` entries.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide the new allocation.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        let hasher = &self.hasher;
+        self.table
+            .shrink_to(min_capacity, |Tuple2(k, _)| hasher.hash_one(k))
+            .unwrap();
+    }
+    /// Returns a reference to the value associated with `key`.
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+    /// Returns references to the key and value of the entry associated with `key`.
+    pub fn get_key_value<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+    {
+        let index = self.find(key)?;
+        // SAFETY: `find` only returns indices of full slots.
+        let Tuple2(k, v) = unsafe { self.table.bucket(index).as_ref() };
+        Some((k, v))
+    }
+    /// Returns a mutable reference to the value associated with `key`.
+    pub fn get_mut<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        let index = self.find(key)?;
+        // SAFETY: `find` only returns indices of full slots.
+        Some(unsafe { &mut self.table.bucket(index).as_mut().1 })
+    }
+    /// Returns `true` if the map contains an entry for `key`.
+    pub fn contains_key<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.find(key).is_some()
+    }
+    /// Associates `value` with `key`, returning the value that was previously associated with it.
+    ///
+    /// If `key` was already present, it is not replaced.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+    /// Removes the entry associated with `key`, returning its value.
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+    /// Removes the entry associated with `key`, returning it.
+    pub fn remove_entry<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+    {
+        let index = self.find(key)?;
+        // SAFETY: `find` only returns indices of full slots.
+        Some(unsafe { self.table.remove(index) }.into())
+    }
+    /// Returns the entry associated with `key`, to inspect or modify it in place.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation to hold one more entry.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S, Alloc> {
+        let hash = self.hash(&key);
+        match self.table.find(hash, |Tuple2(k, _)| *k == key) {
+            Some(index) => Entry::Occupied(OccupiedEntry { map: self, index }),
+            None => {
+                self.reserve(1);
+                Entry::Vacant(VacantEntry {
+                    map: self,
+                    hash,
+                    key,
+                })
+            }
+        }
+    }
+}
+
+/// A view into an entry of a [`HashMap`], obtained through [`HashMap::entry`].
+pub enum Entry<'a, K, V, S, Alloc: IAlloc> {
+    /// The map contains an entry for the key.
+    Occupied(OccupiedEntry<'a, K, V, S, Alloc>),
+    /// The map doesn't contain an entry for the key.
+    Vacant(VacantEntry<'a, K, V, S, Alloc>),
+}
+impl<'a, K, V, S, Alloc: IAlloc> Entry<'a, K, V, S, Alloc> {
+    /// The entry's key.
+    #[rustversion::attr(since(1.73), const)]
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+    /// Inserts `default` if the entry is vacant, and returns a mutable reference to its value.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+    /// Inserts the result of `default` if the entry is vacant, and returns a mutable reference to its value.
+    pub fn or_insert_with(self, default: impl FnOnce() -> V) -> &'a mut V {
+        self.or_insert_with_key(|_| default())
+    }
+    /// Inserts the result of `default` if the entry is vacant, and returns a mutable reference to its value.
+    ///
+    /// `default` is passed a reference to the entry's key.
+    pub fn or_insert_with_key(self, default: impl FnOnce(&K) -> V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let value = default(entry.key());
+                entry.insert(value)
+            }
+        }
+    }
+    /// Inserts `V::default()` if the entry is vacant, and returns a mutable reference to its value.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+    /// Calls `f` on the entry's value if it's occupied.
+    pub fn and_modify(mut self, f: impl FnOnce(&mut V)) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut())
+        }
+        self
+    }
+}
+impl<K: Debug, V: Debug, S, Alloc: IAlloc> Debug for Entry<'_, K, V, S, Alloc> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Entry::Occupied(entry) => f.debug_tuple("Entry").field(entry).finish(),
+            Entry::Vacant(entry) => f.debug_tuple("Entry").field(entry).finish(),
+        }
+    }
+}
+
+/// A view into an occupied entry of a [`HashMap`].
+pub struct OccupiedEntry<'a, K, V, S, Alloc: IAlloc> {
+    map: &'a mut HashMap<K, V, S, Alloc>,
+    index: usize,
+}
+impl<'a, K, V, S, Alloc: IAlloc> OccupiedEntry<'a, K, V, S, Alloc> {
+    #[rustversion::attr(since(1.73), const)]
+    fn entry(&self) -> &Tuple2<K, V> {
+        // SAFETY: `index` is a full slot of the map, which is borrowed mutably by `self`.
+        unsafe { self.map.table.bucket(self.index).as_ref() }
+    }
+    #[rustversion::attr(since(1.83), const)]
+    fn entry_mut(&mut self) -> &mut Tuple2<K, V> {
+        // SAFETY: `index` is a full slot of the map, which is borrowed mutably by `self`.
+        unsafe { self.map.table.bucket(self.index).as_mut() }
+    }
+    /// The key stored in the map.
+    #[rustversion::attr(since(1.73), const)]
+    pub fn key(&self) -> &K {
+        &self.entry().0
+    }
+    /// The entry's value.
+    #[rustversion::attr(since(1.73), const)]
+    pub fn get(&self) -> &V {
+        &self.entry().1
+    }
+    /// A mutable reference to the entry's value.
+    #[rustversion::attr(since(1.83), const)]
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.entry_mut().1
+    }
+    /// Converts the entry into a mutable reference to its value, with the lifetime of the map's borrow.
+    #[rustversion::attr(since(1.83), const)]
+    pub fn into_mut(self) -> &'a mut V {
+        // SAFETY: `index` is a full slot of the map, which is borrowed mutably for `'a`.
+        unsafe { &mut self.map.table.bucket(self.index).as_mut().1 }
+    }
+    /// Replaces the entry's value, returning the previous one.
+    #[rustversion::attr(since(1.83), const)]
+    pub fn insert(&mut self, value: V) -> V {
+        core::mem::replace(self.get_mut(), value)
+    }
+    /// Removes the entry from the map, returning its value.
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+    /// Removes the entry from the map, returning it.
+    pub fn remove_entry(self) -> (K, V) {
+        // SAFETY: `index` is a full slot of the map.
+        unsafe { self.map.table.remove(self.index) }.into()
+    }
+}
+impl<K: Debug, V: Debug, S, Alloc: IAlloc> Debug for OccupiedEntry<'_, K, V, S, Alloc> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("OccupiedEntry")
+            .field("key", self.key())
+            .field("value", self.get())
+            .finish()
+    }
+}
+
+/// A view into a vacant entry of a [`HashMap`].
+pub struct VacantEntry<'a, K, V, S, Alloc: IAlloc> {
+    map: &'a mut HashMap<K, V, S, Alloc>,
+    hash: u64,
+    key: K,
+}
+impl<'a, K, V, S, Alloc: IAlloc> VacantEntry<'a, K, V, S, Alloc> {
+    /// The key that would be inserted.
+    pub const fn key(&self) -> &K {
+        &self.key
+    }
+    /// Takes ownership of the key.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+    /// Inserts `value` with the entry's key, returning a mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        // SAFETY: `HashMap::entry` reserved room for one more entry before constructing `self`.
+        unsafe {
+            let index = self
+                .map
+                .table
+                .insert_no_grow(self.hash, Tuple2(self.key, value));
+            &mut self.map.table.bucket(index).as_mut().1
+        }
+    }
+}
+impl<K: Debug, V, S, Alloc: IAlloc> Debug for VacantEntry<'_, K, V, S, Alloc> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("VacantEntry").field(self.key()).finish()
+    }
+}
+
+/// An iterator over the entries of a [`HashMap`].
+pub struct Iter<'a, K, V> {
+    iter: RawIter<Tuple2<K, V>>,
+    marker: PhantomData<&'a Tuple2<K, V>>,
+}
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.iter.next()?;
+        // SAFETY: the map is borrowed for `'a`.
+        let Tuple2(k, v) = unsafe { self.iter.bucket(index).as_ref() };
+        Some((k, v))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+impl<K, V> core::iter::FusedIterator for Iter<'_, K, V> {}
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            iter: self.iter.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+impl<K: Debug, V: Debug> Debug for Iter<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+// SAFETY: `Iter` is equivalent to `&HashMap`.
+unsafe impl<K: Sync, V: Sync> Send for Iter<'_, K, V> {}
+// SAFETY: `Iter` is equivalent to `&HashMap`.
+unsafe impl<K: Sync, V: Sync> Sync for Iter<'_, K, V> {}
+
+/// A mutable iterator over the entries of a [`HashMap`].
+pub struct IterMut<'a, K, V> {
+    iter: RawIter<Tuple2<K, V>>,
+    marker: PhantomData<&'a mut Tuple2<K, V>>,
+}
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.iter.next()?;
+        // SAFETY: the map is borrowed mutably for `'a`, and each entry is yielded once.
+        let Tuple2(k, v) = unsafe { self.iter.bucket(index).as_mut() };
+        Some((k, v))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+impl<K, V> core::iter::FusedIterator for IterMut<'_, K, V> {}
+// SAFETY: `IterMut` is equivalent to `&mut HashMap`.
+unsafe impl<K: Send, V: Send> Send for IterMut<'_, K, V> {}
+// SAFETY: `IterMut` is equivalent to `&mut HashMap`.
+unsafe impl<K: Sync, V: Sync> Sync for IterMut<'_, K, V> {}
+
+/// An iterator over the keys of a [`HashMap`].
+pub struct Keys<'a, K, V> {
+    iter: Iter<'a, K, V>,
+}
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, _)| k)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
+impl<K, V> core::iter::FusedIterator for Keys<'_, K, V> {}
+impl<K, V> Clone for Keys<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+/// An iterator over the values of a [`HashMap`].
+pub struct Values<'a, K, V> {
+    iter: Iter<'a, K, V>,
+}
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, v)| v)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+impl<K, V> core::iter::FusedIterator for Values<'_, K, V> {}
+impl<K, V> Clone for Values<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+/// A mutable iterator over the values of a [`HashMap`].
+pub struct ValuesMut<'a, K, V> {
+    iter: IterMut<'a, K, V>,
+}
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, v)| v)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<K, V> ExactSizeIterator for ValuesMut<'_, K, V> {}
+impl<K, V> core::iter::FusedIterator for ValuesMut<'_, K, V> {}
+
+/// An iterator over the entries of a [`HashMap`], by value.
+pub struct IntoIter<K, V, Alloc: IAlloc = DefaultAllocator> {
+    iter: RawIntoIter<Tuple2<K, V>, Alloc>,
+}
+impl<K, V, Alloc: IAlloc> Iterator for IntoIter<K, V, Alloc> {
+    type Item = (K, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(Into::into)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<K, V, Alloc: IAlloc> ExactSizeIterator for IntoIter<K, V, Alloc> {}
+impl<K, V, Alloc: IAlloc> core::iter::FusedIterator for IntoIter<K, V, Alloc> {}
+// SAFETY: `IntoIter` owns the remaining entries.
+unsafe impl<K: Send, V: Send, Alloc: IAlloc + Send> Send for IntoIter<K, V, Alloc> {}
+// SAFETY: `IntoIter` owns the remaining entries.
+unsafe impl<K: Sync, V: Sync, Alloc: IAlloc + Sync> Sync for IntoIter<K, V, Alloc> {}
+
+/// An iterator over the keys of a [`HashMap`], by value.
+pub struct IntoKeys<K, V, Alloc: IAlloc = DefaultAllocator> {
+    iter: IntoIter<K, V, Alloc>,
+}
+impl<K, V, Alloc: IAlloc> Iterator for IntoKeys<K, V, Alloc> {
+    type Item = K;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, _)| k)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<K, V, Alloc: IAlloc> ExactSizeIterator for IntoKeys<K, V, Alloc> {}
+impl<K, V, Alloc: IAlloc> core::iter::FusedIterator for IntoKeys<K, V, Alloc> {}
+
+/// An iterator over the values of a [`HashMap`], by value.
+pub struct IntoValues<K, V, Alloc: IAlloc = DefaultAllocator> {
+    iter: IntoIter<K, V, Alloc>,
+}
+impl<K, V, Alloc: IAlloc> Iterator for IntoValues<K, V, Alloc> {
+    type Item = V;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, v)| v)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<K, V, Alloc: IAlloc> ExactSizeIterator for IntoValues<K, V, Alloc> {}
+impl<K, V, Alloc: IAlloc> core::iter::FusedIterator for IntoValues<K, V, Alloc> {}
+
+/// A draining iterator over the entries of a [`HashMap`], obtained through [`HashMap::drain`].
+pub struct Drain<'a, K, V, Alloc: IAlloc = DefaultAllocator> {
+    iter: RawDrain<'a, Tuple2<K, V>, Alloc>,
+}
+impl<K, V, Alloc: IAlloc> Iterator for Drain<'_, K, V, Alloc> {
+    type Item = (K, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(Into::into)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<K, V, Alloc: IAlloc> ExactSizeIterator for Drain<'_, K, V, Alloc> {}
+impl<K, V, Alloc: IAlloc> core::iter::FusedIterator for Drain<'_, K, V, Alloc> {}
+
+impl<K, V, S, Alloc: IAlloc> IntoIterator for HashMap<K, V, S, Alloc> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, Alloc>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            iter: self.table.into_iter(),
+        }
+    }
+}
+impl<'a, K, V, S, Alloc: IAlloc> IntoIterator for &'a HashMap<K, V, S, Alloc> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, K, V, S, Alloc: IAlloc> IntoIterator for &'a mut HashMap<K, V, S, Alloc> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+impl<K: Hash + Eq, V, S: BuildHasher, Alloc: IAlloc> Extend<(K, V)> for HashMap<K, V, S, Alloc> {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        let iter = iter.into_iter();
+        // Keys may be duplicated: only reserve for half of them if the map already has entries, like `std` does.
+        let additional = if self.is_empty() {
+            iter.size_hint().0
+        } else {
+            iter.size_hint().0.wrapping_add(1) / 2
+        };
+        self.reserve(additional);
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+impl<'a, K: Hash + Eq + Copy, V: Copy, S: BuildHasher, Alloc: IAlloc> Extend<(&'a K, &'a V)>
+    for HashMap<K, V, S, Alloc>
+{
+    fn extend<T: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: T) {
+        self.extend(iter.into_iter().map(|(k, v)| (*k, *v)))
+    }
+}
+impl<K: Hash + Eq, V, S: BuildHasher + Default, Alloc: IAlloc + Default> FromIterator<(K, V)>
+    for HashMap<K, V, S, Alloc>
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut this = Self::new_in(Alloc::default());
+        this.extend(iter);
+        this
+    }
+}
+impl<K: Hash + Eq, V, S: BuildHasher + Default, Alloc: IAlloc + Default, const N: usize>
+    From<[(K, V); N]> for HashMap<K, V, S, Alloc>
+{
+    fn from(value: [(K, V); N]) -> Self {
+        value.into_iter().collect()
+    }
+}
+impl<K, V, S: Default, Alloc: IAlloc + Default> Default for HashMap<K, V, S, Alloc> {
+    fn default() -> Self {
+        Self::new_in(Alloc::default())
+    }
+}
+impl<K: Clone, V: Clone, S: Clone, Alloc: IAlloc + Clone> Clone for HashMap<K, V, S, Alloc> {
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    fn clone(&self) -> Self {
+        Self {
+            table: self.table.try_clone().unwrap(),
+            hasher: self.hasher.clone(),
+        }
+    }
+}
+impl<K: Hash + Eq, V: PartialEq, S: BuildHasher, Alloc: IAlloc> PartialEq
+    for HashMap<K, V, S, Alloc>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+impl<K: Hash + Eq, V: Eq, S: BuildHasher, Alloc: IAlloc> Eq for HashMap<K, V, S, Alloc> {}
+impl<K: Debug, V: Debug, S, Alloc: IAlloc> Debug for HashMap<K, V, S, Alloc> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+impl<K: Hash + Eq + Borrow<Q>, Q: Hash + Eq + ?Sized, V, S: BuildHasher, Alloc: IAlloc>
+    core::ops::Index<&Q> for HashMap<K, V, S, Alloc>
+{
+    type Output = V;
+    /// # Panics
+    /// If `key` isn't present in the map.
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("key not found in HashMap")
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+    use serde::{de::Visitor, Deserialize, Serialize};
+    impl<K: Serialize, V: Serialize, S, Alloc: IAlloc> Serialize for HashMap<K, V, S, Alloc> {
+        fn serialize<Ser>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error>
+        where
+            Ser: serde::Serializer,
+        {
+            serializer.collect_map(self.iter())
+        }
+    }
+    impl<
+            'a,
+            K: Deserialize<'a> + Hash + Eq,
+            V: Deserialize<'a>,
+            S: BuildHasher + Default,
+            Alloc: IAlloc + Default,
+        > Deserialize<'a> for HashMap<K, V, S, Alloc>
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'a>,
+        {
+            deserializer.deserialize_map(HashMapVisitor(PhantomData))
+        }
+    }
+    pub struct HashMapVisitor<K, V, S, Alloc>(PhantomData<(K, V, S, Alloc)>);
+    impl<
+            'a,
+            K: Deserialize<'a> + Hash + Eq,
+            V: Deserialize<'a>,
+            S: BuildHasher + Default,
+            Alloc: IAlloc + Default,
+        > Visitor<'a> for HashMapVisitor<K, V, S, Alloc>
+    {
+        type Value = HashMap<K, V, S, Alloc>;
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            formatter.write_str("A map")
+        }
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::MapAccess<'a>,
+        {
+            let mut this = HashMap::with_capacity_and_hasher_in(
+                map.size_hint().unwrap_or(0),
+                S::default(),
+                Alloc::default(),
+            );
+            while let Some((k, v)) = map.next_entry()? {
+                this.insert(k, v);
+            }
+            Ok(this)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn siphash() {
+        // `DefaultHasher::new` is SipHash-1-3 with null keys.
+        let mut std_hasher = std::collections::hash_map::DefaultHasher::new();
+        let mut hasher = SipHashBuilder::with_keys(0, 0).build_hasher();
+        for chunk in [&b"stabby"[..], b"", b"0123456789abcdefghij", b"x"] {
+            std_hasher.write(chunk);
+            hasher.write(chunk);
+            assert_eq!(std_hasher.finish(), hasher.finish());
+        }
+    }
+
+    #[test]
+    fn capacities() {
+        assert_eq!(HashMap::<u32, u32>::with_capacity(0).capacity(), 0);
+        assert_eq!(HashMap::<u32, u32>::with_capacity(7).capacity(), 7);
+        assert_eq!(HashMap::<u32, u32>::with_capacity(8).capacity(), 14);
+        assert_eq!(HashMap::<u32, u32>::with_capacity(14).capacity(), 14);
+        // Churning through keys only leaves tombstones behind, which are cleared by rehashing in place.
+        let mut map = HashMap::with_capacity(14);
+        for key in 0..1000u32 {
+            map.insert(key, key);
+            assert_eq!(map.remove(&key), Some(key));
+        }
+        assert_eq!(map.capacity(), 14);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash_map() {
+        use rand::Rng;
+        let _ = crate::AssertStable::<HashMap<u32, crate::alloc::string::String>>(PhantomData);
+        let mut rng = rand::thread_rng();
+        for _ in 0..if cfg!(miri) { 5 } else { 200 } {
+            let mut reference = std::collections::HashMap::new();
+            let mut map = HashMap::new();
+            for _ in 0..rng.gen_range(0..1000) {
+                let key = rng.gen_range(0..200u16);
+                match rng.gen_range(0..4) {
+                    0 => assert_eq!(map.remove(&key), reference.remove(&key)),
+                    1 => assert_eq!(map.get(&key), reference.get(&key)),
+                    _ => {
+                        let value = rng.gen::<u32>();
+                        assert_eq!(map.insert(key, value), reference.insert(key, value))
+                    }
+                }
+                assert_eq!(map.len(), reference.len());
+                assert!(map.capacity() >= map.len());
+            }
+            let mut entries = map
+                .iter()
+                .map(|(k, v)| (*k, *v))
+                .collect::<std::vec::Vec<_>>();
+            let mut expected = reference.into_iter().collect::<std::vec::Vec<_>>();
+            entries.sort();
+            expected.sort();
+            assert_eq!(entries, expected);
+            let clone = map.clone();
+            assert_eq!(clone, map);
+            map.retain(|k, _| k % 2 == 0);
+            assert!(map.keys().all(|k| k % 2 == 0));
+            map.shrink_to_fit();
+            assert!(clone
+                .iter()
+                .all(|(k, v)| k % 2 == 1 || map.get(k) == Some(v)));
+            let mut drained = map.drain().collect::<std::vec::Vec<_>>();
+            drained.sort();
+            assert!(drained
+                .iter()
+                .copied()
+                .eq(expected.into_iter().filter(|(k, _)| k % 2 == 0)));
+            assert!(map.is_empty());
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn differential() {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        for _ in 0..if cfg!(miri) { 5 } else { 100 } {
+            let mut reference = std::collections::HashMap::new();
+            let mut map = HashMap::new();
+            for _ in 0..rng.gen_range(0..500) {
+                let key = rng.gen_range(0..100u16);
+                match rng.gen_range(0..16) {
+                    0..=5 => {
+                        let value = rng.gen::<u32>();
+                        assert_eq!(map.insert(key, value), reference.insert(key, value))
+                    }
+                    6..=8 => assert_eq!(map.remove(&key), reference.remove(&key)),
+                    9 => {
+                        let modulo = rng.gen_range(1..5u16);
+                        map.retain(|k, v| {
+                            *v = v.wrapping_add(1);
+                            k % modulo != 0
+                        });
+                        reference.retain(|k, v| {
+                            *v = v.wrapping_add(1);
+                            k % modulo != 0
+                        });
+                    }
+                    10 => {
+                        let additional = rng.gen_range(0..100);
+                        map.reserve(additional);
+                        assert!(map.capacity() >= map.len().wrapping_add(additional));
+                    }
+                    11 => {
+                        let (min_capacity, capacity) = (rng.gen_range(0..100), map.capacity());
+                        map.shrink_to(min_capacity);
+                        // Like `std`'s, `shrink_to` never grows the map.
+                        assert!(map.capacity() >= map.len().max(min_capacity).min(capacity));
+                    }
+                    12 => {
+                        map.shrink_to_fit();
+                        assert!(map.capacity() >= map.len());
+                    }
+                    13 => {
+                        let clone = map.clone();
+                        assert_eq!(clone, map);
+                        map = clone;
+                    }
+                    14 => {
+                        let mut drained = map.drain().collect::<std::vec::Vec<_>>();
+                        let mut expected = reference.drain().collect::<std::vec::Vec<_>>();
+                        drained.sort();
+                        expected.sort();
+                        assert_eq!(drained, expected);
+                    }
+                    _ => assert_eq!(map.get(&key), reference.get(&key)),
+                }
+                assert_eq!(map.len(), reference.len());
+                assert!(map.capacity() >= map.len());
+                assert!(reference.iter().all(|(k, v)| map.get(k) == Some(v)));
+            }
+        }
+    }
+
+    #[test]
+    fn clones_keep_tombstones() {
+        // Every key has the same hash, so they all share a single probe sequence.
+        #[derive(Default)]
+        struct Collide;
+        impl Hasher for Collide {
+            fn finish(&self) -> u64 {
+                0
+            }
+            fn write(&mut self, _: &[u8]) {}
+        }
+        let mut map = HashMap::<u8, u8, core::hash::BuildHasherDefault<Collide>>::default();
+        for key in 0..14 {
+            map.insert(key, key);
+        }
+        // The removed key is surrounded by full slots, so it leaves a tombstone behind.
+        assert_eq!(map.remove(&5), Some(5));
+        let clone = map.clone();
+        assert!((0..14).all(|key| clone.get(&key) == map.get(&key)));
+        assert_eq!(clone.capacity(), map.capacity());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn rehash_panics() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+        // Hashing panics once this reaches 1, counting down with each hash.
+        static COUNTDOWN: AtomicUsize = AtomicUsize::new(0);
+        #[derive(PartialEq, Eq)]
+        struct Key(u32);
+        impl Hash for Key {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                if COUNTDOWN.fetch_sub(1, Ordering::Relaxed) == 1 {
+                    panic!("hashing {}", self.0)
+                }
+                self.0.hash(state)
+            }
+        }
+        let counter = std::sync::Arc::new(());
+        let mut map = HashMap::with_capacity(7);
+        for key in 0..7 {
+            map.insert(Key(key), counter.clone());
+        }
+        assert_eq!(map.capacity(), 7);
+        // Hashing the new key succeeds, but growing the map to insert it panics after moving a few entries.
+        COUNTDOWN.store(4, Ordering::Relaxed);
+        let value = counter.clone();
+        assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            map.insert(Key(7), value);
+        }))
+        .is_err());
+        COUNTDOWN.store(0, Ordering::Relaxed);
+        // The map is left as it was before growing, and the value that couldn't be inserted was dropped.
+        assert_eq!(map.len(), 7);
+        assert_eq!(map.capacity(), 7);
+        assert!((0..7).all(|key| map.contains_key(&Key(key))));
+        assert!(!map.contains_key(&Key(7)));
+        assert_eq!(std::sync::Arc::strong_count(&counter), 8);
+        map.insert(Key(7), counter.clone());
+        assert!(map.capacity() > 7);
+        assert_eq!(std::sync::Arc::strong_count(&counter), 9);
+        drop(map);
+        assert_eq!(std::sync::Arc::strong_count(&counter), 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn entries() {
+        let mut map: HashMap<crate::alloc::string::String, usize> = HashMap::new();
+        for word in "the quick brown fox jumps over the lazy dog the end".split(' ') {
+            *map.entry(word.into()).or_default() += 1;
+        }
+        assert_eq!(map.len(), 9);
+        assert_eq!(map.get("the"), Some(&3));
+        assert_eq!(map.get("fox"), Some(&1));
+        map.entry("fox".into()).and_modify(|count| *count += 10);
+        match map.entry("fox".into()) {
+            Entry::Occupied(entry) => assert_eq!(entry.remove_entry(), ("fox".into(), 11)),
+            Entry::Vacant(_) => panic!("fox should be present"),
+        }
+        assert!(!map.contains_key("fox"));
+        assert_eq!(*map.entry("cat".into()).or_insert_with_key(|k| k.len()), 3);
+        let mut values = map.into_values().collect::<std::vec::Vec<_>>();
+        values.sort();
+        assert_eq!(values, [1, 1, 1, 1, 1, 1, 1, 3, 3]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn drops() {
+        let counter = std::sync::Arc::new(());
+        let mut map = HashMap::new();
+        for i in 0..100u8 {
+            map.insert(i, counter.clone());
+            map.insert(i.wrapping_add(50), counter.clone());
+        }
+        assert_eq!(std::sync::Arc::strong_count(&counter), 151);
+        map.retain(|k, _| *k < 100);
+        assert_eq!(std::sync::Arc::strong_count(&counter), 101);
+        let clone = map.clone();
+        assert_eq!(std::sync::Arc::strong_count(&counter), 201);
+        let mut iter = clone.into_iter();
+        let _ = iter.next();
+        drop(iter);
+        assert_eq!(std::sync::Arc::strong_count(&counter), 101);
+        map.drain().take(3).for_each(drop);
+        assert!(map.is_empty());
+        assert_eq!(std::sync::Arc::strong_count(&counter), 1);
+        let mut zsts = HashMap::<(), ()>::new();
+        assert_eq!(zsts.insert((), ()), None);
+        assert_eq!(zsts.insert((), ()), Some(()));
+        assert_eq!(zsts.len(), 1);
+    }
+}
@@ -0,0 +1,511 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   Pierre Avital, <pierre.avital@me.com>
+//
+
+use core::{
+    borrow::Borrow,
+    fmt::Debug,
+    hash::{BuildHasher, Hash},
+};
+
+use super::hash_map::{self, DefaultHashBuilder, HashMap};
+use crate::alloc::{AllocationError, DefaultAllocator, IAlloc};
+
+/// An ABI-stable hash set.
+///
+/// A `HashSet<T, S, Alloc>` has the same layout as a [`HashMap<T, (), S, Alloc>`](HashMap), whose documentation describes it.
+#[crate::stabby]
+pub struct HashSet<T, S = DefaultHashBuilder, Alloc: IAlloc = DefaultAllocator> {
+    map: HashMap<T, (), S, Alloc>,
+}
+
+#[cfg(not(stabby_default_alloc = "disabled"))]
+impl<T> HashSet<T> {
+    /// Constructs an empty set with the default allocator and hasher. This doesn't allocate.
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+    /// Constructs an empty set with the default allocator and hasher, able to hold `capacity` values without reallocating.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
+    }
+}
+#[cfg(not(stabby_default_alloc = "disabled"))]
+impl<T, S> HashSet<T, S> {
+    /// Constructs an empty set with the default allocator and `hasher`. This doesn't allocate.
+    pub const fn with_hasher(hasher: S) -> Self {
+        Self {
+            map: HashMap::with_hasher(hasher),
+        }
+    }
+    /// Constructs an empty set with the default allocator and `hasher`, able to hold `capacity` values without reallocating.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        Self {
+            map: HashMap::with_capacity_and_hasher(capacity, hasher),
+        }
+    }
+}
+impl<T, S: Default, Alloc: IAlloc> HashSet<T, S, Alloc> {
+    /// Constructs an empty set in `alloc`. This doesn't allocate.
+    pub fn new_in(alloc: Alloc) -> Self {
+        Self {
+            map: HashMap::new_in(alloc),
+        }
+    }
+    /// Constructs an empty set in `alloc`, able to hold `capacity` values without reallocating.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn with_capacity_in(capacity: usize, alloc: Alloc) -> Self {
+        Self {
+            map: HashMap::with_capacity_in(capacity, alloc),
+        }
+    }
+}
+impl<T, S, Alloc: IAlloc> HashSet<T, S, Alloc> {
+    /// Constructs an empty set in `alloc`, using `hasher` to hash its values. This doesn't allocate.
+    pub const fn with_hasher_in(hasher: S, alloc: Alloc) -> Self {
+        Self {
+            map: HashMap::with_hasher_in(hasher, alloc),
+        }
+    }
+    /// Constructs an empty set in `alloc`, able to hold `capacity` values without reallocating.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn with_capacity_and_hasher_in(capacity: usize, hasher: S, alloc: Alloc) -> Self {
+        Self {
+            map: HashMap::with_capacity_and_hasher_in(capacity, hasher, alloc),
+        }
+    }
+    /// The number of values in the set.
+    pub const fn len(&self) -> usize {
+        self.map.len()
+    }
+    /// Returns `true` if the set has no values.
+    pub const fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+    /// The number of values the set can hold without reallocating.
+    pub const fn capacity(&self) -> usize {
+        self.map.capacity()
+    }
+    /// The set's hasher.
+    pub const fn hasher(&self) -> &S {
+        self.map.hasher()
+    }
+    /// The set's allocator.
+    pub const fn allocator(&self) -> &Alloc {
+        self.map.allocator()
+    }
+    /// Iterates over the set's values, in arbitrary order.
+    pub const fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            iter: self.map.keys(),
+        }
+    }
+    /// Removes every value from the set, returning them as an iterator.
+    ///
+    /// The set keeps its allocation. Values that haven't been yielded when the iterator is dropped are dropped as well.
+    #[rustversion::attr(since(1.83), const)]
+    pub fn drain(&mut self) -> Drain<'_, T, Alloc> {
+        Drain {
+            iter: self.map.drain(),
+        }
+    }
+    /// Keeps only the values for which `f` returns `true`.
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        self.map.retain(|value, _| f(value))
+    }
+    /// Removes every value from the set, keeping its allocation.
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+}
+impl<T: Hash + Eq, S: BuildHasher, Alloc: IAlloc> HashSet<T, S, Alloc> {
+    /// Ensures that `additional` more values can be inserted without reallocating.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn reserve(&mut self, additional: usize) {
+        self.map.reserve(additional)
+    }
+    /// Ensures that `additional` more values can be inserted without reallocating.
+    ///
+    /// # Errors
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), AllocationError> {
+        self.map.try_reserve(additional)
+    }
+    /// Shrinks the set's allocation as much as possible.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide the new allocation.
+    pub fn shrink_to_fit(&mut self) {
+        self.map.shrink_to_fit()
+    }
+    /// Shrinks the set's allocation, keeping room for at least `min_capacity` values.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide the new allocation.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.map.shrink_to(min_capacity)
+    }
+    /// Returns `true` if the set contains `value`.
+    pub fn contains<Q: Hash + Eq + ?Sized>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+    {
+        self.map.contains_key(value)
+    }
+    /// Returns a reference to the value in the set that is equal to `value`.
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+    {
+        self.map.get_key_value(value).map(|(k, _)| k)
+    }
+    /// Adds `value` to the set, returning `true` if it wasn't present yet.
+    ///
+    /// If an equal value was already present, it is not replaced.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn insert(&mut self, value: T) -> bool {
+        match self.map.entry(value) {
+            hash_map::Entry::Occupied(_) => false,
+            hash_map::Entry::Vacant(entry) => {
+                entry.insert(());
+                true
+            }
+        }
+    }
+    /// Adds `value` to the set, returning the equal value it replaced, if any.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn replace(&mut self, value: T) -> Option<T> {
+        let previous = self.map.remove_entry(&value).map(|(k, _)| k);
+        self.map.insert(value, ());
+        previous
+    }
+    /// Removes `value` from the set, returning `true` if it was present.
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+    {
+        self.map.remove(value).is_some()
+    }
+    /// Removes the value equal to `value` from the set, returning it.
+    pub fn take<Q: Hash + Eq + ?Sized>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+    {
+        self.map.remove_entry(value).map(|(k, _)| k)
+    }
+    /// Returns `true` if every value of `self` is also in `other`.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.len() <= other.len() && self.iter().all(|value| other.contains(value))
+    }
+    /// Returns `true` if every value of `other` is also in `self`.
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+    /// Returns `true` if `self` and `other` have no values in common.
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        let (small, large) = if self.len() <= other.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        small.iter().all(|value| !large.contains(value))
+    }
+    /// Iterates over the values of `self` that aren't in `other`.
+    pub fn difference<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a T> + 'a {
+        self.iter().filter(move |value| !other.contains(*value))
+    }
+    /// Iterates over the values that are in `self` or in `other`, but not in both.
+    pub fn symmetric_difference<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a T> + 'a {
+        self.difference(other).chain(other.difference(self))
+    }
+    /// Iterates over the values that are in both `self` and `other`.
+    pub fn intersection<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a T> + 'a {
+        let (small, large) = if self.len() <= other.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        small.iter().filter(move |value| large.contains(*value))
+    }
+    /// Iterates over the values that are in `self` or in `other`, without duplicates.
+    pub fn union<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a T> + 'a {
+        self.iter().chain(other.difference(self))
+    }
+}
+
+/// An iterator over the values of a [`HashSet`].
+pub struct Iter<'a, T> {
+    iter: hash_map::Keys<'a, T, ()>,
+}
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> core::iter::FusedIterator for Iter<'_, T> {}
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            iter: self.iter.clone(),
+        }
+    }
+}
+impl<T: Debug> Debug for Iter<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// An iterator over the values of a [`HashSet`], by value.
+pub struct IntoIter<T, Alloc: IAlloc = DefaultAllocator> {
+    iter: hash_map::IntoKeys<T, (), Alloc>,
+}
+impl<T, Alloc: IAlloc> Iterator for IntoIter<T, Alloc> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<T, Alloc: IAlloc> ExactSizeIterator for IntoIter<T, Alloc> {}
+impl<T, Alloc: IAlloc> core::iter::FusedIterator for IntoIter<T, Alloc> {}
+
+/// A draining iterator over the values of a [`HashSet`], obtained through [`HashSet::drain`].
+pub struct Drain<'a, T, Alloc: IAlloc = DefaultAllocator> {
+    iter: hash_map::Drain<'a, T, (), Alloc>,
+}
+impl<T, Alloc: IAlloc> Iterator for Drain<'_, T, Alloc> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(value, _)| value)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<T, Alloc: IAlloc> ExactSizeIterator for Drain<'_, T, Alloc> {}
+impl<T, Alloc: IAlloc> core::iter::FusedIterator for Drain<'_, T, Alloc> {}
+
+impl<T, S, Alloc: IAlloc> IntoIterator for HashSet<T, S, Alloc> {
+    type Item = T;
+    type IntoIter = IntoIter<T, Alloc>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            iter: self.map.into_keys(),
+        }
+    }
+}
+impl<'a, T, S, Alloc: IAlloc> IntoIterator for &'a HashSet<T, S, Alloc> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<T: Hash + Eq, S: BuildHasher, Alloc: IAlloc> Extend<T> for HashSet<T, S, Alloc> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.map.extend(iter.into_iter().map(|value| (value, ())))
+    }
+}
+impl<'a, T: Hash + Eq + Copy, S: BuildHasher, Alloc: IAlloc> Extend<&'a T>
+    for HashSet<T, S, Alloc>
+{
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+impl<T: Hash + Eq, S: BuildHasher + Default, Alloc: IAlloc + Default> FromIterator<T>
+    for HashSet<T, S, Alloc>
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut this = Self::new_in(Alloc::default());
+        this.extend(iter);
+        this
+    }
+}
+impl<T: Hash + Eq, S: BuildHasher + Default, Alloc: IAlloc + Default, const N: usize> From<[T; N]>
+    for HashSet<T, S, Alloc>
+{
+    fn from(value: [T; N]) -> Self {
+        value.into_iter().collect()
+    }
+}
+impl<T, S: Default, Alloc: IAlloc + Default> Default for HashSet<T, S, Alloc> {
+    fn default() -> Self {
+        Self::new_in(Alloc::default())
+    }
+}
+impl<T: Clone, S: Clone, Alloc: IAlloc + Clone> Clone for HashSet<T, S, Alloc> {
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map.clone(),
+        }
+    }
+}
+impl<T: Hash + Eq, S: BuildHasher, Alloc: IAlloc> PartialEq for HashSet<T, S, Alloc> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+impl<T: Hash + Eq, S: BuildHasher, Alloc: IAlloc> Eq for HashSet<T, S, Alloc> {}
+impl<T: Debug, S, Alloc: IAlloc> Debug for HashSet<T, S, Alloc> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+    use core::marker::PhantomData;
+    use serde::{de::Visitor, Deserialize, Serialize};
+    impl<T: Serialize, S, Alloc: IAlloc> Serialize for HashSet<T, S, Alloc> {
+        fn serialize<Ser>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error>
+        where
+            Ser: serde::Serializer,
+        {
+            serializer.collect_seq(self.iter())
+        }
+    }
+    impl<'a, T: Deserialize<'a> + Hash + Eq, S: BuildHasher + Default, Alloc: IAlloc + Default>
+        Deserialize<'a> for HashSet<T, S, Alloc>
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'a>,
+        {
+            deserializer.deserialize_seq(HashSetVisitor(PhantomData))
+        }
+    }
+    pub struct HashSetVisitor<T, S, Alloc>(PhantomData<(T, S, Alloc)>);
+    impl<'a, T: Deserialize<'a> + Hash + Eq, S: BuildHasher + Default, Alloc: IAlloc + Default>
+        Visitor<'a> for HashSetVisitor<T, S, Alloc>
+    {
+        type Value = HashSet<T, S, Alloc>;
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            formatter.write_str("A sequence")
+        }
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::SeqAccess<'a>,
+        {
+            let mut this = HashSet::with_capacity_and_hasher_in(
+                seq.size_hint().unwrap_or(0),
+                S::default(),
+                Alloc::default(),
+            );
+            while let Some(v) = seq.next_element()? {
+                this.insert(v);
+            }
+            Ok(this)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_set() {
+        let _ = crate::AssertStable::<HashSet<u64>>(core::marker::PhantomData);
+        let evens: HashSet<u32> = (0..100).step_by(2).collect();
+        let threes: HashSet<u32> = (0..100).step_by(3).collect();
+        assert_eq!(evens.len(), 50);
+        assert!(evens.contains(&42) && !evens.contains(&43));
+        let mut sixes = evens
+            .intersection(&threes)
+            .copied()
+            .collect::<std::vec::Vec<_>>();
+        sixes.sort();
+        assert!(sixes.into_iter().eq((0..100).step_by(6)));
+        assert_eq!(evens.union(&threes).count(), 67);
+        assert_eq!(evens.difference(&threes).count(), 33);
+        assert_eq!(evens.symmetric_difference(&threes).count(), 50);
+        let mut set = evens.clone();
+        assert!(!set.insert(42));
+        assert!(set.remove(&42));
+        assert!(set.is_subset(&evens) && !evens.is_subset(&set));
+        assert!(set.is_disjoint(&HashSet::from([1, 3, 42])));
+        set.retain(|v| *v < 10);
+        assert_eq!(set, HashSet::from([0, 2, 4, 6, 8]));
+    }
+
+    #[test]
+    fn differential() {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        for _ in 0..if cfg!(miri) { 5 } else { 100 } {
+            let mut reference = std::collections::HashSet::new();
+            let mut set = HashSet::new();
+            for _ in 0..rng.gen_range(0..500) {
+                let value = rng.gen_range(0..100u16);
+                match rng.gen_range(0..12) {
+                    0..=4 => assert_eq!(set.insert(value), reference.insert(value)),
+                    5..=6 => assert_eq!(set.remove(&value), reference.remove(&value)),
+                    7 => {
+                        let modulo = rng.gen_range(1..5u16);
+                        set.retain(|v| v % modulo != 0);
+                        reference.retain(|v| v % modulo != 0);
+                    }
+                    8 => {
+                        let additional = rng.gen_range(0..100);
+                        set.reserve(additional);
+                        assert!(set.capacity() >= set.len().wrapping_add(additional));
+                    }
+                    9 => {
+                        set.shrink_to_fit();
+                        assert!(set.capacity() >= set.len());
+                    }
+                    10 => {
+                        let clone = set.clone();
+                        assert_eq!(clone, set);
+                        set = clone;
+                    }
+                    _ => {
+                        let mut drained = set.drain().collect::<std::vec::Vec<_>>();
+                        let mut expected = reference.drain().collect::<std::vec::Vec<_>>();
+                        drained.sort();
+                        expected.sort();
+                        assert_eq!(drained, expected);
+                    }
+                }
+                assert_eq!(set.len(), reference.len());
+                assert!(reference.iter().all(|v| set.contains(v)));
+            }
+        }
+    }
+}
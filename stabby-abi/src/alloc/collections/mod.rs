@@ -5,3 +5,17 @@
 /// Mutating an ArcBTree that whose ownership is shared will result in every node from the insertion spot
 /// to the root to be copied, while the remaining nodes will just see their reference counts increase.
 pub mod arc_btree;
+
+mod raw_table;
+
+/// ABI-stable hash maps, built on an open-addressing table inspired by [SwissTable](https://abseil.io/about/design/swisstables).
+pub mod hash_map;
+pub use hash_map::HashMap;
+
+/// ABI-stable hash sets, which are [`HashMap`]s without values.
+pub mod hash_set;
+pub use hash_set::HashSet;
+
+/// ABI-stable double-ended queues, implemented as ring buffers.
+pub mod vec_deque;
+pub use vec_deque::VecDeque;
@@ -0,0 +1,695 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   Pierre Avital, <pierre.avital@me.com>
+//
+
+//! The open-addressing table behind [`HashMap`](super::HashMap) and [`HashSet`](super::HashSet).
+//!
+//! The table is a single allocation, prefixed by an [`AllocPrefix`](crate::alloc::AllocPrefix):
+//! `buckets` slots of `T`, followed by `buckets + GROUP` control bytes.
+//!
+//! Each control byte describes the slot at the same index: [`EMPTY`], [`DELETED`], or the 7 top bits
+//! of the hash of the slot's element. The last `GROUP` control bytes mirror the first ones, so that
+//! groups of `GROUP` control bytes can be read from any index without wrapping around.
+//!
+//! Lookups start at the group designated by the low bits of the hash, compare its control bytes with
+//! the top bits of the hash in a single word operation, and probe the following groups triangularly until
+//! a group containing an [`EMPTY`] byte is found.
+
+use core::ptr::NonNull;
+
+use crate::alloc::{AllocPrefix, AllocPtr, AllocationError, IAlloc, Layout};
+
+/// The number of control bytes read at once.
+pub(crate) const GROUP: usize = 8;
+/// The control byte of a slot that has never been used since the last rehash.
+pub(crate) const EMPTY: u8 = 0xff;
+/// The control byte of a slot whose element was removed, which may be on a probe sequence.
+pub(crate) const DELETED: u8 = 0x80;
+
+const LO: u64 = u64::from_ne_bytes([0x01; GROUP]);
+const HI: u64 = u64::from_ne_bytes([0x80; GROUP]);
+
+/// A group of control bytes, read as a little-endian word so that byte `i` is at bits `8i..8i+8`.
+#[derive(Clone, Copy)]
+struct Group(u64);
+impl Group {
+    /// # Safety
+    /// `ctrl` must be valid for reads of `GROUP` bytes.
+    const unsafe fn load(ctrl: *const u8) -> Self {
+        Self(u64::from_le_bytes(unsafe {
+            core::ptr::read_unaligned(ctrl.cast::<[u8; GROUP]>())
+        }))
+    }
+    /// Returns the bytes that may be equal to `byte`: false positives are possible, but not false negatives.
+    const fn match_byte(self, byte: u8) -> BitMask {
+        let cmp = self.0 ^ u64::from_ne_bytes([byte; GROUP]);
+        BitMask(cmp.wrapping_sub(LO) & !cmp & HI)
+    }
+    const fn match_empty(self) -> BitMask {
+        BitMask(self.0 & (self.0 << 1) & HI)
+    }
+    const fn match_empty_or_deleted(self) -> BitMask {
+        BitMask(self.0 & HI)
+    }
+}
+
+/// A set of bytes within a [`Group`], represented by their high bit.
+#[derive(Clone, Copy)]
+struct BitMask(u64);
+impl BitMask {
+    const fn any(self) -> bool {
+        self.0 != 0
+    }
+    const fn lowest(self) -> Option<usize> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(self.0.trailing_zeros() as usize / 8)
+        }
+    }
+    const fn leading_bytes(self) -> usize {
+        self.0.leading_zeros() as usize / 8
+    }
+    const fn trailing_bytes(self) -> usize {
+        self.0.trailing_zeros() as usize / 8
+    }
+}
+impl Iterator for BitMask {
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        let lowest = self.lowest()?;
+        self.0 &= self.0.wrapping_sub(1);
+        Some(lowest)
+    }
+}
+
+/// The top 7 bits of `hash`, used as the control byte of its slot.
+const fn h2(hash: u64) -> u8 {
+    (hash >> 57) as u8
+}
+const fn is_full(ctrl: u8) -> bool {
+    ctrl & 0x80 == 0
+}
+/// The number of elements a table of `buckets` slots may hold before growing.
+const fn buckets_to_capacity(buckets: usize) -> usize {
+    (buckets / 8).wrapping_mul(7)
+}
+/// The number of slots needed to hold `capacity` elements, or `None` if that number would overflow.
+const fn capacity_to_buckets(capacity: usize) -> Option<usize> {
+    if capacity == 0 {
+        return Some(0);
+    }
+    // The smallest number of buckets `buckets_to_capacity` maps to at least `capacity`.
+    let Some(scaled) = capacity.checked_mul(8) else {
+        return None;
+    };
+    let buckets = (scaled / 7)
+        .wrapping_add((scaled % 7 != 0) as usize)
+        .next_power_of_two();
+    if buckets == 0 || buckets > isize::MAX as usize {
+        return None;
+    }
+    Some(if buckets < GROUP { GROUP } else { buckets })
+}
+
+/// An open-addressing hash table of `T`.
+///
+/// Hashing is left to the caller: every method that needs to place an element takes its hash,
+/// and growing the table takes a function to recompute the hashes of its elements.
+#[crate::stabby]
+pub struct RawTable<T, Alloc: IAlloc> {
+    /// The allocation: `buckets` slots, followed by `buckets + GROUP` control bytes. Dangling if `buckets == 0`.
+    data: AllocPtr<T, Alloc>,
+    /// The number of slots, either 0 or a power of 2 no lower than `GROUP`.
+    buckets: usize,
+    /// The number of elements in the table.
+    items: usize,
+    /// The number of [`EMPTY`] slots that may still be filled before the table must grow.
+    growth_left: usize,
+    alloc: Alloc,
+}
+// SAFETY: The table owns its elements.
+unsafe impl<T: Send, Alloc: IAlloc + Send> Send for RawTable<T, Alloc> {}
+// SAFETY: The table owns its elements.
+unsafe impl<T: Sync, Alloc: IAlloc + Sync> Sync for RawTable<T, Alloc> {}
+
+impl<T, Alloc: IAlloc> RawTable<T, Alloc> {
+    /// Constructs an empty table in `alloc`. This doesn't allocate.
+    pub(crate) const fn new_in(alloc: Alloc) -> Self {
+        Self {
+            data: AllocPtr::dangling(),
+            buckets: 0,
+            items: 0,
+            growth_left: 0,
+            alloc,
+        }
+    }
+    /// Constructs a table in `alloc` that can hold at least `capacity` elements without growing.
+    pub(crate) fn try_with_capacity_in(
+        capacity: usize,
+        mut alloc: Alloc,
+    ) -> Result<Self, (Alloc, AllocationError)> {
+        let buckets = match capacity_to_buckets(capacity) {
+            Some(buckets) => buckets,
+            None => return Err((alloc, AllocationError())),
+        };
+        match Self::allocate(&mut alloc, buckets) {
+            Ok(data) => Ok(Self {
+                data,
+                buckets,
+                items: 0,
+                growth_left: buckets_to_capacity(buckets),
+                alloc,
+            }),
+            Err(e) => Err((alloc, e)),
+        }
+    }
+    /// Allocates `buckets` slots and their control bytes, marking all of them as [`EMPTY`].
+    fn allocate(alloc: &mut Alloc, buckets: usize) -> Result<AllocPtr<T, Alloc>, AllocationError> {
+        if buckets == 0 {
+            return Ok(AllocPtr::dangling());
+        }
+        let ctrl_len = buckets.wrapping_add(GROUP);
+        let size = core::mem::size_of::<T>()
+            .checked_mul(buckets)
+            .and_then(|size| size.checked_add(ctrl_len))
+            // Leave room to align the slots after the prefix.
+            .and_then(|size| size.checked_add(core::mem::align_of::<T>()))
+            .and_then(|size| size.checked_add(core::mem::size_of::<AllocPrefix<Alloc>>()))
+            .filter(|size| *size <= isize::MAX as usize)
+            .ok_or(AllocationError())?;
+        let layout = Layout {
+            size,
+            align: core::mem::align_of::<AllocPrefix<Alloc>>(),
+        };
+        let ptr = NonNull::new(alloc.alloc(layout)).ok_or(AllocationError())?;
+        // SAFETY: `ptr` was allocated with enough room for the prefix, the aligned slots and the control bytes.
+        let data: AllocPtr<T, Alloc> = unsafe { AllocPtr::init_in(ptr, buckets, alloc) };
+        // SAFETY: the control bytes are part of the allocation.
+        unsafe {
+            core::ptr::write_bytes(data.ptr.as_ptr().add(buckets).cast::<u8>(), EMPTY, ctrl_len)
+        };
+        Ok(data)
+    }
+    /// The number of elements in the table.
+    pub(crate) const fn len(&self) -> usize {
+        self.items
+    }
+    /// The number of elements the table can hold without growing.
+    pub(crate) const fn capacity(&self) -> usize {
+        self.items.wrapping_add(self.growth_left)
+    }
+    pub(crate) const fn allocator(&self) -> &Alloc {
+        &self.alloc
+    }
+    const fn mask(&self) -> usize {
+        self.buckets.wrapping_sub(1)
+    }
+    const fn ctrl_ptr(&self) -> *mut u8 {
+        // SAFETY: the control bytes follow the slots in the allocation. If nothing was allocated, this is a dangling pointer
+        // that is never read from, since `buckets == 0`.
+        unsafe { self.data.ptr.as_ptr().add(self.buckets).cast() }
+    }
+    /// # Safety
+    /// `index` must be lower than `self.buckets() + GROUP`.
+    unsafe fn ctrl(&self, index: usize) -> u8 {
+        unsafe { *self.ctrl_ptr().add(index) }
+    }
+    /// Sets the control byte of `index`, as well as its mirror if it's one of the first `GROUP` slots.
+    /// # Safety
+    /// `index` must be lower than `self.buckets()`.
+    unsafe fn set_ctrl(&mut self, index: usize, ctrl: u8) {
+        let mirror = (index.wrapping_sub(GROUP) & self.mask()).wrapping_add(GROUP);
+        let ptr = self.ctrl_ptr();
+        unsafe {
+            *ptr.add(index) = ctrl;
+            *ptr.add(mirror) = ctrl;
+        }
+    }
+    /// Returns whether the slot at `index` holds an element.
+    /// # Safety
+    /// `index` must be lower than `self.buckets()`.
+    pub(crate) unsafe fn is_full(&self, index: usize) -> bool {
+        is_full(unsafe { self.ctrl(index) })
+    }
+    /// Returns a pointer to the slot at `index`.
+    /// # Safety
+    /// `index` must be lower than `self.buckets()`.
+    pub(crate) const unsafe fn bucket(&self, index: usize) -> NonNull<T> {
+        unsafe { NonNull::new_unchecked(self.data.ptr.as_ptr().add(index)) }
+    }
+    /// Returns the index of the first element with this `hash` for which `eq` returns `true`.
+    pub(crate) fn find(&self, hash: u64, mut eq: impl FnMut(&T) -> bool) -> Option<usize> {
+        if self.items == 0 {
+            return None;
+        }
+        let h2 = h2(hash);
+        let mask = self.mask();
+        let mut pos = hash as usize & mask;
+        let mut stride = 0usize;
+        loop {
+            // SAFETY: `pos < buckets`, and the control bytes are `buckets + GROUP` long.
+            let group = unsafe { Group::load(self.ctrl_ptr().add(pos)) };
+            for bit in group.match_byte(h2) {
+                let index = pos.wrapping_add(bit) & mask;
+                // SAFETY: `index < buckets`, and the slot is full since its control byte matched `h2`.
+                if eq(unsafe { self.bucket(index).as_ref() }) {
+                    return Some(index);
+                }
+            }
+            if group.match_empty().any() {
+                return None;
+            }
+            stride = stride.wrapping_add(GROUP);
+            pos = pos.wrapping_add(stride) & mask;
+        }
+    }
+    /// Returns the index of the first [`EMPTY`] or [`DELETED`] slot on `hash`'s probe sequence.
+    ///
+    /// The table must have been allocated.
+    const fn find_insert_slot(&self, hash: u64) -> usize {
+        let mask = self.mask();
+        let mut pos = hash as usize & mask;
+        let mut stride = 0usize;
+        loop {
+            // SAFETY: `pos < buckets`, and the control bytes are `buckets + GROUP` long.
+            let group = unsafe { Group::load(self.ctrl_ptr().add(pos)) };
+            if let Some(bit) = group.match_empty_or_deleted().lowest() {
+                return pos.wrapping_add(bit) & mask;
+            }
+            stride = stride.wrapping_add(GROUP);
+            pos = pos.wrapping_add(stride) & mask;
+        }
+    }
+    /// Ensures that `additional` elements may be inserted without growing.
+    ///
+    /// # Errors
+    /// If the allocator failed to provide the new table, in which case `self` is left untouched.
+    pub(crate) fn try_reserve(
+        &mut self,
+        additional: usize,
+        hasher: impl Fn(&T) -> u64,
+    ) -> Result<(), AllocationError> {
+        if additional <= self.growth_left {
+            return Ok(());
+        }
+        let needed = self
+            .items
+            .checked_add(additional)
+            .ok_or(AllocationError())?;
+        let full_capacity = buckets_to_capacity(self.buckets);
+        // If the table is mostly tombstones, rehashing it at the same size is enough.
+        let capacity = if needed > full_capacity / 2 {
+            needed.max(full_capacity.wrapping_add(1))
+        } else {
+            full_capacity
+        };
+        self.resize(capacity, hasher)
+    }
+    /// Moves all elements to a smaller allocation, if one can hold `min_capacity` elements.
+    ///
+    /// # Errors
+    /// If the allocator failed to provide the new table, in which case `self` is left untouched.
+    pub(crate) fn shrink_to(
+        &mut self,
+        min_capacity: usize,
+        hasher: impl Fn(&T) -> u64,
+    ) -> Result<(), AllocationError> {
+        let capacity = min_capacity.max(self.items);
+        match capacity_to_buckets(capacity) {
+            Some(buckets) if buckets < self.buckets => self.resize(capacity, hasher),
+            _ => Ok(()),
+        }
+    }
+    /// Moves all elements to a new allocation, able to hold at least `capacity` elements.
+    ///
+    /// # Errors
+    /// If the allocator failed to provide the new table, in which case `self` is left untouched.
+    fn resize(
+        &mut self,
+        capacity: usize,
+        hasher: impl Fn(&T) -> u64,
+    ) -> Result<(), AllocationError> {
+        let buckets = capacity_to_buckets(capacity.max(self.items)).ok_or(AllocationError())?;
+        let data = Self::allocate(&mut self.alloc, buckets)?;
+        // Elements are only copied to the new allocation until all of them have been moved, so that if `hasher`
+        // panics, the old table can be restored with all of its elements.
+        let guard = Restore {
+            data: core::mem::replace(&mut self.data, data),
+            buckets: core::mem::replace(&mut self.buckets, buckets),
+            items: core::mem::replace(&mut self.items, 0),
+            growth_left: core::mem::replace(&mut self.growth_left, buckets_to_capacity(buckets)),
+            table: self,
+        };
+        for index in 0..guard.buckets {
+            if guard.table.items == guard.items {
+                break;
+            }
+            // SAFETY: the old control bytes are still allocated, and `index < guard.buckets`.
+            let ctrl = unsafe {
+                *guard
+                    .data
+                    .ptr
+                    .as_ptr()
+                    .add(guard.buckets)
+                    .cast::<u8>()
+                    .add(index)
+            };
+            if is_full(ctrl) {
+                // SAFETY: the slot is full, and is copied exactly once.
+                let value = unsafe { guard.data.ptr.as_ptr().add(index) };
+                let hash = hasher(unsafe { &*value });
+                let table = &mut *guard.table;
+                let new_index = table.find_insert_slot(hash);
+                unsafe {
+                    table.set_ctrl(new_index, h2(hash));
+                    core::ptr::copy_nonoverlapping(value, table.bucket(new_index).as_ptr(), 1);
+                }
+                table.items = table.items.wrapping_add(1);
+                table.growth_left = table.growth_left.wrapping_sub(1);
+            }
+        }
+        if guard.buckets != 0 {
+            // SAFETY: every element has been moved to the new allocation.
+            unsafe { guard.data.free(&mut guard.table.alloc) }
+        }
+        core::mem::forget(guard);
+        Ok(())
+    }
+    /// Inserts `value` at the first free slot of `hash`'s probe sequence, returning its index.
+    ///
+    /// # Safety
+    /// The table must be able to hold one more element without growing.
+    pub(crate) unsafe fn insert_no_grow(&mut self, hash: u64, value: T) -> usize {
+        let index = self.find_insert_slot(hash);
+        unsafe {
+            if self.ctrl(index) == EMPTY {
+                self.growth_left = self.growth_left.wrapping_sub(1);
+            }
+            self.set_ctrl(index, h2(hash));
+            self.bucket(index).as_ptr().write(value);
+        }
+        self.items = self.items.wrapping_add(1);
+        index
+    }
+    /// Marks the slot at `index` as free, without dropping its element.
+    ///
+    /// The slot is marked [`EMPTY`] if no probe sequence could have gone through it while looking for another element,
+    /// and [`DELETED`] otherwise.
+    ///
+    /// # Safety
+    /// The slot at `index` must be full, and its element must be considered moved out.
+    unsafe fn erase_no_drop(&mut self, index: usize) {
+        let before = index.wrapping_sub(GROUP) & self.mask();
+        let (empty_before, empty_after) = unsafe {
+            (
+                Group::load(self.ctrl_ptr().add(before)).match_empty(),
+                Group::load(self.ctrl_ptr().add(index)).match_empty(),
+            )
+        };
+        let ctrl = if empty_before
+            .leading_bytes()
+            .wrapping_add(empty_after.trailing_bytes())
+            >= GROUP
+        {
+            DELETED
+        } else {
+            self.growth_left = self.growth_left.wrapping_add(1);
+            EMPTY
+        };
+        unsafe { self.set_ctrl(index, ctrl) };
+        self.items = self.items.wrapping_sub(1);
+    }
+    /// Removes the element at `index` from the table, returning it.
+    ///
+    /// # Safety
+    /// The slot at `index` must be full.
+    pub(crate) unsafe fn remove(&mut self, index: usize) -> T {
+        unsafe {
+            let value = self.bucket(index).as_ptr().read();
+            self.erase_no_drop(index);
+            value
+        }
+    }
+    /// Marks every slot as [`EMPTY`], without dropping their elements.
+    #[rustversion::attr(since(1.83), const)]
+    fn clear_no_drop(&mut self) {
+        if self.buckets != 0 {
+            // SAFETY: the control bytes are `buckets + GROUP` long.
+            unsafe {
+                core::ptr::write_bytes(self.ctrl_ptr(), EMPTY, self.buckets.wrapping_add(GROUP))
+            };
+        }
+        self.items = 0;
+        self.growth_left = buckets_to_capacity(self.buckets);
+    }
+    /// Drops every element in the table, keeping its allocation.
+    pub(crate) fn clear(&mut self) {
+        self.drain();
+    }
+    /// Keeps only the elements for which `f` returns `true`.
+    pub(crate) fn retain(&mut self, mut f: impl FnMut(&mut T) -> bool) {
+        for index in 0..self.buckets {
+            // SAFETY: `index < buckets`.
+            unsafe {
+                if self.is_full(index) && !f(self.bucket(index).as_mut()) {
+                    self.erase_no_drop(index);
+                    self.bucket(index).as_ptr().drop_in_place();
+                }
+            }
+        }
+    }
+    /// Iterates over the slots that hold an element.
+    pub(crate) const fn raw_iter(&self) -> RawIter<T> {
+        RawIter {
+            data: self.data.ptr,
+            ctrl: self.ctrl_ptr(),
+            next: 0,
+            remaining: self.items,
+        }
+    }
+    /// Returns a draining iterator over the elements of the table.
+    #[rustversion::attr(since(1.83), const)]
+    pub(crate) fn drain(&mut self) -> RawDrain<'_, T, Alloc> {
+        RawDrain {
+            iter: self.raw_iter(),
+            table: self,
+        }
+    }
+    /// Consumes the table into an iterator over its elements.
+    pub(crate) fn into_iter(self) -> RawIntoIter<T, Alloc> {
+        let this = core::mem::ManuallyDrop::new(self);
+        RawIntoIter {
+            iter: this.raw_iter(),
+            data: this.data,
+            buckets: this.buckets,
+            // SAFETY: `this` is never dropped, so `alloc` is moved out exactly once.
+            alloc: unsafe { core::ptr::read(&this.alloc) },
+        }
+    }
+    /// Returns a table with the same capacity that holds clones of `self`'s elements, in the same slots.
+    ///
+    /// # Errors
+    /// If the allocator failed to provide the new table.
+    pub(crate) fn try_clone(&self) -> Result<Self, AllocationError>
+    where
+        T: Clone,
+        Alloc: Clone,
+    {
+        let mut alloc = self.alloc.clone();
+        let data = Self::allocate(&mut alloc, self.buckets)?;
+        let mut clone = Self {
+            data,
+            buckets: self.buckets,
+            items: 0,
+            growth_left: self.growth_left,
+            alloc,
+        };
+        // Tombstones are copied too, since they may be on the probe sequences of `self`'s elements,
+        // and `growth_left` doesn't count them as free.
+        for index in 0..self.buckets {
+            // SAFETY: `index < self.buckets`, and both tables have as many buckets.
+            unsafe {
+                if self.ctrl(index) == DELETED {
+                    clone.set_ctrl(index, DELETED);
+                }
+            }
+        }
+        for index in self.raw_iter() {
+            // SAFETY: `index` is a full slot of `self`, and the same slot in `clone` is empty.
+            // Since `clone`'s control byte is only set once the element is written, a panicking `clone` doesn't
+            // lead to uninitialized elements being dropped.
+            unsafe {
+                clone
+                    .bucket(index)
+                    .as_ptr()
+                    .write(self.bucket(index).as_ref().clone());
+                clone.set_ctrl(index, self.ctrl(index));
+            }
+            clone.items = clone.items.wrapping_add(1);
+        }
+        Ok(clone)
+    }
+}
+impl<T, Alloc: IAlloc> Drop for RawTable<T, Alloc> {
+    fn drop(&mut self) {
+        if self.buckets != 0 {
+            // SAFETY: the table owns its elements, and its allocation.
+            unsafe {
+                self.raw_iter().drop_remaining();
+                self.data.free(&mut self.alloc)
+            }
+        }
+    }
+}
+
+/// Puts the old allocation of a [`RawTable`] back in place if it is dropped during [`RawTable::resize`],
+/// freeing the new one without dropping the elements that were copied to it.
+struct Restore<'a, T, Alloc: IAlloc> {
+    table: &'a mut RawTable<T, Alloc>,
+    data: AllocPtr<T, Alloc>,
+    buckets: usize,
+    items: usize,
+    growth_left: usize,
+}
+impl<T, Alloc: IAlloc> Drop for Restore<'_, T, Alloc> {
+    fn drop(&mut self) {
+        let table = &mut *self.table;
+        let data = core::mem::replace(&mut table.data, self.data);
+        let buckets = core::mem::replace(&mut table.buckets, self.buckets);
+        table.items = self.items;
+        table.growth_left = self.growth_left;
+        if buckets != 0 {
+            // SAFETY: the old allocation still owns every element, so the new one only holds copies of them.
+            unsafe { data.free(&mut table.alloc) }
+        }
+    }
+}
+
+/// An iterator over the indices of the full slots of a [`RawTable`].
+///
+/// It doesn't borrow the table: the caller is responsible for keeping the table alive and unchanged while using it.
+pub(crate) struct RawIter<T> {
+    data: NonNull<T>,
+    ctrl: *const u8,
+    next: usize,
+    remaining: usize,
+}
+impl<T> RawIter<T> {
+    /// Returns a pointer to the slot at `index`, which must have been yielded by this iterator.
+    pub(crate) const unsafe fn bucket(&self, index: usize) -> NonNull<T> {
+        unsafe { NonNull::new_unchecked(self.data.as_ptr().add(index)) }
+    }
+    /// Drops the elements that haven't been yielded yet.
+    ///
+    /// # Safety
+    /// The slots must still be allocated, and their elements must not be used after this.
+    unsafe fn drop_remaining(&mut self) {
+        if core::mem::needs_drop::<T>() {
+            while let Some(index) = self.next() {
+                unsafe { self.bucket(index).as_ptr().drop_in_place() }
+            }
+        }
+    }
+}
+impl<T> Iterator for RawIter<T> {
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        if self.remaining == 0 {
+            return None;
+        }
+        loop {
+            let index = self.next;
+            self.next = index.wrapping_add(1);
+            // SAFETY: since elements remain, `index` hasn't reached the end of the slots.
+            if is_full(unsafe { *self.ctrl.add(index) }) {
+                self.remaining = self.remaining.wrapping_sub(1);
+                return Some(index);
+            }
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+impl<T> ExactSizeIterator for RawIter<T> {}
+impl<T> core::iter::FusedIterator for RawIter<T> {}
+impl<T> Clone for RawIter<T> {
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data,
+            ctrl: self.ctrl,
+            next: self.next,
+            remaining: self.remaining,
+        }
+    }
+}
+
+/// Removes the elements of a [`RawTable`] as they are yielded, and the remaining ones when dropped.
+pub(crate) struct RawDrain<'a, T, Alloc: IAlloc> {
+    iter: RawIter<T>,
+    table: &'a mut RawTable<T, Alloc>,
+}
+impl<T, Alloc: IAlloc> Iterator for RawDrain<'_, T, Alloc> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        let index = self.iter.next()?;
+        // SAFETY: the slot is full, and removing it doesn't affect the iteration over the following slots.
+        Some(unsafe { self.table.remove(index) })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<T, Alloc: IAlloc> ExactSizeIterator for RawDrain<'_, T, Alloc> {}
+impl<T, Alloc: IAlloc> core::iter::FusedIterator for RawDrain<'_, T, Alloc> {}
+impl<T, Alloc: IAlloc> Drop for RawDrain<'_, T, Alloc> {
+    fn drop(&mut self) {
+        // Each element is removed from the table before being dropped, so a panicking destructor can't lead to a double drop.
+        self.for_each(drop);
+        // Tombstones are no longer needed once the table is empty.
+        self.table.clear_no_drop();
+    }
+}
+
+/// Yields the elements of a [`RawTable`] by value, freeing it when dropped.
+pub(crate) struct RawIntoIter<T, Alloc: IAlloc> {
+    iter: RawIter<T>,
+    data: AllocPtr<T, Alloc>,
+    buckets: usize,
+    alloc: Alloc,
+}
+impl<T, Alloc: IAlloc> Iterator for RawIntoIter<T, Alloc> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        let index = self.iter.next()?;
+        // SAFETY: the slot is full, and is yielded exactly once.
+        Some(unsafe { self.iter.bucket(index).as_ptr().read() })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<T, Alloc: IAlloc> ExactSizeIterator for RawIntoIter<T, Alloc> {}
+impl<T, Alloc: IAlloc> core::iter::FusedIterator for RawIntoIter<T, Alloc> {}
+impl<T, Alloc: IAlloc> Drop for RawIntoIter<T, Alloc> {
+    fn drop(&mut self) {
+        if self.buckets != 0 {
+            // SAFETY: the iterator owns the remaining elements, and the allocation.
+            unsafe {
+                self.iter.drop_remaining();
+                self.data.free(&mut self.alloc)
+            }
+        }
+    }
+}
@@ -0,0 +1,1093 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   Pierre Avital, <pierre.avital@me.com>
+//
+
+use core::{fmt::Debug, hash::Hash};
+
+use crate::alloc::{
+    vec::{bounds, ptr_add, Vec, VecInner},
+    AllocPtr, AllocationError, DefaultAllocator, IAlloc,
+};
+
+/// An ABI-stable double-ended queue, implemented as a growable ring buffer.
+///
+/// # Layout
+/// The deque is a `#[repr(C)]` struct made of:
+/// - a pointer to its buffer, which is preceded by an [`AllocPrefix`](crate::alloc::AllocPrefix) like [`Vec`]'s,
+///   and dangling if nothing has been allocated yet,
+/// - the capacity of the buffer (`usize`), which is `usize::MAX` for zero-sized types,
+/// - the index of the first element in the buffer (`usize`),
+/// - the number of elements (`usize`),
+/// - the allocator.
+///
+/// The element at index `i` is stored at `(head + i) % capacity` in the buffer, so the elements may wrap around
+/// the end of the buffer. [`VecDeque::make_contiguous`] moves them so that they don't.
+///
+/// Since the buffer is allocated like [`Vec`]'s, converting a [`Vec`] into a [`VecDeque`] never copies its elements,
+/// and the reverse conversion only moves them if they wrap around the end of the buffer.
+#[crate::stabby]
+pub struct VecDeque<T, Alloc: IAlloc = DefaultAllocator> {
+    start: AllocPtr<T, Alloc>,
+    capacity: usize,
+    head: usize,
+    len: usize,
+    alloc: Alloc,
+}
+// SAFETY: The deque owns its elements, like a `Vec`.
+unsafe impl<T: Send, Alloc: IAlloc + Send> Send for VecDeque<T, Alloc> {}
+// SAFETY: The deque owns its elements, like a `Vec`.
+unsafe impl<T: Sync, Alloc: IAlloc + Sync> Sync for VecDeque<T, Alloc> {}
+
+#[cfg(not(stabby_default_alloc = "disabled"))]
+impl<T> VecDeque<T> {
+    /// Constructs a new deque with the default allocator. This doesn't actually allocate.
+    pub const fn new() -> Self {
+        Self::new_in(DefaultAllocator::new())
+    }
+}
+impl<T, Alloc: IAlloc> VecDeque<T, Alloc> {
+    const FIRST_CAPACITY: usize = match 1024usize.checked_div(core::mem::size_of::<T>()) {
+        Some(0) => 1,
+        Some(v @ 1..=8) => v,
+        _ => 8,
+    };
+    #[inline(always)]
+    const fn zst_mode() -> bool {
+        core::mem::size_of::<T>() == 0
+    }
+    /// Constructs a new deque in `alloc`. This doesn't actually allocate.
+    pub const fn new_in(alloc: Alloc) -> Self {
+        Self {
+            start: AllocPtr::dangling(),
+            capacity: if Self::zst_mode() { usize::MAX } else { 0 },
+            head: 0,
+            len: 0,
+            alloc,
+        }
+    }
+    /// Constructs a new deque in `alloc`, allocating sufficient space for `capacity` elements.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn with_capacity_in(capacity: usize, alloc: Alloc) -> Self {
+        let mut this = Self::new_in(alloc);
+        this.reserve(capacity);
+        this
+    }
+    /// Constructs a new deque, allocating sufficient space for `capacity` elements.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn with_capacity(capacity: usize) -> Self
+    where
+        Alloc: Default,
+    {
+        Self::with_capacity_in(capacity, Alloc::default())
+    }
+    /// Returns the number of elements in the deque.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+    /// Returns `true` if the deque is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// The total capacity of the deque.
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+    /// Returns a reference to the deque's allocator.
+    pub const fn allocator(&self) -> &Alloc {
+        &self.alloc
+    }
+    /// The index in the buffer of the element at `index`.
+    const fn physical(&self, index: usize) -> usize {
+        let physical = self.head.wrapping_add(index);
+        if physical >= self.capacity {
+            physical.wrapping_sub(self.capacity)
+        } else {
+            physical
+        }
+    }
+    /// A pointer to the slot at `physical` in the buffer.
+    const fn slot(&self, physical: usize) -> *mut T {
+        if Self::zst_mode() {
+            self.start.ptr.as_ptr()
+        } else {
+            // SAFETY: `physical` is always lower than `capacity` when this is called.
+            unsafe { self.start.ptr.as_ptr().add(physical) }
+        }
+    }
+    /// Returns `true` if the elements don't wrap around the end of the buffer.
+    const fn is_contiguous(&self) -> bool {
+        Self::zst_mode() || self.head <= self.capacity.wrapping_sub(self.len)
+    }
+    /// Ensures that `additional` more elements can be pushed on `self` without reallocating.
+    ///
+    /// # Panics
+    /// This function panics if the allocator failed to provide an appropriate allocation.
+    pub fn reserve(&mut self, additional: usize) {
+        self.try_reserve(additional).unwrap();
+    }
+    /// Ensures that `additional` more elements can be pushed on `self` without reallocating.
+    ///
+    /// # Errors
+    /// If the allocator failed to provide an appropriate allocation, in which case `self` is left untouched.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), AllocationError> {
+        if self.capacity.wrapping_sub(self.len) >= additional {
+            return Ok(());
+        }
+        let new_capacity = self
+            .len
+            .checked_add(additional)
+            .filter(|capacity| {
+                capacity
+                    .checked_mul(core::mem::size_of::<T>())
+                    .map_or(false, |size| size <= isize::MAX as usize)
+            })
+            .ok_or(AllocationError())?;
+        let old_capacity = self.capacity;
+        let start = if old_capacity != 0 {
+            // SAFETY: `start` isn't dangling since the deque has a capacity.
+            unsafe {
+                self.start
+                    .realloc(&mut self.alloc, old_capacity, new_capacity)
+            }
+        } else {
+            AllocPtr::alloc_array(&mut self.alloc, new_capacity)
+        };
+        self.start = start.ok_or(AllocationError())?;
+        self.capacity = new_capacity;
+        // If the elements wrapped around the end of the old buffer, move the shortest part so that they still follow each other.
+        if self.head > old_capacity.wrapping_sub(self.len) {
+            let head_len = old_capacity.wrapping_sub(self.head);
+            let tail_len = self.len.wrapping_sub(head_len);
+            // SAFETY: both ranges are within the new buffer.
+            unsafe {
+                if tail_len < head_len && tail_len <= new_capacity.wrapping_sub(old_capacity) {
+                    core::ptr::copy_nonoverlapping(self.slot(0), self.slot(old_capacity), tail_len);
+                } else {
+                    let new_head = new_capacity.wrapping_sub(head_len);
+                    core::ptr::copy(self.slot(self.head), self.slot(new_head), head_len);
+                    self.head = new_head;
+                }
+            }
+        }
+        Ok(())
+    }
+    fn grow(&mut self) {
+        let additional = if self.capacity == 0 {
+            Self::FIRST_CAPACITY
+        } else {
+            (self.capacity >> 1).max(1)
+        };
+        self.reserve(additional)
+    }
+    /// Adds `value` at the back of the deque.
+    ///
+    /// # Panics
+    /// This function panics if the deque tried to grow due to
+    /// being full, and the allocator failed to provide a new allocation.
+    pub fn push_back(&mut self, value: T) {
+        if self.len == self.capacity {
+            self.grow()
+        }
+        // SAFETY: the slot after the last element is free.
+        unsafe { self.slot(self.physical(self.len)).write(value) };
+        self.len = self.len.wrapping_add(1);
+    }
+    /// Adds `value` at the front of the deque.
+    ///
+    /// # Panics
+    /// This function panics if the deque tried to grow due to
+    /// being full, and the allocator failed to provide a new allocation.
+    pub fn push_front(&mut self, value: T) {
+        if self.len == self.capacity {
+            self.grow()
+        }
+        self.head = if self.head == 0 {
+            self.capacity.wrapping_sub(1)
+        } else {
+            self.head.wrapping_sub(1)
+        };
+        // SAFETY: the slot before the first element is free.
+        unsafe { self.slot(self.head).write(value) };
+        self.len = self.len.wrapping_add(1);
+    }
+    /// Removes the last element of the deque and returns it.
+    #[rustversion::attr(since(1.83), const)]
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len = self.len.wrapping_sub(1);
+        // SAFETY: the slot held the last element, which is no longer part of the deque.
+        Some(unsafe { self.slot(self.physical(self.len)).read() })
+    }
+    /// Removes the first element of the deque and returns it.
+    #[rustversion::attr(since(1.83), const)]
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: the slot held the first element, which is no longer part of the deque.
+        let value = unsafe { self.slot(self.head).read() };
+        self.head = self.physical(1);
+        self.len = self.len.wrapping_sub(1);
+        Some(value)
+    }
+    /// Returns a reference to the element at `index`.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        // SAFETY: the element is initialized, since `index < len`.
+        (index < self.len).then(|| unsafe { &*self.slot(self.physical(index)) })
+    }
+    /// Returns a mutable reference to the element at `index`.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        // SAFETY: the element is initialized, since `index < len`.
+        (index < self.len).then(|| unsafe { &mut *self.slot(self.physical(index)) })
+    }
+    /// Returns a reference to the first element.
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+    /// Returns a mutable reference to the first element.
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+    /// Returns a reference to the last element.
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.wrapping_sub(1))
+    }
+    /// Returns a mutable reference to the last element.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len.wrapping_sub(1))
+    }
+    /// Swaps the elements at indices `a` and `b`.
+    ///
+    /// # Panics
+    /// If either index is out of bounds.
+    pub fn swap(&mut self, a: usize, b: usize) {
+        assert!(
+            a < self.len && b < self.len,
+            "Attempted to swap indices {a} and {b} in a VecDeque of length {}",
+            self.len
+        );
+        // SAFETY: both indices are in bounds.
+        unsafe { core::ptr::swap(self.slot(self.physical(a)), self.slot(self.physical(b))) }
+    }
+    /// Inserts `value` at `index`, shifting the elements after it.
+    ///
+    /// # Panics
+    /// If `index > self.len()`, or if the deque tried to grow and the allocator failed to provide a new allocation.
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(
+            index <= self.len,
+            "Attempted to insert at index {index} in a VecDeque of length {}",
+            self.len
+        );
+        if index < self.len / 2 {
+            self.push_front(value);
+            for i in 0..index {
+                self.swap(i, i.wrapping_add(1));
+            }
+        } else {
+            self.push_back(value);
+            for i in (index..self.len.wrapping_sub(1)).rev() {
+                self.swap(i, i.wrapping_add(1));
+            }
+        }
+    }
+    /// Removes the element at `index`, shifting the elements around it.
+    ///
+    /// Returns `None` if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+        if index < self.len / 2 {
+            for i in (0..index).rev() {
+                self.swap(i, i.wrapping_add(1));
+            }
+            self.pop_front()
+        } else {
+            for i in index..self.len.wrapping_sub(1) {
+                self.swap(i, i.wrapping_add(1));
+            }
+            self.pop_back()
+        }
+    }
+    /// Removes all elements from the `len`th onward.
+    ///
+    /// Does nothing if `self.len() <= len`.
+    pub fn truncate(&mut self, len: usize) {
+        while self.len > len {
+            drop(self.pop_back());
+        }
+    }
+    /// Removes all elements from the deque, keeping its allocation.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+        self.head = 0;
+    }
+    /// Keeps only the elements for which `f` returns `true`, preserving their order.
+    pub fn retain(&mut self, mut f: impl FnMut(&mut T) -> bool) {
+        let mut kept = 0usize;
+        for index in 0..self.len {
+            // SAFETY: `index < len`.
+            if f(unsafe { &mut *self.slot(self.physical(index)) }) {
+                if index != kept {
+                    self.swap(index, kept);
+                }
+                kept = kept.wrapping_add(1);
+            }
+        }
+        self.truncate(kept);
+    }
+    /// Returns `true` if the deque contains an element equal to `value`.
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|item| item == value)
+    }
+    /// Returns the elements of the deque, in order, as two slices.
+    ///
+    /// The second slice is empty unless the elements wrap around the end of the buffer.
+    pub const fn as_slices(&self) -> (&[T], &[T]) {
+        let (front, back) = self.slice_ranges();
+        // SAFETY: both ranges hold initialized elements.
+        unsafe {
+            (
+                core::slice::from_raw_parts(self.slot(front.0), front.1),
+                core::slice::from_raw_parts(self.slot(0), back),
+            )
+        }
+    }
+    /// Returns the elements of the deque, in order, as two mutable slices.
+    ///
+    /// The second slice is empty unless the elements wrap around the end of the buffer.
+    #[rustversion::attr(since(1.83), const)]
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (front, back) = self.slice_ranges();
+        // SAFETY: both ranges hold initialized elements, and don't overlap.
+        unsafe {
+            (
+                core::slice::from_raw_parts_mut(self.slot(front.0), front.1),
+                core::slice::from_raw_parts_mut(self.slot(0), back),
+            )
+        }
+    }
+    /// The start and length of the first slice, and the length of the second one (which starts at 0).
+    const fn slice_ranges(&self) -> ((usize, usize), usize) {
+        if self.is_contiguous() {
+            ((self.head, self.len), 0)
+        } else {
+            let head_len = self.capacity.wrapping_sub(self.head);
+            ((self.head, head_len), self.len.wrapping_sub(head_len))
+        }
+    }
+    /// Moves the elements so that they're contiguous in the buffer, returning them as a single slice.
+    ///
+    /// This doesn't allocate, doesn't move the elements if they're already contiguous, and otherwise only moves the
+    /// elements (never the rest of the buffer), in `O(len)`.
+    #[rustversion::attr(since(1.92), const)]
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        if !self.is_contiguous() {
+            let free = self.capacity.wrapping_sub(self.len);
+            // The elements at the end of the buffer come first, followed by those at its start.
+            let front_len = self.capacity.wrapping_sub(self.head);
+            let back_len = self.len.wrapping_sub(front_len);
+            // SAFETY: all ranges are within the buffer, and only initialized elements are moved, with `copy` used
+            // wherever the source and destination may overlap. The rotated ranges only hold initialized elements.
+            unsafe {
+                if free >= front_len {
+                    // From `DEFGH....ABC` to `ABCDEFGH....`.
+                    core::ptr::copy(self.slot(0), self.slot(front_len), back_len);
+                    core::ptr::copy_nonoverlapping(self.slot(self.head), self.slot(0), front_len);
+                    self.head = 0;
+                } else if free >= back_len {
+                    // From `FGH....ABCDE` to `...ABCDEFGH.`.
+                    core::ptr::copy(self.slot(self.head), self.slot(back_len), front_len);
+                    core::ptr::copy_nonoverlapping(self.slot(0), self.slot(self.len), back_len);
+                    self.head = back_len;
+                } else if front_len > back_len {
+                    // From `FGH.ABCDE` to `.FGHABCDE`, then rotate the elements to `.ABCDEFGH`.
+                    core::ptr::copy(self.slot(0), self.slot(free), back_len);
+                    core::slice::from_raw_parts_mut(self.slot(free), self.len)
+                        .rotate_left(back_len);
+                    self.head = free;
+                } else {
+                    // From `DEFGH.ABC` to `DEFGHABC.`, then rotate the elements to `ABCDEFGH.`.
+                    core::ptr::copy(self.slot(self.head), self.slot(back_len), front_len);
+                    core::slice::from_raw_parts_mut(self.slot(0), self.len).rotate_right(front_len);
+                    self.head = 0;
+                }
+            }
+        }
+        self.as_mut_slices().0
+    }
+    /// Iterates immutably over the deque's elements, from front to back.
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (front, back) = self.as_slices();
+        Iter {
+            front: front.iter(),
+            back: back.iter(),
+        }
+    }
+    /// Iterates mutably over the deque's elements, from front to back.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let (front, back) = self.as_mut_slices();
+        IterMut {
+            front: front.iter_mut(),
+            back: back.iter_mut(),
+        }
+    }
+    /// Removes the specified range from the deque in bulk, returning all removed elements as an iterator.
+    /// If the iterator is dropped before being fully consumed, it drops the remaining removed elements.
+    ///
+    /// If the drain is leaked, the deque may lose and leak elements, even if they weren't in the specified `range`.
+    ///
+    /// # Panics
+    /// If the range has a negative size, or if it exceeds `self.len()`.
+    pub fn drain<R: core::ops::RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T, Alloc> {
+        let (from, to) = bounds(&range, self.len);
+        let original_len = core::mem::replace(&mut self.len, from);
+        Drain {
+            deque: self,
+            from,
+            to,
+            front: from,
+            back: to,
+            original_len,
+        }
+    }
+}
+
+impl<T, Alloc: IAlloc> Drop for VecDeque<T, Alloc> {
+    fn drop(&mut self) {
+        let (front, back) = self.as_mut_slices();
+        // SAFETY: the elements are never accessed again.
+        unsafe {
+            core::ptr::drop_in_place(front);
+            core::ptr::drop_in_place(back);
+        }
+        if !Self::zst_mode() && self.capacity != 0 {
+            // SAFETY: the buffer was allocated by `self.alloc`.
+            unsafe { self.start.free(&mut self.alloc) }
+        }
+    }
+}
+
+impl<T, Alloc: IAlloc> From<Vec<T, Alloc>> for VecDeque<T, Alloc> {
+    /// Takes ownership of the vector's buffer, without copying its elements.
+    fn from(value: Vec<T, Alloc>) -> Self {
+        let len = value.len();
+        let (slice, capacity, alloc) = value.into_raw_components();
+        Self {
+            start: slice.start,
+            capacity: if Self::zst_mode() {
+                usize::MAX
+            } else {
+                capacity
+            },
+            head: 0,
+            len,
+            alloc,
+        }
+    }
+}
+impl<T, Alloc: IAlloc> From<VecDeque<T, Alloc>> for Vec<T, Alloc> {
+    /// Takes ownership of the deque's buffer, only moving its elements if they don't already start at the beginning
+    /// of the buffer.
+    fn from(mut value: VecDeque<T, Alloc>) -> Self {
+        value.make_contiguous();
+        if value.head != 0 && !VecDeque::<T, Alloc>::zst_mode() {
+            // SAFETY: the elements are contiguous, and `copy` handles overlapping ranges.
+            unsafe { core::ptr::copy(value.slot(value.head), value.slot(0), value.len) }
+        }
+        let value = core::mem::ManuallyDrop::new(value);
+        // SAFETY: `value` is never dropped, so the allocator is moved out exactly once.
+        let alloc = unsafe { core::ptr::read(&value.alloc) };
+        if VecDeque::<T, Alloc>::zst_mode() {
+            let mut vec = Vec::new_in(alloc);
+            // SAFETY: there are `value.len` zero-sized elements, whose ownership is transferred to `vec`.
+            unsafe { vec.set_len(value.len) };
+            vec
+        } else {
+            Vec {
+                inner: VecInner {
+                    start: value.start,
+                    end: ptr_add(value.start.ptr, value.len),
+                    capacity: ptr_add(value.start.ptr, value.capacity),
+                    alloc,
+                },
+            }
+        }
+    }
+}
+impl<T, Alloc: IAlloc + Default, const N: usize> From<[T; N]> for VecDeque<T, Alloc> {
+    fn from(value: [T; N]) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+impl<T: Clone, Alloc: IAlloc + Clone> Clone for VecDeque<T, Alloc> {
+    fn clone(&self) -> Self {
+        let mut ret = Self::with_capacity_in(self.len, self.alloc.clone());
+        ret.extend(self.iter().cloned());
+        ret
+    }
+}
+impl<T: PartialEq, Alloc: IAlloc> PartialEq for VecDeque<T, Alloc> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+impl<T: Eq, Alloc: IAlloc> Eq for VecDeque<T, Alloc> {}
+impl<T: PartialOrd, Alloc: IAlloc> PartialOrd for VecDeque<T, Alloc> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+impl<T: Ord, Alloc: IAlloc> Ord for VecDeque<T, Alloc> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+impl<T: Hash, Alloc: IAlloc> Hash for VecDeque<T, Alloc> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        self.iter().for_each(|item| item.hash(state));
+    }
+}
+impl<T: Debug, Alloc: IAlloc> Debug for VecDeque<T, Alloc> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+impl<T, Alloc: IAlloc + Default> Default for VecDeque<T, Alloc> {
+    fn default() -> Self {
+        Self::new_in(Alloc::default())
+    }
+}
+impl<T, Alloc: IAlloc> core::ops::Index<usize> for VecDeque<T, Alloc> {
+    type Output = T;
+    /// # Panics
+    /// If `index` is out of bounds.
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("Out of bounds access")
+    }
+}
+impl<T, Alloc: IAlloc> core::ops::IndexMut<usize> for VecDeque<T, Alloc> {
+    /// # Panics
+    /// If `index` is out of bounds.
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.get_mut(index).expect("Out of bounds access")
+    }
+}
+impl<T, Alloc: IAlloc> Extend<T> for VecDeque<T, Alloc> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        iter.for_each(|item| self.push_back(item))
+    }
+}
+impl<'a, T: Copy + 'a, Alloc: IAlloc> Extend<&'a T> for VecDeque<T, Alloc> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+impl<T, Alloc: IAlloc + Default> FromIterator<T> for VecDeque<T, Alloc> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut ret = Self::default();
+        ret.extend(iter);
+        ret
+    }
+}
+
+/// An iterator over the elements of a [`VecDeque`].
+pub struct Iter<'a, T> {
+    front: core::slice::Iter<'a, T>,
+    back: core::slice::Iter<'a, T>,
+}
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            front: self.front.clone(),
+            back: self.back.clone(),
+        }
+    }
+}
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len().wrapping_add(self.back.len());
+        (len, Some(len))
+    }
+}
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> core::iter::FusedIterator for Iter<'_, T> {}
+impl<T: Debug> Debug for Iter<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// A mutable iterator over the elements of a [`VecDeque`].
+pub struct IterMut<'a, T> {
+    front: core::slice::IterMut<'a, T>,
+    back: core::slice::IterMut<'a, T>,
+}
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len().wrapping_add(self.back.len());
+        (len, Some(len))
+    }
+}
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> core::iter::FusedIterator for IterMut<'_, T> {}
+
+/// An iterator over the elements of a [`VecDeque`], by value.
+#[crate::stabby]
+pub struct IntoIter<T, Alloc: IAlloc = DefaultAllocator> {
+    deque: VecDeque<T, Alloc>,
+}
+impl<T, Alloc: IAlloc> Iterator for IntoIter<T, Alloc> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.deque.pop_front()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.deque.len, Some(self.deque.len))
+    }
+}
+impl<T, Alloc: IAlloc> DoubleEndedIterator for IntoIter<T, Alloc> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.deque.pop_back()
+    }
+}
+impl<T, Alloc: IAlloc> ExactSizeIterator for IntoIter<T, Alloc> {}
+impl<T, Alloc: IAlloc> core::iter::FusedIterator for IntoIter<T, Alloc> {}
+
+impl<T, Alloc: IAlloc> IntoIterator for VecDeque<T, Alloc> {
+    type Item = T;
+    type IntoIter = IntoIter<T, Alloc>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { deque: self }
+    }
+}
+impl<'a, T, Alloc: IAlloc> IntoIterator for &'a VecDeque<T, Alloc> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, T, Alloc: IAlloc> IntoIterator for &'a mut VecDeque<T, Alloc> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator that removes elements from a [`VecDeque`], obtained through [`VecDeque::drain`].
+///
+/// Dropping the `Drain` drops the elements that haven't been yielded, and closes the gap left in the deque.
+///
+/// Note that leaking the `Drain` may cause its [`VecDeque`] to lose and leak elements,
+/// even outside the specified range.
+pub struct Drain<'a, T, Alloc: IAlloc = DefaultAllocator> {
+    deque: &'a mut VecDeque<T, Alloc>,
+    from: usize,
+    to: usize,
+    front: usize,
+    back: usize,
+    original_len: usize,
+}
+impl<T, Alloc: IAlloc> Drain<'_, T, Alloc> {
+    /// A pointer to the element at `index` in the deque, as it was before draining.
+    const fn element(&self, index: usize) -> *mut T {
+        self.deque.slot(self.deque.physical(index))
+    }
+}
+impl<T, Alloc: IAlloc> Iterator for Drain<'_, T, Alloc> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        (self.front < self.back).then(|| {
+            // SAFETY: the element is in the drained range, and hasn't been yielded yet.
+            let value = unsafe { self.element(self.front).read() };
+            self.front = self.front.wrapping_add(1);
+            value
+        })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back.wrapping_sub(self.front);
+        (remaining, Some(remaining))
+    }
+}
+impl<T, Alloc: IAlloc> DoubleEndedIterator for Drain<'_, T, Alloc> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        (self.front < self.back).then(|| {
+            self.back = self.back.wrapping_sub(1);
+            // SAFETY: the element is in the drained range, and hasn't been yielded yet.
+            unsafe { self.element(self.back).read() }
+        })
+    }
+}
+impl<T, Alloc: IAlloc> ExactSizeIterator for Drain<'_, T, Alloc> {}
+impl<T, Alloc: IAlloc> core::iter::FusedIterator for Drain<'_, T, Alloc> {}
+impl<T, Alloc: IAlloc> Drop for Drain<'_, T, Alloc> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+        let drained = self.to.wrapping_sub(self.from);
+        if drained == 0 {
+            self.deque.len = self.original_len;
+            return;
+        }
+        let tail_len = self.original_len.wrapping_sub(self.to);
+        // Close the gap by moving whichever side of it is shortest.
+        // SAFETY: each element is moved to a slot that is either in the gap, or has already been moved out of.
+        unsafe {
+            if self.from <= tail_len {
+                for index in (0..self.from).rev() {
+                    core::ptr::copy_nonoverlapping(
+                        self.element(index),
+                        self.element(index.wrapping_add(drained)),
+                        1,
+                    );
+                }
+                self.deque.head = self.deque.physical(drained);
+            } else {
+                for index in self.to..self.original_len {
+                    core::ptr::copy_nonoverlapping(
+                        self.element(index),
+                        self.element(index.wrapping_sub(drained)),
+                        1,
+                    );
+                }
+            }
+        }
+        self.deque.len = self.original_len.wrapping_sub(drained);
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+    use serde::{de::Visitor, Deserialize, Serialize};
+    impl<T: Serialize, Alloc: IAlloc> Serialize for VecDeque<T, Alloc> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            serializer.collect_seq(self.iter())
+        }
+    }
+    impl<'a, T: Deserialize<'a>, Alloc: IAlloc + Default> Deserialize<'a> for VecDeque<T, Alloc> {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'a>,
+        {
+            deserializer.deserialize_seq(VecDequeVisitor(core::marker::PhantomData))
+        }
+    }
+    pub struct VecDequeVisitor<T, Alloc>(core::marker::PhantomData<(T, Alloc)>);
+    impl<'a, T: Deserialize<'a>, Alloc: IAlloc + Default> Visitor<'a> for VecDequeVisitor<T, Alloc> {
+        type Value = VecDeque<T, Alloc>;
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            formatter.write_str("A sequence")
+        }
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::SeqAccess<'a>,
+        {
+            let mut this =
+                VecDeque::with_capacity_in(seq.size_hint().unwrap_or(0), Alloc::default());
+            while let Some(v) = seq.next_element()? {
+                this.push_back(v);
+            }
+            Ok(this)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn vec_deque() {
+        use rand::Rng;
+        let _ = crate::AssertStable::<VecDeque<u32>>(core::marker::PhantomData);
+        let mut rng = rand::thread_rng();
+        for _ in 0..if cfg!(miri) { 5 } else { 200 } {
+            let mut reference = std::collections::VecDeque::new();
+            let mut deque = VecDeque::new();
+            for _ in 0..rng.gen_range(0..500) {
+                let value = rng.gen::<u16>();
+                match rng.gen_range(0..8) {
+                    0 => assert_eq!(deque.pop_front(), reference.pop_front()),
+                    1 => assert_eq!(deque.pop_back(), reference.pop_back()),
+                    2 => {
+                        deque.push_front(value);
+                        reference.push_front(value);
+                    }
+                    3 => {
+                        let index = rng.gen_range(0..=deque.len());
+                        deque.insert(index, value);
+                        reference.insert(index, value);
+                    }
+                    4 => {
+                        let index = rng.gen_range(0..=deque.len());
+                        assert_eq!(deque.remove(index), reference.remove(index));
+                    }
+                    _ => {
+                        deque.push_back(value);
+                        reference.push_back(value);
+                    }
+                }
+                assert!(deque.iter().eq(reference.iter()));
+            }
+            assert!(deque.iter().rev().eq(reference.iter().rev()));
+            let (a, b) = deque.as_slices();
+            assert!(a.iter().chain(b).eq(reference.iter()));
+            let len = deque.len();
+            let (from, to) = (len / 4, len / 2);
+            let drained = deque.drain(from..to).collect::<std::vec::Vec<_>>();
+            let expected = reference.drain(from..to).collect::<std::vec::Vec<_>>();
+            assert_eq!(drained, expected);
+            assert!(deque.iter().eq(reference.iter()));
+            deque.retain(|v| *v % 3 != 0);
+            reference.retain(|v| *v % 3 != 0);
+            assert!(deque
+                .make_contiguous()
+                .iter()
+                .eq(reference.make_contiguous().iter()));
+            let vec: crate::alloc::vec::Vec<u16> = deque.into();
+            assert_eq!(vec.as_slice(), reference.make_contiguous());
+        }
+    }
+
+    #[test]
+    fn make_contiguous() {
+        let capacity = VecDeque::<u32>::with_capacity(8).capacity();
+        for head in 0..capacity {
+            for len in 0..=capacity {
+                let mut deque = VecDeque::<u32>::with_capacity(capacity);
+                for _ in 0..head {
+                    deque.push_back(u32::MAX);
+                }
+                for _ in 0..head {
+                    deque.pop_front();
+                }
+                deque.extend(0..len as u32);
+                assert_eq!(deque.capacity(), capacity);
+                assert!(deque.make_contiguous().iter().copied().eq(0..len as u32));
+                assert!(deque.as_slices().1.is_empty());
+                let vec = crate::alloc::vec::Vec::from(deque);
+                assert!(vec.iter().copied().eq(0..len as u32));
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn drains() {
+        let counter = std::sync::Arc::new(());
+        let capacity = VecDeque::<u32>::with_capacity(8).capacity();
+        for head in 0..capacity {
+            for len in 0..=capacity {
+                for from in 0..=len {
+                    for to in from..=len {
+                        // Drop the drain untouched, partly consumed from either or both ends, or fully consumed.
+                        for (taken_front, taken_back) in [(0, 0), (1, 0), (0, 1), (1, 1), (len, 0)]
+                        {
+                            let mut deque: VecDeque<_> = VecDeque::with_capacity(capacity);
+                            for _ in 0..head {
+                                deque.push_back((u32::MAX, counter.clone()));
+                            }
+                            for _ in 0..head {
+                                deque.pop_front();
+                            }
+                            deque.extend((0..len as u32).map(|i| (i, counter.clone())));
+                            let mut reference =
+                                (0..len as u32).collect::<std::collections::VecDeque<_>>();
+                            let mut drain = deque.drain(from..to);
+                            let mut expected = reference.drain(from..to);
+                            for _ in 0..taken_front {
+                                assert_eq!(drain.next().map(|v| v.0), expected.next());
+                            }
+                            for _ in 0..taken_back {
+                                assert_eq!(drain.next_back().map(|v| v.0), expected.next_back());
+                            }
+                            assert_eq!(drain.len(), expected.len());
+                            drop(drain);
+                            drop(expected);
+                            assert_eq!(deque.capacity(), capacity);
+                            assert!(deque.iter().map(|v| v.0).eq(reference.iter().copied()));
+                            assert_eq!(
+                                std::sync::Arc::strong_count(&counter),
+                                deque.len().wrapping_add(1)
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        use core::ops::Bound;
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+        let mut deque: VecDeque<u32> = VecDeque::from([0, 1, 2]);
+        assert_eq!(deque.drain(3..).count(), 0);
+        assert_eq!(deque.drain(1..1).count(), 0);
+        assert_eq!(
+            deque
+                .drain((Bound::Excluded(0), Bound::Included(1)))
+                .count(),
+            1
+        );
+        for range in [
+            (Bound::Excluded(usize::MAX), Bound::Unbounded),
+            (Bound::Unbounded, Bound::Included(usize::MAX)),
+            (Bound::Included(2), Bound::Excluded(1)),
+            (Bound::Unbounded, Bound::Excluded(3)),
+        ] {
+            assert!(catch_unwind(AssertUnwindSafe(|| {
+                deque.drain(range);
+            }))
+            .is_err());
+        }
+        assert!(deque.iter().eq(&[0, 2]));
+    }
+
+    #[test]
+    fn zsts() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        struct Zst;
+        impl Drop for Zst {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        let mut deque = VecDeque::new();
+        assert_eq!(deque.capacity(), usize::MAX);
+        for _ in 0..10 {
+            deque.push_front(Zst);
+            deque.push_back(Zst);
+        }
+        assert_eq!(deque.len(), 20);
+        assert!(deque.pop_front().is_some());
+        assert!(deque.pop_back().is_some());
+        deque.insert(3, Zst);
+        assert!(deque.remove(5).is_some());
+        assert_eq!(DROPS.load(Ordering::Relaxed), 3);
+        let mut drain = deque.drain(2..12);
+        assert!(drain.next().is_some());
+        assert!(drain.next_back().is_some());
+        drop(drain);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 13);
+        assert_eq!(deque.len(), 8);
+        assert_eq!(deque.drain(4..4).count(), 0);
+        assert_eq!(deque.iter().count(), 8);
+        assert_eq!(deque.make_contiguous().len(), 8);
+        let vec = crate::alloc::vec::Vec::from(deque);
+        assert_eq!(vec.len(), 8);
+        let mut deque = VecDeque::from(vec);
+        deque.push_front(Zst);
+        assert_eq!(deque.len(), 9);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 13);
+        drop(deque);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 22);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn wrapped_conversions() {
+        let counter = std::sync::Arc::new(());
+        let capacity = VecDeque::<u32>::with_capacity(8).capacity();
+        for head in 0..capacity {
+            for len in 0..=capacity {
+                let vec: crate::alloc::vec::Vec<_> =
+                    (0..capacity as u32).map(|i| (i, counter.clone())).collect();
+                let ptr = vec.as_ptr();
+                // Rotate the elements of a full deque, so that they wrap around the end of the buffer.
+                let mut deque = VecDeque::from(vec);
+                for _ in 0..head {
+                    let front = deque.pop_front().unwrap();
+                    deque.push_back(front);
+                }
+                while deque.len() > len {
+                    deque.pop_back();
+                }
+                assert_eq!(deque.capacity(), capacity);
+                let expected = (0..capacity as u32)
+                    .cycle()
+                    .skip(head)
+                    .take(len)
+                    .collect::<std::vec::Vec<_>>();
+                let vec = crate::alloc::vec::Vec::from(deque);
+                assert_eq!(vec.as_ptr(), ptr);
+                assert!(vec.iter().map(|v| v.0).eq(expected.iter().copied()));
+                assert_eq!(std::sync::Arc::strong_count(&counter), len.wrapping_add(1));
+                let mut deque = VecDeque::from(vec);
+                deque.push_front((u32::MAX, counter.clone()));
+                assert_eq!(deque.pop_front().map(|v| v.0), Some(u32::MAX));
+                assert!(deque.iter().map(|v| v.0).eq(expected.iter().copied()));
+            }
+        }
+        assert_eq!(std::sync::Arc::strong_count(&counter), 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn conversions() {
+        let counter = std::sync::Arc::new(());
+        let vec: crate::alloc::vec::Vec<_> = (0..10).map(|_| counter.clone()).collect();
+        let ptr = vec.as_ptr();
+        let mut deque = VecDeque::from(vec);
+        deque.pop_back();
+        deque.push_front(counter.clone());
+        assert_eq!(std::sync::Arc::strong_count(&counter), 11);
+        let vec = crate::alloc::vec::Vec::from(deque);
+        assert_eq!(vec.as_ptr(), ptr);
+        let mut deque = VecDeque::from(vec);
+        deque.drain(2..5).next();
+        assert_eq!(deque.len(), 7);
+        assert_eq!(std::sync::Arc::strong_count(&counter), 8);
+        drop(deque.drain(..).rev());
+        assert_eq!(std::sync::Arc::strong_count(&counter), 1);
+        let mut zsts: VecDeque<()> = VecDeque::from([(), (), ()]);
+        zsts.push_front(());
+        assert_eq!(zsts.pop_back(), Some(()));
+        assert_eq!(crate::alloc::vec::Vec::from(zsts).len(), 3);
+    }
+}
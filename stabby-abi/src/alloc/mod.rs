@@ -154,6 +154,18 @@ pub trait IAlloc: Unpin {
         }
         ret
     }
+    /// Identifies the allocator, so that builds with `--cfg stabby_check_alloc="true"` can check that allocations
+    /// are only freed or reallocated by an allocator with the same identity as the one that made them.
+    ///
+    /// By default, that's allocators whose `free` is the same function: allocators of the same type, from the same
+    /// library. Allocators whose instances can't free each other's allocations, or that may free those made by other
+    /// libraries, should override it.
+    fn identity(&self) -> AllocIdentity {
+        AllocIdentity::new(
+            *b"IAlloc\0\0",
+            <Self as IAlloc>::free as unsafe fn(&mut Self, *mut ()) as *const (),
+        )
+    }
 }
 
 /// An ABI stable equivalent to [`IAlloc`].
@@ -247,6 +259,88 @@ pub struct AllocPrefix<Alloc> {
     pub origin: NonNull<()>,
     /// A slot for the allocator.
     pub alloc: core::mem::MaybeUninit<Alloc>,
+    /// The identity of the allocator that made this allocation, checked when it's freed or reallocated.
+    ///
+    /// This is a zero-sized `()` unless `--cfg stabby_check_alloc="true"` has been set in the `RUSTFLAGS`, in which
+    /// case it changes the layout of the prefix: every library exchanging `stabby` containers must then be built with
+    /// it, which the prefix's report (and therefore the reports of all containers) lets import checks enforce.
+    pub identity: AllocIdentitySlot,
+}
+
+/// The type of [`AllocPrefix::identity`]: `()` unless `--cfg stabby_check_alloc="true"` has been set in the `RUSTFLAGS`.
+#[cfg(not(stabby_check_alloc = "true"))]
+pub type AllocIdentitySlot = ();
+/// The type of [`AllocPrefix::identity`]: `()` unless `--cfg stabby_check_alloc="true"` has been set in the `RUSTFLAGS`.
+#[cfg(stabby_check_alloc = "true")]
+pub type AllocIdentitySlot = AllocIdentity;
+
+/// Identifies the allocator that made an allocation, as returned by [`IAlloc::identity`].
+///
+/// When `--cfg stabby_check_alloc="true"` is set, every [`AllocPrefix`] records one, and freeing or reallocating
+/// it through an allocator with another identity panics instead of silently corrupting the heap. This typically
+/// catches pointers that crossed libraries built with different default allocators, or that were handed to the
+/// wrong arena.
+#[crate::stabby]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllocIdentity {
+    magic: u64,
+    kind: [u8; 8],
+    instance: *const (),
+}
+impl AllocIdentity {
+    const MAGIC: u64 = u64::from_le_bytes(*b"stabbyAI");
+    /// The identity of allocators of `kind`, which names them in error messages.
+    ///
+    /// `instance` distinguishes allocators of the same kind that can't free each other's allocations, and should be
+    /// null if any allocator of that kind can free them.
+    pub const fn new(kind: [u8; 8], instance: *const ()) -> Self {
+        Self {
+            magic: Self::MAGIC,
+            kind,
+            instance,
+        }
+    }
+    /// The kind of allocator, as passed to [`AllocIdentity::new`].
+    pub const fn kind(&self) -> [u8; 8] {
+        self.kind
+    }
+    /// The instance of the allocator, as passed to [`AllocIdentity::new`].
+    pub const fn instance(&self) -> *const () {
+        self.instance
+    }
+    /// Checks that `self`, the identity of the allocator that made the allocation at `origin`, is that of `freeing`.
+    ///
+    /// `operation` is what was about to be done with the allocation.
+    ///
+    /// # Panics
+    /// If `self` isn't a valid identity, or is that of another allocator.
+    #[track_caller]
+    pub fn check(&self, freeing: &Self, origin: NonNull<()>, operation: &str) {
+        if self.magic != Self::MAGIC {
+            panic!(
+                "stabby: attempted to {operation} the allocation at {origin:p} through {freeing}, but it has no allocator identity: it was either not allocated by a stabby container built with `stabby_check_alloc`, or its prefix has been overwritten"
+            )
+        }
+        if self != freeing {
+            panic!(
+                "stabby: attempted to {operation} the allocation at {origin:p} through {freeing}, but it was allocated by {self}"
+            )
+        }
+    }
+}
+impl core::fmt::Display for AllocIdentity {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let len = self.kind.iter().position(|b| *b == 0).unwrap_or(8);
+        let kind = self.kind.get(..len).unwrap_or(&self.kind);
+        match core::str::from_utf8(kind) {
+            Ok(kind) => write!(f, "`{kind}`")?,
+            Err(_) => write!(f, "{kind:?}")?,
+        }
+        if !self.instance.is_null() {
+            write!(f, " ({:p})", self.instance)?;
+        }
+        Ok(())
+    }
 }
 impl<Alloc> AllocPrefix<Alloc> {
     /// The offset between the prefix and a field of type `T`.
@@ -347,6 +441,9 @@ impl<T, Alloc> AllocPtr<T, Alloc> {
     /// Initializes any given pointer:
     /// - The returned pointer is guaranteed to be correctly aligned for `T`
     /// - It is guaranteed to preceded without padding by an `AllocPrefix<Alloc>`
+    ///
+    /// When `stabby_check_alloc` is enabled, the prefix doesn't identify its allocator: use [`AllocPtr::init_in`] for
+    /// pointers that will be freed through [`AllocPtr::free`].
     /// # Safety
     /// `ptr` MUST be word-aligned, and MUST be valid for writes for at least the size of
     /// `#[repr(C)] struct { prefix: AllocPrefix<Alloc>, data: [T; capacity] }`
@@ -370,11 +467,33 @@ impl<T, Alloc> AllocPtr<T, Alloc> {
             capacity: AtomicUsize::new(capacity),
             origin: ptr,
             alloc: core::mem::MaybeUninit::uninit(),
+            #[cfg(not(stabby_check_alloc = "true"))]
+            identity: (),
+            #[cfg(stabby_check_alloc = "true")]
+            identity: AllocIdentity {
+                magic: 0,
+                kind: [0; 8],
+                instance: core::ptr::null(),
+            },
         });
         this
     }
 }
 impl<T, Alloc: IAlloc> AllocPtr<T, Alloc> {
+    /// Initializes a pointer allocated by `alloc`, as [`AllocPtr::init`] does, recording `alloc`'s identity if
+    /// `stabby_check_alloc` is enabled.
+    /// # Safety
+    /// See [`AllocPtr::init`]. `ptr` must have been allocated by `alloc`.
+    pub unsafe fn init_in(ptr: NonNull<()>, capacity: usize, alloc: &Alloc) -> Self {
+        #[allow(unused_mut)]
+        let mut this = unsafe { Self::init(ptr, capacity) };
+        #[cfg(stabby_check_alloc = "true")]
+        unsafe {
+            this.prefix_mut().identity = alloc.identity()
+        };
+        let _ = alloc;
+        this
+    }
     /// Allocates a pointer to a single element of `T`, prefixed by an [`AllocPrefix`]
     pub fn alloc(alloc: &mut Alloc) -> Option<Self> {
         Self::alloc_array(alloc, 1)
@@ -384,7 +503,7 @@ impl<T, Alloc: IAlloc> AllocPtr<T, Alloc> {
         let mut layout = Layout::of::<AllocPrefix<Alloc>>().concat(Layout::array::<T>(capacity));
         layout.align = core::mem::align_of::<AllocPrefix<Alloc>>();
         let ptr = alloc.alloc(layout);
-        NonNull::new(ptr).map(|ptr| unsafe { Self::init(ptr, capacity) })
+        NonNull::new(ptr).map(|ptr| unsafe { Self::init_in(ptr, capacity, alloc) })
     }
     /// Reallocates a pointer to an array of `capacity` `T`, prefixed by an [`AllocPrefix`].
     ///
@@ -401,6 +520,8 @@ impl<T, Alloc: IAlloc> AllocPtr<T, Alloc> {
         let mut layout =
             Layout::of::<AllocPrefix<Alloc>>().concat(Layout::array::<T>(prev_capacity));
         layout.align = core::mem::align_of::<AllocPrefix<Alloc>>();
+        #[cfg(stabby_check_alloc = "true")]
+        self.check_identity(alloc, "reallocate");
         let ptr = alloc.realloc(
             self.prefix_ptr().cast().as_ptr(),
             layout,
@@ -408,14 +529,25 @@ impl<T, Alloc: IAlloc> AllocPtr<T, Alloc> {
                 .concat(Layout::array::<T>(new_capacity))
                 .size,
         );
-        NonNull::new(ptr).map(|ptr| unsafe { Self::init(ptr, new_capacity) })
+        NonNull::new(ptr).map(|ptr| unsafe { Self::init_in(ptr, new_capacity, alloc) })
     }
     /// Reallocates a pointer to an array of `capacity` `T`, prefixed by an [`AllocPrefix`]
     /// # Safety
     /// `self` must not be dangling, and is freed after this returns.
     pub unsafe fn free(self, alloc: &mut Alloc) {
+        #[cfg(stabby_check_alloc = "true")]
+        self.check_identity(alloc, "free");
         alloc.free(self.prefix().origin.as_ptr())
     }
+    /// Panics if the allocation wasn't made by an allocator with the same identity as `alloc`.
+    #[cfg(stabby_check_alloc = "true")]
+    #[track_caller]
+    unsafe fn check_identity(&self, alloc: &Alloc, operation: &str) {
+        let prefix = self.prefix();
+        prefix
+            .identity
+            .check(&alloc.identity(), prefix.origin, operation)
+    }
 }
 
 /// A helper to work with allocated slices.
@@ -449,3 +581,53 @@ impl<T, Alloc> Clone for AllocSlice<T, Alloc> {
         *self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alloc_identity() {
+        let _ = crate::AssertStable::<AllocIdentity>(core::marker::PhantomData);
+        let origin = NonNull::<u64>::dangling().cast();
+        let default = DefaultAllocator::default().identity();
+        default.check(&DefaultAllocator::default().identity(), origin, "free");
+        let arena = allocators::Arena::new(64).unwrap();
+        let other = allocators::Arena::new(64).unwrap();
+        assert_eq!(arena.allocator().identity(), arena.allocator().identity());
+        assert_ne!(arena.allocator().identity(), other.allocator().identity());
+        assert_ne!(arena.allocator().identity(), default);
+        let tracker = allocators::AllocTracker::new();
+        let tracking = allocators::TrackingAlloc::new(DefaultAllocator::default(), &tracker);
+        assert_ne!(tracking.identity(), default);
+    }
+    #[test]
+    #[should_panic(expected = "but it was allocated by `Arena`")]
+    fn alloc_identity_mismatch() {
+        let origin = NonNull::<u64>::dangling().cast();
+        let arena = allocators::Arena::new(64).unwrap();
+        let other = allocators::Arena::new(64).unwrap();
+        arena
+            .allocator()
+            .identity()
+            .check(&other.allocator().identity(), origin, "free");
+    }
+    #[cfg(stabby_check_alloc = "true")]
+    #[test]
+    #[should_panic(expected = "but it was allocated by")]
+    fn free_through_wrong_allocator() {
+        #[derive(Default)]
+        struct Other(DefaultAllocator);
+        impl IAlloc for Other {
+            fn alloc(&mut self, layout: Layout) -> *mut () {
+                IAlloc::alloc(&mut self.0, layout)
+            }
+            unsafe fn free(&mut self, ptr: *mut ()) {
+                IAlloc::free(&mut self.0, ptr)
+            }
+        }
+        let boxed = boxed::Box::new(42u64);
+        // SAFETY: `Other` forwards to `DefaultAllocator`, so this would be sound without the identity check.
+        let _ = unsafe { core::mem::transmute::<boxed::Box<u64>, boxed::Box<u64, Other>>(boxed) };
+    }
+}
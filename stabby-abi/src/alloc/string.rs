@@ -45,6 +45,641 @@ impl<Alloc: IAlloc> String<Alloc> {
     pub fn try_concat<S: AsRef<str> + ?Sized>(&mut self, s: &S) -> Result<(), AllocationError> {
         self.try_concat_str(s.as_ref())
     }
+    /// Constructs a new string in `alloc`, allocating sufficient space for `capacity` bytes.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn with_capacity_in(capacity: usize, alloc: Alloc) -> Self {
+        Self {
+            inner: Vec::with_capacity_in(capacity, alloc),
+        }
+    }
+    /// Constructs a new string, allocating sufficient space for `capacity` bytes.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn with_capacity(capacity: usize) -> Self
+    where
+        Alloc: Default,
+    {
+        Self::with_capacity_in(capacity, Alloc::default())
+    }
+    /// Constructs a new string in `alloc`, allocating sufficient space for `capacity` bytes.
+    ///
+    /// # Errors
+    /// Gives back `alloc` if it couldn't provide a sufficient allocation.
+    pub fn try_with_capacity_in(capacity: usize, alloc: Alloc) -> Result<Self, Alloc> {
+        Vec::try_with_capacity_in(capacity, alloc).map(|inner| Self { inner })
+    }
+    /// Constructs a new string, allocating sufficient space for `capacity` bytes.
+    ///
+    /// # Errors
+    /// Gives back the allocator if it couldn't provide a sufficient allocation.
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, Alloc>
+    where
+        Alloc: Default,
+    {
+        Self::try_with_capacity_in(capacity, Alloc::default())
+    }
+    /// Converts a vector of bytes into a string, checking that it is valid UTF-8.
+    ///
+    /// # Errors
+    /// If `bytes` isn't valid UTF-8, the returned error gives access to the bytes through [`FromUtf8Error::into_bytes`].
+    #[rustversion::attr(since(1.86), const)]
+    pub fn from_utf8(bytes: Vec<u8, Alloc>) -> Result<Self, FromUtf8Error<Alloc>> {
+        match core::str::from_utf8(bytes.as_slice()) {
+            Ok(_) => Ok(Self { inner: bytes }),
+            Err(error) => Err(FromUtf8Error { bytes, error }),
+        }
+    }
+    /// Converts a vector of bytes into a string without checking that it is valid UTF-8.
+    ///
+    /// # Safety
+    /// `bytes` must be valid UTF-8.
+    pub const unsafe fn from_utf8_unchecked(bytes: Vec<u8, Alloc>) -> Self {
+        Self { inner: bytes }
+    }
+    /// Converts a slice of bytes into a string, replacing invalid UTF-8 sequences with `U+FFFD REPLACEMENT CHARACTER`.
+    ///
+    /// Unlike `std`'s, this always allocates.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn from_utf8_lossy(bytes: &[u8]) -> Self
+    where
+        Alloc: Default,
+    {
+        Self::try_from_utf8_lossy(bytes).unwrap()
+    }
+    /// Converts a slice of bytes into a string, replacing invalid UTF-8 sequences with `U+FFFD REPLACEMENT CHARACTER`.
+    ///
+    /// # Errors
+    /// Returns an [`AllocationError`] if the allocator failed to provide a large enough allocation.
+    pub fn try_from_utf8_lossy(mut bytes: &[u8]) -> Result<Self, AllocationError>
+    where
+        Alloc: Default,
+    {
+        let mut this = Self::try_with_capacity(bytes.len()).map_err(|_| AllocationError())?;
+        loop {
+            match core::str::from_utf8(bytes) {
+                Ok(valid) => return this.try_push_str(valid).map(|()| this),
+                Err(e) => {
+                    let (valid, invalid) = bytes.split_at(e.valid_up_to());
+                    this.try_push_str(unsafe { core::str::from_utf8_unchecked(valid) })?;
+                    this.try_push(char::REPLACEMENT_CHARACTER)?;
+                    match e.error_len() {
+                        Some(error_len) => bytes = invalid.get(error_len..).unwrap_or_default(),
+                        None => return Ok(this),
+                    }
+                }
+            }
+        }
+    }
+    /// Decodes UTF-16 into a string.
+    ///
+    /// # Errors
+    /// If `v` contains an unpaired surrogate.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn from_utf16(v: &[u16]) -> Result<Self, FromUtf16Error>
+    where
+        Alloc: Default,
+    {
+        let mut this = Self::with_capacity(v.len());
+        for c in char::decode_utf16(v.iter().copied()) {
+            this.push(c.map_err(|_| FromUtf16Error(()))?);
+        }
+        Ok(this)
+    }
+    /// Decodes UTF-16 into a string, replacing unpaired surrogates with `U+FFFD REPLACEMENT CHARACTER`.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide a large enough allocation.
+    pub fn from_utf16_lossy(v: &[u16]) -> Self
+    where
+        Alloc: Default,
+    {
+        Self::try_from_utf16_lossy(v).unwrap()
+    }
+    /// Decodes UTF-16 into a string, replacing unpaired surrogates with `U+FFFD REPLACEMENT CHARACTER`.
+    ///
+    /// # Errors
+    /// Returns an [`AllocationError`] if the allocator failed to provide a large enough allocation.
+    pub fn try_from_utf16_lossy(v: &[u16]) -> Result<Self, AllocationError>
+    where
+        Alloc: Default,
+    {
+        let mut this = Self::try_with_capacity(v.len()).map_err(|_| AllocationError())?;
+        this.try_extend(
+            char::decode_utf16(v.iter().copied()).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)),
+        )?;
+        Ok(this)
+    }
+    /// Returns the string's underlying bytes.
+    pub fn into_bytes(self) -> Vec<u8, Alloc> {
+        self.inner
+    }
+    /// Returns a mutable reference to the string's underlying bytes.
+    ///
+    /// # Safety
+    /// The bytes must still be valid UTF-8 once the borrow ends.
+    #[rustversion::attr(since(1.86), const)]
+    pub unsafe fn as_mut_vec(&mut self) -> &mut Vec<u8, Alloc> {
+        &mut self.inner
+    }
+    /// Converts the string into a [`BoxedStr`], without reallocating.
+    ///
+    /// Use [`Self::shrink_to_fit`] beforehand to release the excess capacity.
+    pub fn into_boxed_str(self) -> BoxedStr<Alloc> {
+        self.into()
+    }
+    /// Consumes the string, returning a mutable reference to its contents.
+    ///
+    /// Neither the contents nor the allocation (or the allocator) will ever be freed.
+    pub fn leak<'a>(self) -> &'a mut str
+    where
+        Alloc: 'a,
+    {
+        unsafe { core::str::from_utf8_unchecked_mut(self.inner.leak()) }
+    }
+    /// The total capacity of the string, in bytes.
+    pub const fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+    /// Ensures that `additional` more bytes can be pushed on `self` without reallocating.
+    ///
+    /// # Panics
+    /// If the allocator failed to provide an appropriate allocation.
+    pub fn reserve(&mut self, additional: usize) {
+        self.inner.reserve(additional)
+    }
+    /// Ensures that `additional` more bytes can be pushed on `self` without reallocating.
+    ///
+    /// # Errors
+    /// Returns an [`AllocationError`] if the allocator failed to provide an appropriate allocation,
+    /// in which case `self` is left untouched.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), AllocationError> {
+        self.inner.try_reserve(additional).map(|_| ())
+    }
+    /// Shrinks the string's capacity as close to its length as the allocator allows.
+    ///
+    /// # Panics
+    /// If the allocator failed to reallocate the string.
+    pub fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit()
+    }
+    /// Shrinks the string's capacity as close to its length as the allocator allows.
+    ///
+    /// # Errors
+    /// If the allocator failed to reallocate the string, in which case `self` is left untouched.
+    pub fn try_shrink_to_fit(&mut self) -> Result<(), AllocationError> {
+        self.inner.try_shrink_to_fit()
+    }
+    /// Shrinks the string's capacity to `max(self.len(), min_capacity)`.
+    ///
+    /// # Panics
+    /// If the allocator failed to reallocate the string.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.inner.shrink_to(min_capacity)
+    }
+    /// Shrinks the string's capacity to `max(self.len(), min_capacity)`.
+    ///
+    /// # Errors
+    /// If the allocator failed to reallocate the string, in which case `self` is left untouched.
+    pub fn try_shrink_to(&mut self, min_capacity: usize) -> Result<(), AllocationError> {
+        self.inner.try_shrink_to(min_capacity)
+    }
+    /// Appends `c` to the end of the string.
+    ///
+    /// # Panics
+    /// If the string needed to grow, and the allocator failed to provide a new allocation.
+    pub fn push(&mut self, c: char) {
+        self.try_push(c).unwrap()
+    }
+    /// Appends `c` to the end of the string.
+    ///
+    /// # Errors
+    /// If the string needed to grow, and the allocator failed to provide a new allocation.
+    pub fn try_push(&mut self, c: char) -> Result<(), AllocationError> {
+        self.try_push_str(c.encode_utf8(&mut [0; 4]))
+    }
+    /// Appends `s` to the end of the string.
+    ///
+    /// # Panics
+    /// If the string needed to grow, and the allocator failed to provide a new allocation.
+    pub fn push_str(&mut self, s: &str) {
+        self.try_push_str(s).unwrap()
+    }
+    /// Appends `s` to the end of the string.
+    ///
+    /// # Errors
+    /// If the string needed to grow, and the allocator failed to provide a new allocation.
+    pub fn try_push_str(&mut self, s: &str) -> Result<(), AllocationError> {
+        self.try_concat_str(s)
+    }
+    /// Appends the characters of `iter` to the end of the string.
+    ///
+    /// This is the fallible counterpart of [`Extend::extend`].
+    ///
+    /// # Errors
+    /// If the string needed to grow, and the allocator failed to provide a new allocation.
+    /// The characters pushed before that happened are kept.
+    pub fn try_extend<I: IntoIterator<Item = char>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), AllocationError> {
+        let iter = iter.into_iter();
+        self.try_reserve(iter.size_hint().0)?;
+        for c in iter {
+            self.try_push(c)?;
+        }
+        Ok(())
+    }
+    /// Removes the last character of the string, returning it.
+    pub fn pop(&mut self) -> Option<char> {
+        let c = self.chars().next_back()?;
+        let len = self.len().wrapping_sub(c.len_utf8());
+        // SAFETY: `len` is the start of the last character, and bytes don't need dropping.
+        unsafe { self.inner.set_len(len) };
+        Some(c)
+    }
+    /// Removes the character starting at byte `index`, returning it.
+    ///
+    /// # Panics
+    /// If `index` isn't the start of a character of the string.
+    pub fn remove(&mut self, index: usize) -> char {
+        let Some(c) = self.get(index..).and_then(|s| s.chars().next()) else {
+            panic!("cannot remove a char at byte {index}: it is not the start of a char")
+        };
+        self.inner.drain(index..index.wrapping_add(c.len_utf8()));
+        c
+    }
+    /// Shortens the string to `new_len` bytes.
+    ///
+    /// Does nothing if `new_len >= self.len()`.
+    ///
+    /// # Panics
+    /// If `new_len` isn't on a character boundary.
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len < self.len() {
+            assert!(
+                self.is_char_boundary(new_len),
+                "new_len (is {new_len}) should be on a char boundary"
+            );
+            self.inner.truncate(new_len)
+        }
+    }
+    /// Removes the string's contents, keeping its capacity.
+    pub fn clear(&mut self) {
+        self.inner.clear()
+    }
+    /// Inserts `c` at byte `index`.
+    ///
+    /// # Panics
+    /// If `index` isn't on a character boundary, or if the string needed to grow,
+    /// and the allocator failed to provide a new allocation.
+    pub fn insert(&mut self, index: usize, c: char) {
+        self.try_insert(index, c).unwrap()
+    }
+    /// Inserts `c` at byte `index`.
+    ///
+    /// # Panics
+    /// If `index` isn't on a character boundary.
+    ///
+    /// # Errors
+    /// If the string needed to grow, and the allocator failed to provide a new allocation,
+    /// in which case `self` is left untouched.
+    pub fn try_insert(&mut self, index: usize, c: char) -> Result<(), AllocationError> {
+        self.try_insert_str(index, c.encode_utf8(&mut [0; 4]))
+    }
+    /// Inserts `s` at byte `index`.
+    ///
+    /// # Panics
+    /// If `index` isn't on a character boundary, or if the string needed to grow,
+    /// and the allocator failed to provide a new allocation.
+    pub fn insert_str(&mut self, index: usize, s: &str) {
+        self.try_insert_str(index, s).unwrap()
+    }
+    /// Inserts `s` at byte `index`.
+    ///
+    /// # Panics
+    /// If `index` isn't on a character boundary.
+    ///
+    /// # Errors
+    /// If the string needed to grow, and the allocator failed to provide a new allocation,
+    /// in which case `self` is left untouched.
+    pub fn try_insert_str(&mut self, index: usize, s: &str) -> Result<(), AllocationError> {
+        assert!(
+            self.is_char_boundary(index),
+            "index (is {index}) should be on a char boundary"
+        );
+        self.inner.try_copy_extend(s.as_bytes())?;
+        if let Some(moved) = self.inner.get_mut(index..) {
+            moved.rotate_right(s.len());
+        }
+        Ok(())
+    }
+    /// Retains only the characters for which `f` returns `true`.
+    pub fn retain<F: FnMut(char) -> bool>(&mut self, mut f: F) {
+        /// Keeps the string valid UTF-8 even if `f` panics, by closing the gap between the `write` first bytes
+        /// and the `read..len` unvisited ones.
+        struct Compactor<'a, Alloc: IAlloc> {
+            inner: &'a mut Vec<u8, Alloc>,
+            read: usize,
+            write: usize,
+            len: usize,
+        }
+        impl<Alloc: IAlloc> Drop for Compactor<'_, Alloc> {
+            fn drop(&mut self) {
+                self.inner
+                    .as_slice_mut()
+                    .copy_within(self.read..self.len, self.write);
+                let len = self.write.wrapping_add(self.len.wrapping_sub(self.read));
+                // SAFETY: the `len` first bytes are the kept characters, followed by the unvisited ones.
+                unsafe { self.inner.set_len(len) }
+            }
+        }
+        let len = self.len();
+        let mut compactor = Compactor {
+            inner: &mut self.inner,
+            read: 0,
+            write: 0,
+            len,
+        };
+        while compactor.read < len {
+            // SAFETY: `read` is always at the start of a character that hasn't been moved yet.
+            let rest = unsafe {
+                core::str::from_utf8_unchecked(
+                    compactor
+                        .inner
+                        .as_slice()
+                        .get(compactor.read..)
+                        .unwrap_or_default(),
+                )
+            };
+            let Some(c) = rest.chars().next() else {
+                break;
+            };
+            let next = compactor.read.wrapping_add(c.len_utf8());
+            if f(c) {
+                compactor
+                    .inner
+                    .as_slice_mut()
+                    .copy_within(compactor.read..next, compactor.write);
+                compactor.write = compactor.write.wrapping_add(c.len_utf8());
+            }
+            compactor.read = next;
+        }
+    }
+    /// Returns an iterator that removes the characters in the `range` of bytes and yields them.
+    ///
+    /// The range is removed when the [`Drain`] is dropped, even if it wasn't fully consumed.
+    ///
+    /// # Panics
+    /// If the range has a negative size, exceeds `self.len()`, or if its ends aren't on character boundaries.
+    pub fn drain<R: core::ops::RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, Alloc> {
+        let (from, to) = super::vec::bounds(&range, self.len());
+        assert!(
+            self.is_char_boundary(from) && self.is_char_boundary(to),
+            "range ({from}..{to}) should be on char boundaries"
+        );
+        // Like `std`'s, the `Drain` keeps a raw pointer to the string, as a `&mut` would alias `chars`.
+        let string = self as *mut Self;
+        // SAFETY: the drained range is only removed from the string when the `Drain` is dropped,
+        // and the `Drain` borrows the string mutably in the meantime.
+        let chars = unsafe { self.as_str().get_unchecked(from..to) }.chars();
+        Drain {
+            string,
+            from,
+            to,
+            chars,
+            marker: core::marker::PhantomData,
+        }
+    }
+    /// Replaces the `range` of bytes with `replace_with`.
+    ///
+    /// # Panics
+    /// If the range has a negative size, exceeds `self.len()`, or if its ends aren't on character boundaries,
+    /// or if the string needed to grow, and the allocator failed to provide a new allocation.
+    pub fn replace_range<R: core::ops::RangeBounds<usize>>(
+        &mut self,
+        range: R,
+        replace_with: &str,
+    ) {
+        self.try_replace_range(range, replace_with).unwrap()
+    }
+    /// Replaces the `range` of bytes with `replace_with`.
+    ///
+    /// # Panics
+    /// If the range has a negative size, exceeds `self.len()`, or if its ends aren't on character boundaries.
+    ///
+    /// # Errors
+    /// If the string needed to grow, and the allocator failed to provide a new allocation,
+    /// in which case `self` is left untouched.
+    pub fn try_replace_range<R: core::ops::RangeBounds<usize>>(
+        &mut self,
+        range: R,
+        replace_with: &str,
+    ) -> Result<(), AllocationError> {
+        let (from, to) = super::vec::bounds(&range, self.len());
+        assert!(
+            self.is_char_boundary(from) && self.is_char_boundary(to),
+            "range ({from}..{to}) should be on char boundaries"
+        );
+        // Reserving beforehand ensures the splice won't need to allocate.
+        self.inner.try_reserve(replace_with.len())?;
+        self.inner
+            .splice(from..to, replace_with.bytes())
+            .for_each(drop);
+        Ok(())
+    }
+    /// Splits the string in two at byte `at`, returning the bytes from `at` onward in a new string
+    /// that uses a clone of `self`'s allocator.
+    ///
+    /// # Panics
+    /// If `at` isn't on a character boundary, or if the allocator failed to provide an allocation for the new string.
+    pub fn split_off(&mut self, at: usize) -> Self
+    where
+        Alloc: Clone,
+    {
+        self.try_split_off(at).unwrap()
+    }
+    /// Splits the string in two at byte `at`, returning the bytes from `at` onward in a new string
+    /// that uses a clone of `self`'s allocator.
+    ///
+    /// # Panics
+    /// If `at` isn't on a character boundary.
+    ///
+    /// # Errors
+    /// If the allocator failed to provide an allocation for the new string, in which case `self` is left untouched.
+    pub fn try_split_off(&mut self, at: usize) -> Result<Self, AllocationError>
+    where
+        Alloc: Clone,
+    {
+        assert!(
+            self.is_char_boundary(at),
+            "at (is {at}) should be on a char boundary"
+        );
+        self.inner.try_split_off(at).map(|inner| Self { inner })
+    }
+    /// Appends a copy of the `range` of bytes to the end of the string.
+    ///
+    /// # Panics
+    /// If the range has a negative size, exceeds `self.len()`, or if its ends aren't on character boundaries,
+    /// or if the string needed to grow, and the allocator failed to provide a new allocation.
+    pub fn extend_from_within<R: core::ops::RangeBounds<usize>>(&mut self, range: R) {
+        self.try_extend_from_within(range).unwrap()
+    }
+    /// Appends a copy of the `range` of bytes to the end of the string.
+    ///
+    /// # Panics
+    /// If the range has a negative size, exceeds `self.len()`, or if its ends aren't on character boundaries.
+    ///
+    /// # Errors
+    /// If the string needed to grow, and the allocator failed to provide a new allocation,
+    /// in which case `self` is left untouched.
+    pub fn try_extend_from_within<R: core::ops::RangeBounds<usize>>(
+        &mut self,
+        range: R,
+    ) -> Result<(), AllocationError> {
+        let (from, to) = super::vec::bounds(&range, self.len());
+        assert!(
+            self.is_char_boundary(from) && self.is_char_boundary(to),
+            "range ({from}..{to}) should be on char boundaries"
+        );
+        self.inner.try_extend_from_within(from..to)
+    }
+}
+
+/// An iterator that removes a range of characters from a [`String`].
+///
+/// Dropping the `Drain` removes the whole range, even if it wasn't fully consumed.
+pub struct Drain<'a, Alloc: IAlloc + 'a> {
+    string: *mut String<Alloc>,
+    from: usize,
+    to: usize,
+    chars: core::str::Chars<'a>,
+    marker: core::marker::PhantomData<&'a mut String<Alloc>>,
+}
+// SAFETY: `Drain` acts as a `&mut String<Alloc>`.
+unsafe impl<Alloc: IAlloc> Send for Drain<'_, Alloc> where String<Alloc>: Send {}
+// SAFETY: `Drain` acts as a `&mut String<Alloc>`.
+unsafe impl<Alloc: IAlloc> Sync for Drain<'_, Alloc> where String<Alloc>: Sync {}
+impl<'a, Alloc: IAlloc + 'a> Drain<'a, Alloc> {
+    /// Returns the characters that haven't been yielded yet.
+    pub fn as_str(&self) -> &str {
+        self.chars.as_str()
+    }
+}
+impl<'a, Alloc: IAlloc + 'a> Iterator for Drain<'a, Alloc> {
+    type Item = char;
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chars.size_hint()
+    }
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chars.next()
+    }
+}
+impl<'a, Alloc: IAlloc + 'a> DoubleEndedIterator for Drain<'a, Alloc> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.chars.next_back()
+    }
+}
+impl<'a, Alloc: IAlloc + 'a> core::iter::FusedIterator for Drain<'a, Alloc> {}
+impl<'a, Alloc: IAlloc + 'a> Drop for Drain<'a, Alloc> {
+    fn drop(&mut self) {
+        // SAFETY: `chars` is never used again, and `string` was borrowed mutably for `'a`.
+        unsafe { (*self.string).inner.drain(self.from..self.to) };
+    }
+}
+
+/// The error returned by [`String::from_utf8`].
+pub struct FromUtf8Error<Alloc: IAlloc = super::DefaultAllocator> {
+    bytes: Vec<u8, Alloc>,
+    error: core::str::Utf8Error,
+}
+impl<Alloc: IAlloc> FromUtf8Error<Alloc> {
+    /// Returns the bytes that failed to convert.
+    pub const fn as_bytes(&self) -> &[u8] {
+        self.bytes.as_slice()
+    }
+    /// Returns the bytes that failed to convert.
+    pub fn into_bytes(self) -> Vec<u8, Alloc> {
+        self.bytes
+    }
+    /// Returns details about the conversion failure.
+    pub const fn utf8_error(&self) -> core::str::Utf8Error {
+        self.error
+    }
+}
+impl<Alloc: IAlloc> core::fmt::Debug for FromUtf8Error<Alloc> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FromUtf8Error")
+            .field("bytes", &self.bytes)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+impl<Alloc: IAlloc> core::fmt::Display for FromUtf8Error<Alloc> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Display::fmt(&self.error, f)
+    }
+}
+#[cfg(feature = "std")]
+impl<Alloc: IAlloc> std::error::Error for FromUtf8Error<Alloc> {}
+
+/// The error returned by [`String::from_utf16`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FromUtf16Error(());
+impl core::fmt::Display for FromUtf16Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("invalid utf-16: lone surrogate found")
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for FromUtf16Error {}
+
+impl<Alloc: IAlloc> Extend<char> for String<Alloc> {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        self.try_extend(iter).unwrap()
+    }
+}
+impl<'a, Alloc: IAlloc> Extend<&'a char> for String<Alloc> {
+    fn extend<I: IntoIterator<Item = &'a char>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+impl<'a, Alloc: IAlloc> Extend<&'a str> for String<Alloc> {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|s| self.push_str(s))
+    }
+}
+impl<Alloc: IAlloc + Default> FromIterator<char> for String<Alloc> {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut this = Self::default();
+        this.extend(iter);
+        this
+    }
+}
+impl<'a, Alloc: IAlloc + Default> FromIterator<&'a char> for String<Alloc> {
+    fn from_iter<I: IntoIterator<Item = &'a char>>(iter: I) -> Self {
+        let mut this = Self::default();
+        this.extend(iter);
+        this
+    }
+}
+impl<'a, Alloc: IAlloc + Default> FromIterator<&'a str> for String<Alloc> {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut this = Self::default();
+        this.extend(iter);
+        this
+    }
+}
+impl<Alloc: IAlloc + Default> From<char> for String<Alloc> {
+    fn from(value: char) -> Self {
+        Self::from(&*value.encode_utf8(&mut [0; 4]))
+    }
 }
 impl<Alloc: IAlloc + Default> Default for String<Alloc> {
     fn default() -> Self {
@@ -88,6 +723,11 @@ impl<Alloc: IAlloc> core::ops::Deref for String<Alloc> {
     }
 }
 
+impl<Alloc: IAlloc> core::borrow::Borrow<str> for String<Alloc> {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
 impl<Alloc: IAlloc> core::convert::AsRef<str> for String<Alloc> {
     fn as_ref(&self) -> &str {
         self.as_str()
@@ -171,6 +811,11 @@ impl<Alloc: IAlloc> ArcStr<Alloc> {
         ArcSlice::is_unique(&this.inner)
     }
 }
+impl<Alloc: IAlloc> core::borrow::Borrow<str> for ArcStr<Alloc> {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
 impl<Alloc: IAlloc> AsRef<str> for ArcStr<Alloc> {
     fn as_ref(&self) -> &str {
         self.as_str()
@@ -288,6 +933,11 @@ impl<Alloc: IAlloc> BoxedStr<Alloc> {
         unsafe { core::str::from_utf8_unchecked_mut(self.inner.as_slice_mut()) }
     }
 }
+impl<Alloc: IAlloc> core::borrow::Borrow<str> for BoxedStr<Alloc> {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
 impl<Alloc: IAlloc> AsRef<str> for BoxedStr<Alloc> {
     fn as_ref(&self) -> &str {
         self.as_str()
@@ -346,10 +996,135 @@ impl<Alloc: IAlloc> core::fmt::Display for BoxedStr<Alloc> {
     }
 }
 
-impl core::fmt::Write for String {
+impl<Alloc: IAlloc> core::fmt::Write for String<Alloc> {
     fn write_str(&mut self, s: &str) -> core::fmt::Result {
         self.try_concat(s).map_err(|_| core::fmt::Error)
     }
+    fn write_char(&mut self, c: char) -> core::fmt::Result {
+        self.try_push(c).map_err(|_| core::fmt::Error)
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn std_parity() {
+    use core::fmt::Write;
+    let mut new: String = String::with_capacity(4);
+    let mut std = std::string::String::with_capacity(4);
+    let check = |new: &String, std: &std::string::String| assert_eq!(new.as_str(), std.as_str());
+    for s in ["héllo", " ", "wörld", "!", "日本語"] {
+        new.push_str(s);
+        std.push_str(s);
+    }
+    new.push('ß');
+    std.push('ß');
+    new.insert(1, 'ü');
+    std.insert(1, 'ü');
+    new.insert_str(0, "¡¿");
+    std.insert_str(0, "¡¿");
+    check(&new, &std);
+    assert_eq!(new.remove(2), std.remove(2));
+    assert_eq!(new.pop(), std.pop());
+    new.retain(|c| c != 'o' && c != '語');
+    std.retain(|c| c != 'o' && c != '語');
+    check(&new, &std);
+    let drained: std::string::String = new.drain(2..5).collect();
+    assert_eq!(drained, std.drain(2..5).collect::<std::string::String>());
+    let mut partial = new.drain(4..);
+    assert_eq!(partial.next_back(), Some('本'));
+    drop(partial);
+    std.truncate(4);
+    check(&new, &std);
+    new.replace_range(2..4, "ZZZ");
+    std.replace_range(2..4, "ZZZ");
+    new.extend_from_within(..2);
+    std.extend_from_within(..2);
+    new.extend(['a', 'b']);
+    std.extend(['a', 'b']);
+    new.extend(["cd", "ef"]);
+    std.extend(["cd", "ef"]);
+    write!(new, "{}", 42).unwrap();
+    write!(std, "{}", 42).unwrap();
+    check(&new, &std);
+    let new_tail = new.split_off(4);
+    let std_tail = std.split_off(4);
+    check(&new, &std);
+    check(&new_tail, &std_tail);
+    new.truncate(2);
+    std.truncate(2);
+    new.shrink_to_fit();
+    assert_eq!(new.capacity(), new.len());
+    check(&new, &std);
+    new.clear();
+    assert!(new.is_empty());
+
+    let invalid = b"ab\xF0\x90\x80cd\xFFe\xE2";
+    let lossy: String = String::from_utf8_lossy(invalid);
+    assert_eq!(lossy, std::string::String::from_utf8_lossy(invalid));
+    let error = String::<crate::alloc::DefaultAllocator>::from_utf8(Vec::from(invalid.as_slice()))
+        .unwrap_err();
+    assert_eq!(error.utf8_error().valid_up_to(), 2);
+    assert_eq!(error.into_bytes().as_slice(), invalid);
+    let utf16: std::vec::Vec<u16> = "𝄞music".encode_utf16().collect();
+    assert_eq!(
+        String::<crate::alloc::DefaultAllocator>::from_utf16(&utf16).unwrap(),
+        "𝄞music"
+    );
+    let lone = [0xD834, 0x006d];
+    assert!(String::<crate::alloc::DefaultAllocator>::from_utf16(&lone).is_err());
+    assert_eq!(
+        String::<crate::alloc::DefaultAllocator>::from_utf16_lossy(&lone),
+        std::string::String::from_utf16_lossy(&lone)
+    );
+    let collected: String = "tschüß".chars().rev().collect();
+    assert_eq!(collected.into_bytes().as_slice(), "ßühcst".as_bytes());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn panicking_retain() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    let mut string: String = String::from("aébcdé");
+    assert!(catch_unwind(AssertUnwindSafe(|| string.retain(|c| {
+        assert_ne!(c, 'c');
+        c != 'é'
+    })))
+    .is_err());
+    // The characters `retain` didn't get to are kept.
+    assert_eq!(string.as_str(), "abcdé");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn boundary_panics() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    // 'é' spans bytes 1 to 3.
+    let mut string: String = String::from("aé");
+    let mut panics = |f: &dyn Fn(&mut String)| {
+        let panicked = catch_unwind(AssertUnwindSafe(|| f(&mut string))).is_err();
+        assert_eq!(string.as_str(), "aé");
+        panicked
+    };
+    assert!(panics(&|s| s.insert(2, 'x')));
+    assert!(panics(&|s| s.insert(4, 'x')));
+    assert!(panics(&|s| s.insert_str(2, "x")));
+    assert!(panics(&|s| {
+        s.remove(2);
+    }));
+    assert!(panics(&|s| {
+        s.remove(3);
+    }));
+    assert!(panics(&|s| {
+        s.drain(2..);
+    }));
+    assert!(panics(&|s| {
+        s.drain(..2);
+    }));
+    assert!(panics(&|s| {
+        s.drain(1..4);
+    }));
+    assert!(panics(&|s| s.truncate(2)));
+    assert!(!panics(&|s| s.truncate(4)));
 }
 
 #[cfg(feature = "std")]
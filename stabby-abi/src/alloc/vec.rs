@@ -47,11 +47,14 @@ pub struct Vec<T, Alloc: IAlloc = super::DefaultAllocator> {
 }
 
 pub(crate) const fn ptr_diff<T>(lhs: NonNull<T>, rhs: NonNull<T>) -> usize {
-    let diff = if core::mem::size_of::<T>() == 0 {
-        unsafe { lhs.as_ptr().cast::<u8>().offset_from(rhs.as_ptr().cast()) }
-    } else {
-        unsafe { lhs.as_ptr().offset_from(rhs.as_ptr()) }
-    };
+    if core::mem::size_of::<T>() == 0 {
+        // ZST vectors' capacity is the `usize::MAX` address, which may lie more than `isize::MAX` bytes after their start.
+        return unsafe {
+            core::mem::transmute::<NonNull<T>, usize>(lhs)
+                .wrapping_sub(core::mem::transmute::<NonNull<T>, usize>(rhs))
+        };
+    }
+    let diff = unsafe { lhs.as_ptr().offset_from(rhs.as_ptr()) };
     debug_assert!(diff >= 0);
     diff as usize
 }
@@ -178,10 +181,11 @@ impl<T, Alloc: IAlloc> Vec<T, Alloc> {
     pub const fn remaining_capacity(&self) -> usize {
         ptr_diff(self.inner.capacity, self.inner.end)
     }
-    const FIRST_CAPACITY: usize = match 1024 / core::mem::size_of::<T>() {
-        0 => 1,
-        v @ 1..=8 => v,
-        _ => 8,
+    // ZSTs never grow, but `checked_div` keeps this from failing to evaluate for them.
+    const FIRST_CAPACITY: usize = match 1024usize.checked_div(core::mem::size_of::<T>()) {
+        None | Some(0) => 1,
+        Some(v @ 1..=8) => v,
+        Some(_) => 8,
     };
     fn grow(&mut self) {
         self.try_grow().unwrap();
@@ -451,6 +455,584 @@ impl<T, Alloc: IAlloc> Vec<T, Alloc> {
     pub fn allocator_mut(&mut self) -> &mut Alloc {
         &mut self.inner.alloc
     }
+    /// Removes all elements from the vector, keeping its capacity.
+    pub fn clear(&mut self) {
+        self.truncate(0)
+    }
+    /// Inserts `value` at `index`, shifting all elements after it to the right.
+    ///
+    /// # Panics
+    /// If `index > self.len()`, or if the vector tried to grow due to
+    /// being full, and the allocator failed to provide a new allocation.
+    pub fn insert(&mut self, index: usize, value: T) {
+        let len = self.len();
+        assert!(
+            index <= len,
+            "insertion index (is {index}) should be <= len (is {len})"
+        );
+        if self.try_insert(index, value).is_err() {
+            panic!("{}", AllocationError())
+        }
+    }
+    /// Inserts `value` at `index`, shifting all elements after it to the right.
+    ///
+    /// # Errors
+    /// This function gives back the `value` if `index > self.len()`, or if the vector tried to grow due to
+    /// being full, and the allocator failed to provide a new allocation.
+    ///
+    /// `self` is still valid should that happen.
+    pub fn try_insert(&mut self, index: usize, value: T) -> Result<(), T> {
+        let len = self.len();
+        if index > len || (self.inner.end == self.inner.capacity && self.try_grow().is_err()) {
+            return Err(value);
+        }
+        unsafe {
+            let slot = self.inner.start.as_ptr().add(index);
+            core::ptr::copy(slot, slot.add(1), len.wrapping_sub(index));
+            slot.write(value);
+            self.set_len(len.wrapping_add(1));
+        }
+        Ok(())
+    }
+    /// Retains only the elements for which `f` returns `true`, preserving their order.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        self.retain_mut(|value| f(value))
+    }
+    /// Retains only the elements for which `f` returns `true`, preserving their order.
+    ///
+    /// Unlike [`Self::retain`], `f` may mutate the elements.
+    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, mut f: F) {
+        let len = self.len();
+        // SAFETY: the compactor restores the length once done, even if `f` panics.
+        unsafe { self.set_len(0) };
+        let mut compactor = Compactor {
+            vec: self,
+            read: 0,
+            write: 0,
+            len,
+        };
+        let start = compactor.vec.inner.start.as_ptr();
+        while compactor.read < len {
+            // SAFETY: `read < len`, and the elements in `read..len` haven't been moved nor dropped yet.
+            unsafe {
+                let current = start.add(compactor.read);
+                if f(&mut *current) {
+                    if compactor.read != compactor.write {
+                        core::ptr::copy_nonoverlapping(current, start.add(compactor.write), 1);
+                    }
+                    compactor.write = compactor.write.wrapping_add(1);
+                    compactor.read = compactor.read.wrapping_add(1);
+                } else {
+                    compactor.read = compactor.read.wrapping_add(1);
+                    core::ptr::drop_in_place(current);
+                }
+            }
+        }
+    }
+    /// Removes consecutive duplicate elements, keeping the first of each run.
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b)
+    }
+    /// Removes consecutive elements that map to the same key, keeping the first of each run.
+    pub fn dedup_by_key<K: PartialEq, F: FnMut(&mut T) -> K>(&mut self, mut key: F) {
+        self.dedup_by(|a, b| key(a) == key(b))
+    }
+    /// Removes consecutive elements for which `same_bucket(element, previous)` returns `true`,
+    /// keeping the first of each run.
+    pub fn dedup_by<F: FnMut(&mut T, &mut T) -> bool>(&mut self, mut same_bucket: F) {
+        let len = self.len();
+        if len <= 1 {
+            return;
+        }
+        // SAFETY: the compactor restores the length once done, even if `same_bucket` panics.
+        unsafe { self.set_len(0) };
+        let mut compactor = Compactor {
+            vec: self,
+            read: 1,
+            write: 1,
+            len,
+        };
+        let start = compactor.vec.inner.start.as_ptr();
+        while compactor.read < len {
+            // SAFETY: `0 < write <= read < len`, so both pointers are initialized and distinct.
+            unsafe {
+                let current = start.add(compactor.read);
+                let previous = start.add(compactor.write.wrapping_sub(1));
+                if same_bucket(&mut *current, &mut *previous) {
+                    compactor.read = compactor.read.wrapping_add(1);
+                    core::ptr::drop_in_place(current);
+                } else {
+                    if compactor.read != compactor.write {
+                        core::ptr::copy_nonoverlapping(current, start.add(compactor.write), 1);
+                    }
+                    compactor.write = compactor.write.wrapping_add(1);
+                    compactor.read = compactor.read.wrapping_add(1);
+                }
+            }
+        }
+    }
+    /// Splits the vector in two at `at`, returning the elements from `at` onward in a new vector
+    /// that uses a clone of `self`'s allocator.
+    ///
+    /// `self` keeps its capacity.
+    ///
+    /// # Panics
+    /// If `at > self.len()`, or if the allocator failed to provide an allocation for the new vector.
+    pub fn split_off(&mut self, at: usize) -> Self
+    where
+        Alloc: Clone,
+    {
+        self.try_split_off(at).unwrap()
+    }
+    /// Splits the vector in two at `at`, returning the elements from `at` onward in a new vector
+    /// that uses a clone of `self`'s allocator.
+    ///
+    /// `self` keeps its capacity.
+    ///
+    /// # Panics
+    /// If `at > self.len()`.
+    ///
+    /// # Errors
+    /// Returns an [`AllocationError`] if the allocator failed to provide an allocation for the new vector,
+    /// in which case `self` is left untouched.
+    pub fn try_split_off(&mut self, at: usize) -> Result<Self, AllocationError>
+    where
+        Alloc: Clone,
+    {
+        let len = self.len();
+        assert!(
+            at <= len,
+            "`at` split index (is {at}) should be <= len (is {len})"
+        );
+        let other_len = len.wrapping_sub(at);
+        let mut other = Self::try_with_capacity_in(other_len, self.inner.alloc.clone())
+            .map_err(|_| AllocationError())?;
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                self.inner.start.as_ptr().add(at),
+                other.inner.start.as_ptr(),
+                other_len,
+            );
+            self.set_len(at);
+            other.set_len(other_len);
+        }
+        Ok(other)
+    }
+    /// Moves all of `other`'s elements to the end of `self`, leaving `other` empty.
+    ///
+    /// # Panics
+    /// If extending required an allocation that failed.
+    pub fn append(&mut self, other: &mut Self) {
+        self.try_append(other).unwrap()
+    }
+    /// Moves all of `other`'s elements to the end of `self`, leaving `other` empty.
+    ///
+    /// # Errors
+    /// If extending required an allocation that failed, in which case both vectors are left untouched.
+    pub fn try_append(&mut self, other: &mut Self) -> Result<(), AllocationError> {
+        let other_len = other.len();
+        self.try_reserve(other_len)?;
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                other.inner.start.as_ptr(),
+                self.inner.end.as_ptr(),
+                other_len,
+            );
+            other.set_len(0);
+            self.set_len(self.len().wrapping_add(other_len));
+        }
+        Ok(())
+    }
+    /// Resizes the vector to `new_len`, either truncating it or filling it with clones of `value`.
+    ///
+    /// # Panics
+    /// If extending required an allocation that failed.
+    pub fn resize(&mut self, new_len: usize, value: T)
+    where
+        T: Clone,
+    {
+        self.try_resize(new_len, value).unwrap()
+    }
+    /// Resizes the vector to `new_len`, either truncating it or filling it with clones of `value`.
+    ///
+    /// # Errors
+    /// If extending required an allocation that failed, in which case `self` is left untouched.
+    pub fn try_resize(&mut self, new_len: usize, value: T) -> Result<(), AllocationError>
+    where
+        T: Clone,
+    {
+        let len = self.len();
+        if new_len <= len {
+            self.truncate(new_len);
+            return Ok(());
+        }
+        self.try_reserve(new_len.wrapping_sub(len))?;
+        for _ in len.wrapping_add(1)..new_len {
+            unsafe { self.push_unchecked(value.clone()) }
+        }
+        unsafe { self.push_unchecked(value) }
+        Ok(())
+    }
+    /// Resizes the vector to `new_len`, either truncating it or filling it with the values returned by `f`.
+    ///
+    /// # Panics
+    /// If extending required an allocation that failed.
+    pub fn resize_with<F: FnMut() -> T>(&mut self, new_len: usize, f: F) {
+        self.try_resize_with(new_len, f).unwrap()
+    }
+    /// Resizes the vector to `new_len`, either truncating it or filling it with the values returned by `f`.
+    ///
+    /// # Errors
+    /// If extending required an allocation that failed, in which case `self` is left untouched.
+    pub fn try_resize_with<F: FnMut() -> T>(
+        &mut self,
+        new_len: usize,
+        mut f: F,
+    ) -> Result<(), AllocationError> {
+        let len = self.len();
+        if new_len <= len {
+            self.truncate(new_len);
+            return Ok(());
+        }
+        self.try_reserve(new_len.wrapping_sub(len))?;
+        for _ in len..new_len {
+            unsafe { self.push_unchecked(f()) }
+        }
+        Ok(())
+    }
+    /// Appends clones of `slice`'s elements to `self`.
+    ///
+    /// Prefer [`Self::copy_extend`] for [`Copy`] types.
+    ///
+    /// # Panics
+    /// If extending required an allocation that failed.
+    pub fn extend_from_slice(&mut self, slice: &[T])
+    where
+        T: Clone,
+    {
+        self.try_extend_from_slice(slice).unwrap()
+    }
+    /// Appends clones of `slice`'s elements to `self`.
+    ///
+    /// Prefer [`Self::try_copy_extend`] for [`Copy`] types.
+    ///
+    /// # Errors
+    /// If extending required an allocation that failed, in which case `self` is left untouched.
+    pub fn try_extend_from_slice(&mut self, slice: &[T]) -> Result<(), AllocationError>
+    where
+        T: Clone,
+    {
+        self.try_reserve(slice.len())?;
+        for value in slice {
+            unsafe { self.push_unchecked(value.clone()) }
+        }
+        Ok(())
+    }
+    /// Appends clones of the elements in `range` to the end of `self`.
+    ///
+    /// # Panics
+    /// If the range has a negative size, or exceeds `self.len()`, or if extending required an allocation that failed.
+    pub fn extend_from_within<R: core::ops::RangeBounds<usize>>(&mut self, range: R)
+    where
+        T: Clone,
+    {
+        self.try_extend_from_within(range).unwrap()
+    }
+    /// Appends clones of the elements in `range` to the end of `self`.
+    ///
+    /// # Panics
+    /// If the range has a negative size, or exceeds `self.len()`.
+    ///
+    /// # Errors
+    /// If extending required an allocation that failed, in which case `self` is left untouched.
+    pub fn try_extend_from_within<R: core::ops::RangeBounds<usize>>(
+        &mut self,
+        range: R,
+    ) -> Result<(), AllocationError>
+    where
+        T: Clone,
+    {
+        let (from, to) = bounds(&range, self.len());
+        self.try_reserve(to.wrapping_sub(from))?;
+        for index in from..to {
+            // SAFETY: `index < to <= len`, and reserving beforehand ensures pushing won't move the source.
+            unsafe {
+                let value = (*self.inner.start.as_ptr().add(index)).clone();
+                self.push_unchecked(value)
+            }
+        }
+        Ok(())
+    }
+    /// Replaces the elements in `range` with the elements of `replace_with`, returning an iterator over the removed elements.
+    ///
+    /// The removal and insertion happen when the returned [`Splice`] is dropped, even if it wasn't consumed.
+    /// If it is leaked, the vector may lose and leak elements, like with [`Self::drain`].
+    ///
+    /// # Panics
+    /// This function immediately panics if the range has a negative size, or if the range exceeds `self.len()`.
+    ///
+    /// Dropping the [`Splice`] panics if inserting the new elements required an allocation that failed.
+    pub fn splice<R: core::ops::RangeBounds<usize>, I: IntoIterator<Item = T>>(
+        &mut self,
+        range: R,
+        replace_with: I,
+    ) -> Splice<'_, T, I::IntoIter, Alloc> {
+        Splice {
+            drain: self.drain(range),
+            replace_with: replace_with.into_iter(),
+        }
+    }
+    /// Replaces the elements in `range` with the elements of `replace_with`, returning an iterator over the removed elements.
+    ///
+    /// Unlike [`Self::splice`], this reserves room for the replacement elements beforehand, so that dropping the
+    /// [`Splice`] doesn't allocate as long as `replace_with` reports its length accurately.
+    ///
+    /// # Panics
+    /// This function immediately panics if the range has a negative size, or if the range exceeds `self.len()`.
+    ///
+    /// # Errors
+    /// If reserving room for the replacement elements required an allocation that failed, in which case `self` is left untouched.
+    pub fn try_splice<R: core::ops::RangeBounds<usize>, I: IntoIterator<Item = T>>(
+        &mut self,
+        range: R,
+        replace_with: I,
+    ) -> Result<Splice<'_, T, I::IntoIter, Alloc>, AllocationError>
+    where
+        I::IntoIter: ExactSizeIterator,
+    {
+        let (from, to) = bounds(&range, self.len());
+        let replace_with = replace_with.into_iter();
+        self.try_reserve(replace_with.len().saturating_sub(to.wrapping_sub(from)))?;
+        Ok(Splice {
+            drain: self.drain(from..to),
+            replace_with,
+        })
+    }
+    /// Returns an iterator that removes and yields the elements in `range` for which `filter` returns `true`.
+    ///
+    /// The elements for which `filter` returns `false` are kept in place, in their original order.
+    /// If the iterator is dropped before being fully consumed, the remaining elements are kept.
+    ///
+    /// # Panics
+    /// This function immediately panics if the range has a negative size, or if the range exceeds `self.len()`.
+    pub fn extract_if<R: core::ops::RangeBounds<usize>, F: FnMut(&mut T) -> bool>(
+        &mut self,
+        range: R,
+        filter: F,
+    ) -> ExtractIf<'_, T, F, Alloc> {
+        let original_len = self.len();
+        let (from, to) = bounds(&range, original_len);
+        // SAFETY: `ExtractIf` restores the length when dropped. Leaking it leaks the vector's elements, but is sound.
+        unsafe { self.set_len(0) };
+        ExtractIf {
+            vec: self,
+            index: from,
+            end: to,
+            deleted: 0,
+            original_len,
+            filter,
+        }
+    }
+    /// Shrinks the vector's capacity as close to its length as the allocator allows.
+    ///
+    /// # Panics
+    /// If the allocator failed to reallocate the vector.
+    pub fn shrink_to_fit(&mut self) {
+        self.try_shrink_to_fit().unwrap()
+    }
+    /// Shrinks the vector's capacity as close to its length as the allocator allows.
+    ///
+    /// # Errors
+    /// If the allocator failed to reallocate the vector, in which case `self` is left untouched.
+    pub fn try_shrink_to_fit(&mut self) -> Result<(), AllocationError> {
+        self.try_shrink_to(0)
+    }
+    /// Shrinks the vector's capacity to `max(self.len(), min_capacity)`.
+    ///
+    /// Does nothing if the capacity is already lower.
+    ///
+    /// # Panics
+    /// If the allocator failed to reallocate the vector.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.try_shrink_to(min_capacity).unwrap()
+    }
+    /// Shrinks the vector's capacity to `max(self.len(), min_capacity)`.
+    ///
+    /// Does nothing if the capacity is already lower.
+    ///
+    /// # Errors
+    /// If the allocator failed to reallocate the vector, in which case `self` is left untouched.
+    pub fn try_shrink_to(&mut self, min_capacity: usize) -> Result<(), AllocationError> {
+        let len = self.len();
+        let old_capacity = self.capacity();
+        let new_capacity = len.max(min_capacity);
+        if Self::zst_mode() || new_capacity >= old_capacity {
+            return Ok(());
+        }
+        if new_capacity == 0 {
+            unsafe { self.inner.start.free(&mut self.inner.alloc) };
+            let start = AllocPtr::dangling();
+            self.inner.start = start;
+            self.inner.end = start.ptr;
+            self.inner.capacity = start.ptr;
+            return Ok(());
+        }
+        let Some(start) = (unsafe {
+            self.inner
+                .start
+                .realloc(&mut self.inner.alloc, old_capacity, new_capacity)
+        }) else {
+            return Err(AllocationError());
+        };
+        self.inner.start = start;
+        self.inner.end = ptr_add(*start, len);
+        self.inner.capacity = ptr_add(*start, new_capacity);
+        Ok(())
+    }
+    /// Consumes the vector, returning a mutable reference to its elements.
+    ///
+    /// Neither the elements nor the allocation (or the allocator) will ever be freed.
+    pub fn leak<'a>(self) -> &'a mut [T]
+    where
+        Alloc: 'a,
+    {
+        let this = core::mem::ManuallyDrop::new(self);
+        unsafe { core::slice::from_raw_parts_mut(this.inner.start.as_ptr(), this.len()) }
+    }
+    /// Converts the vector into a [`BoxedSlice`](crate::alloc::boxed::BoxedSlice).
+    ///
+    /// Unlike `std`'s `Box<[T]>`, the boxed slice keeps track of the vector's capacity,
+    /// so this never reallocates: use [`Self::shrink_to_fit`] beforehand to release the excess capacity.
+    pub fn into_boxed_slice(self) -> crate::alloc::boxed::BoxedSlice<T, Alloc> {
+        self.into()
+    }
+    /// Returns the spare capacity of the vector, which may be initialized before calling [`Self::set_len`].
+    #[rustversion::attr(since(1.86), const)]
+    pub fn spare_capacity_mut(&mut self) -> &mut [core::mem::MaybeUninit<T>] {
+        unsafe {
+            core::slice::from_raw_parts_mut(
+                self.inner.end.as_ptr().cast(),
+                ptr_diff(self.inner.capacity, self.inner.end),
+            )
+        }
+    }
+    /// Decomposes the vector into a pointer to its elements, its length, its capacity and its allocator.
+    ///
+    /// The vector can be rebuilt using [`Self::from_raw_parts_in`].
+    pub fn into_raw_parts_with_alloc(self) -> (*mut T, usize, usize, Alloc) {
+        let this = core::mem::ManuallyDrop::new(self);
+        let alloc = unsafe { core::ptr::read(&this.inner.alloc) };
+        (
+            this.inner.start.as_ptr(),
+            this.len(),
+            this.capacity(),
+            alloc,
+        )
+    }
+    /// Rebuilds a vector from the parts returned by [`Self::into_raw_parts_with_alloc`].
+    ///
+    /// # Safety
+    /// `ptr`, `capacity` and `alloc` must have been obtained by decomposing a `Vec<T, Alloc>`,
+    /// and `length` must not exceed `capacity`, the first `length` elements being initialized.
+    pub unsafe fn from_raw_parts_in(
+        ptr: *mut T,
+        length: usize,
+        capacity: usize,
+        alloc: Alloc,
+    ) -> Self {
+        let start = AllocPtr {
+            ptr: NonNull::new_unchecked(ptr),
+            marker: core::marker::PhantomData,
+        };
+        Self {
+            inner: VecInner {
+                start,
+                end: ptr_add(start.ptr, length),
+                capacity: if Self::zst_mode() {
+                    core::mem::transmute::<usize, NonNull<T>>(usize::MAX)
+                } else {
+                    ptr_add(start.ptr, capacity)
+                },
+                alloc,
+            },
+        }
+    }
+    /// Writes `value` at the end of the vector.
+    /// # Safety
+    /// The vector must have some remaining capacity.
+    #[rustversion::attr(since(1.86), const)]
+    unsafe fn push_unchecked(&mut self, value: T) {
+        self.inner.end.as_ptr().write(value);
+        self.inner.end = ptr_add(self.inner.end, 1);
+    }
+}
+
+/// Returns the `[from, to)` indices `range` corresponds to in a collection of `len` elements.
+///
+/// # Panics
+/// If the range has a negative size, or exceeds `len`.
+pub(crate) fn bounds<R: core::ops::RangeBounds<usize>>(range: &R, len: usize) -> (usize, usize) {
+    let from = match range.start_bound() {
+        core::ops::Bound::Included(i) => *i,
+        core::ops::Bound::Excluded(i) => i.checked_add(1).expect("range start overflowed"),
+        core::ops::Bound::Unbounded => 0,
+    };
+    let to = match range.end_bound() {
+        core::ops::Bound::Included(i) => i.checked_add(1).expect("range end overflowed"),
+        core::ops::Bound::Excluded(i) => *i,
+        core::ops::Bound::Unbounded => len,
+    };
+    assert!(
+        from <= to,
+        "range start (is {from}) should be <= range end (is {to})"
+    );
+    assert!(to <= len, "range end (is {to}) should be <= len (is {len})");
+    (from, to)
+}
+
+/// Closes the gap between the `write` first elements of a vector whose length was set to 0
+/// and the `read..len` elements when dropped, setting its length accordingly.
+///
+/// This keeps the vector valid even if a user-provided closure panics while compacting it.
+struct Compactor<'a, T, Alloc: IAlloc> {
+    vec: &'a mut Vec<T, Alloc>,
+    read: usize,
+    write: usize,
+    len: usize,
+}
+impl<T, Alloc: IAlloc> Drop for Compactor<'_, T, Alloc> {
+    fn drop(&mut self) {
+        let tail = self.len.wrapping_sub(self.read);
+        unsafe {
+            let start = self.vec.inner.start.as_ptr();
+            if self.read != self.write {
+                core::ptr::copy(start.add(self.read), start.add(self.write), tail);
+            }
+            self.vec.set_len(self.write.wrapping_add(tail));
+        }
+    }
+}
+
+#[cfg(not(stabby_default_alloc = "disabled"))]
+impl<T> Vec<T> {
+    /// Decomposes the vector into a pointer to its elements, its length and its capacity.
+    ///
+    /// The vector can be rebuilt using [`Self::from_raw_parts`].
+    pub fn into_raw_parts(self) -> (*mut T, usize, usize) {
+        let (ptr, length, capacity, _) = self.into_raw_parts_with_alloc();
+        (ptr, length, capacity)
+    }
+    /// Rebuilds a vector from the parts returned by [`Self::into_raw_parts`].
+    ///
+    /// # Safety
+    /// `ptr` and `capacity` must have been obtained by decomposing a `Vec<T>`,
+    /// and `length` must not exceed `capacity`, the first `length` elements being initialized.
+    pub unsafe fn from_raw_parts(ptr: *mut T, length: usize, capacity: usize) -> Self {
+        Self::from_raw_parts_in(ptr, length, capacity, super::DefaultAllocator::new())
+    }
 }
 
 impl<T: Clone, Alloc: IAlloc + Clone> Clone for Vec<T, Alloc> {
@@ -726,8 +1308,11 @@ impl<'a, T: 'a, Alloc: IAlloc + 'a> ExactSizeIterator for Drain<'a, T, Alloc> {
         self.to.wrapping_sub(self.index)
     }
 }
-impl<'a, T: 'a, Alloc: IAlloc + 'a> Drop for Drain<'a, T, Alloc> {
-    fn drop(&mut self) {
+impl<'a, T: 'a, Alloc: IAlloc + 'a> Drain<'a, T, Alloc> {
+    /// Drops the remaining elements and moves the tail of the vector in their place.
+    ///
+    /// This leaves `self` empty, so that it no longer affects the vector.
+    fn close(&mut self) {
         let tail_length = self.original_len.wrapping_sub(self.to);
         unsafe {
             core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
@@ -741,6 +1326,115 @@ impl<'a, T: 'a, Alloc: IAlloc + 'a> Drop for Drain<'a, T, Alloc> {
             );
             self.vec.set_len(tail_length.wrapping_add(self.from));
         }
+        self.settle();
+    }
+    /// Makes `self` empty, with no tail, at the end of the vector.
+    #[rustversion::attr(since(1.86), const)]
+    fn settle(&mut self) {
+        let len = self.vec.len();
+        self.from = len;
+        self.to = len;
+        self.index = len;
+        self.original_len = len;
+    }
+}
+impl<'a, T: 'a, Alloc: IAlloc + 'a> Drop for Drain<'a, T, Alloc> {
+    fn drop(&mut self) {
+        self.close()
+    }
+}
+/// An iterator that replaces a range of a [`Vec`] with the elements of another iterator.
+///
+/// It yields the removed elements. Dropping the `Splice` will finish removing them,
+/// and then insert the replacement elements.
+///
+/// Note that leaking the `Splice` may cause its [`Vec`] to lose and leak elements,
+/// even outside the specified range.
+pub struct Splice<'a, T: 'a, I: Iterator<Item = T>, Alloc: IAlloc + 'a> {
+    drain: Drain<'a, T, Alloc>,
+    replace_with: I,
+}
+impl<'a, T: 'a, I: Iterator<Item = T>, Alloc: IAlloc + 'a> Iterator for Splice<'a, T, I, Alloc> {
+    type Item = T;
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.drain.size_hint()
+    }
+    fn next(&mut self) -> Option<Self::Item> {
+        self.drain.next()
+    }
+}
+impl<'a, T: 'a, I: Iterator<Item = T>, Alloc: IAlloc + 'a> ExactSizeIterator
+    for Splice<'a, T, I, Alloc>
+{
+    fn len(&self) -> usize {
+        self.drain.len()
+    }
+}
+impl<'a, T: 'a, I: Iterator<Item = T>, Alloc: IAlloc + 'a> Drop for Splice<'a, T, I, Alloc> {
+    fn drop(&mut self) {
+        let from = self.drain.from;
+        let tail_length = self.drain.original_len.wrapping_sub(self.drain.to);
+        self.drain.close();
+        // The replacement elements are pushed after the tail, which is then rotated back behind them.
+        let vec = &mut *self.drain.vec;
+        vec.extend(self.replace_with.by_ref());
+        if let Some(moved) = vec.get_mut(from..) {
+            moved.rotate_left(tail_length);
+        }
+        self.drain.settle();
+    }
+}
+/// An iterator that removes the elements of a [`Vec`] that match a filter.
+///
+/// Dropping it keeps the elements it hasn't visited yet.
+pub struct ExtractIf<'a, T: 'a, F: FnMut(&mut T) -> bool, Alloc: IAlloc + 'a> {
+    vec: &'a mut Vec<T, Alloc>,
+    index: usize,
+    end: usize,
+    deleted: usize,
+    original_len: usize,
+    filter: F,
+}
+impl<'a, T: 'a, F: FnMut(&mut T) -> bool, Alloc: IAlloc + 'a> Iterator
+    for ExtractIf<'a, T, F, Alloc>
+{
+    type Item = T;
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.end.wrapping_sub(self.index)))
+    }
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.vec.inner.start.as_ptr();
+        while self.index < self.end {
+            // SAFETY: `index < end <= original_len`, and the elements in `index..original_len` haven't been moved yet.
+            unsafe {
+                let current = start.add(self.index);
+                let extract = (self.filter)(&mut *current);
+                self.index = self.index.wrapping_add(1);
+                if extract {
+                    self.deleted = self.deleted.wrapping_add(1);
+                    return Some(current.read());
+                } else if self.deleted > 0 {
+                    core::ptr::copy_nonoverlapping(current, current.sub(self.deleted), 1);
+                }
+            }
+        }
+        None
+    }
+}
+impl<'a, T: 'a, F: FnMut(&mut T) -> bool, Alloc: IAlloc + 'a> Drop for ExtractIf<'a, T, F, Alloc> {
+    fn drop(&mut self) {
+        unsafe {
+            if self.deleted > 0 {
+                let current = self.vec.inner.start.as_ptr().add(self.index);
+                core::ptr::copy(
+                    current,
+                    current.sub(self.deleted),
+                    self.original_len.wrapping_sub(self.index),
+                );
+            }
+            self.vec
+                .set_len(self.original_len.wrapping_sub(self.deleted));
+        }
     }
 }
 /// A vector drain that works on both ends.
@@ -872,6 +1566,257 @@ fn try_drain() {
     assert!(new.try_drain(LEN..LEN + 1).is_none());
 }
 
+#[cfg(feature = "std")]
+#[test]
+fn std_parity() {
+    use rand::Rng;
+    const LEN: usize = 200;
+    let mut rng = rand::thread_rng();
+    let values: std::vec::Vec<std::string::String> = (0..LEN)
+        .map(|_| (rng.gen::<u8>() % 8).to_string())
+        .collect();
+    let mut std = values.clone();
+    let mut new: Vec<std::string::String> = values.iter().cloned().collect();
+    let check = |new: &Vec<std::string::String>, std: &std::vec::Vec<std::string::String>| {
+        assert_eq!(new.as_slice(), std.as_slice())
+    };
+    new.insert(3, "inserted".into());
+    std.insert(3, "inserted".into());
+    assert!(new.try_insert(new.len() + 1, "oob".into()).is_err());
+    check(&new, &std);
+    new.retain(|v| v != "3");
+    std.retain(|v| v != "3");
+    check(&new, &std);
+    new.retain_mut(|v| {
+        v.push('!');
+        v != "5!"
+    });
+    std.retain_mut(|v| {
+        v.push('!');
+        v != "5!"
+    });
+    check(&new, &std);
+    new.dedup_by_key(|v| v.clone());
+    std.dedup_by_key(|v| v.clone());
+    check(&new, &std);
+    let mut new_tail = new.split_off(new.len() / 2);
+    let mut std_tail = std.split_off(std.len() / 2);
+    check(&new, &std);
+    check(&new_tail, &std_tail);
+    new.extend_from_within(1..4);
+    std.extend_from_within(1..4);
+    new.append(&mut new_tail);
+    std.append(&mut std_tail);
+    assert!(new_tail.is_empty());
+    check(&new, &std);
+    new.resize(new.len() + 10, "resized".into());
+    std.resize(std.len() + 10, "resized".into());
+    new.resize_with(new.len() - 20, Default::default);
+    std.resize_with(std.len() - 20, Default::default);
+    check(&new, &std);
+    let replacement = ["a", "b", "c", "d", "e"].map(std::string::String::from);
+    let removed: std::vec::Vec<_> = new.splice(2..6, replacement.clone()).collect();
+    assert_eq!(
+        removed,
+        std.splice(2..6, replacement.clone())
+            .collect::<std::vec::Vec<_>>()
+    );
+    check(&new, &std);
+    new.splice(..1, replacement.iter().take(1).cloned());
+    std.splice(..1, replacement.iter().take(1).cloned());
+    new.splice(4..5, core::iter::empty());
+    std.splice(4..5, core::iter::empty());
+    check(&new, &std);
+    let mut std_extracted = std::vec::Vec::new();
+    let mut i = 0;
+    while i < std.len() {
+        if std.get(i).is_some_and(|v| v.ends_with('!')) && i >= 5 {
+            std_extracted.push(std.remove(i));
+        } else {
+            i += 1;
+        }
+    }
+    let new_extracted: std::vec::Vec<_> = new.extract_if(5.., |v| v.ends_with('!')).collect();
+    assert_eq!(new_extracted, std_extracted);
+    check(&new, &std);
+    let mut partial = new.extract_if(.., |_| true);
+    assert!(partial.next().is_some());
+    drop(partial);
+    std.remove(0);
+    check(&new, &std);
+    new.shrink_to_fit();
+    assert_eq!(new.capacity(), new.len());
+    new.spare_capacity_mut();
+    let boxed = new.clone().into_boxed_slice();
+    assert_eq!(&*boxed, std.as_slice());
+    let (ptr, length, capacity) = new.into_raw_parts();
+    let mut new = unsafe { Vec::from_raw_parts(ptr, length, capacity) };
+    check(&new, &std);
+    new.clear();
+    new.shrink_to_fit();
+    assert_eq!(new.capacity(), 0);
+    let leaked: &'static mut [u8] = Vec::<u8>::from([1u8, 2, 3].as_slice()).leak();
+    assert_eq!(leaked, &[1, 2, 3]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn zsts() {
+    static DROPS: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(0);
+    struct Zst;
+    impl Drop for Zst {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, core::sync::atomic::Ordering::Relaxed);
+        }
+    }
+    let mut vec: Vec<Zst> = Vec::new();
+    vec.extend([Zst, Zst, Zst]);
+    let capacity = vec.capacity();
+    assert_eq!(vec.spare_capacity_mut().len(), capacity.wrapping_sub(3));
+    let (ptr, length, _, alloc) = vec.into_raw_parts_with_alloc();
+    let mut vec = unsafe { Vec::from_raw_parts_in(ptr, length, usize::MAX, alloc) };
+    assert_eq!(vec.len(), 3);
+    assert_eq!(vec.capacity(), capacity);
+    vec.push(Zst);
+    assert_eq!(vec.spare_capacity_mut().len(), capacity.wrapping_sub(4));
+    assert_eq!(DROPS.load(core::sync::atomic::Ordering::Relaxed), 0);
+    drop(vec);
+    assert_eq!(DROPS.load(core::sync::atomic::Ordering::Relaxed), 4);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn panicking_closures() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    /// A value that counts its drops.
+    struct Counted<'a>(u32, &'a core::cell::Cell<usize>);
+    impl Drop for Counted<'_> {
+        fn drop(&mut self) {
+            self.1.set(self.1.get().wrapping_add(1))
+        }
+    }
+    let drops = core::cell::Cell::new(0);
+    let values = |vec: &Vec<Counted>| vec.iter().map(|v| v.0).collect::<std::vec::Vec<_>>();
+
+    // The elements the closure didn't get to are kept.
+    let mut vec: Vec<_> = (0..10).map(|i| Counted(i, &drops)).collect();
+    assert!(catch_unwind(AssertUnwindSafe(|| vec.retain(|v| {
+        assert_ne!(v.0, 5);
+        v.0 % 2 == 0
+    })))
+    .is_err());
+    assert_eq!(values(&vec), [0, 2, 4, 5, 6, 7, 8, 9]);
+    assert_eq!(drops.get(), 2);
+    drop(vec);
+    assert_eq!(drops.get(), 10);
+
+    drops.set(0);
+    let mut vec: Vec<_> = (0..10).map(|i| Counted(i / 2, &drops)).collect();
+    assert!(
+        catch_unwind(AssertUnwindSafe(|| vec.dedup_by(|current, previous| {
+            assert_ne!(current.0, 3);
+            current.0 == previous.0
+        })))
+        .is_err()
+    );
+    assert_eq!(values(&vec), [0, 1, 2, 3, 3, 4, 4]);
+    assert_eq!(drops.get(), 3);
+    drop(vec);
+    assert_eq!(drops.get(), 10);
+
+    drops.set(0);
+    let mut vec: Vec<_> = (0..10).map(|i| Counted(i, &drops)).collect();
+    let mut extracted = std::vec::Vec::new();
+    assert!(catch_unwind(AssertUnwindSafe(|| {
+        for value in vec.extract_if(1.., |v| {
+            assert_ne!(v.0, 5);
+            v.0 % 2 == 0
+        }) {
+            extracted.push(value)
+        }
+    }))
+    .is_err());
+    assert_eq!(values(&vec), [0, 1, 3, 5, 6, 7, 8, 9]);
+    assert_eq!(
+        extracted.iter().map(|v| v.0).collect::<std::vec::Vec<_>>(),
+        [2, 4]
+    );
+    assert_eq!(drops.get(), 0);
+    drop(vec);
+    drop(extracted);
+    assert_eq!(drops.get(), 10);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn splice_dropped_early() {
+    let values = || (0..10).map(|i| i.to_string());
+    let mut new: Vec<std::string::String> = values().collect();
+    let mut std: std::vec::Vec<std::string::String> = values().collect();
+    let replacement = ["a", "b"].map(std::string::String::from);
+
+    let mut splice = new.splice(2..6, replacement.clone());
+    assert_eq!(splice.next().as_deref(), Some("2"));
+    drop(splice);
+    std.splice(2..6, replacement.clone());
+    assert_eq!(new, std);
+
+    drop(new.splice(..3, values()));
+    std.splice(..3, values());
+    assert_eq!(new, std);
+
+    drop(new.try_splice(4..9, replacement.clone()).unwrap());
+    std.splice(4..9, replacement.clone());
+    assert_eq!(new, std);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn failed_try_leaves_vec_untouched() {
+    let arena = crate::alloc::allocators::Arena::new(1024).unwrap();
+    let mut vec = Vec::with_capacity_in(4, arena.allocator());
+    vec.copy_extend(&[1u32, 2, 3, 4]);
+    let mut other = Vec::with_capacity_in(1, arena.allocator());
+    other.push(5u32);
+    let mut spare = Vec::with_capacity_in(8, arena.allocator());
+    spare.push(1u32);
+    // Since the arena never reuses freed memory, this exhausts it.
+    while Vec::<u8, _>::try_with_capacity_in(1, arena.allocator()).is_ok() {}
+
+    let capacity = vec.capacity();
+    assert_eq!(capacity, 4);
+    let check = |vec: &Vec<u32, _>| {
+        assert_eq!(vec, &[1, 2, 3, 4]);
+        assert_eq!(vec.capacity(), capacity);
+    };
+    assert!(vec.try_reserve(1).is_err());
+    check(&vec);
+    assert_eq!(vec.try_push(5), Err(5));
+    check(&vec);
+    assert_eq!(vec.try_insert(0, 5), Err(5));
+    check(&vec);
+    assert!(vec.try_copy_extend(&[5]).is_err());
+    check(&vec);
+    assert!(vec.try_extend_from_slice(&[5]).is_err());
+    check(&vec);
+    assert!(vec.try_extend_from_within(..).is_err());
+    check(&vec);
+    assert!(vec.try_resize(5, 5).is_err());
+    check(&vec);
+    assert!(vec.try_resize_with(5, || 5).is_err());
+    check(&vec);
+    assert!(vec.try_append(&mut other).is_err());
+    check(&vec);
+    assert_eq!(other, [5]);
+    assert!(vec.try_split_off(1).is_err());
+    check(&vec);
+    assert!(vec.try_splice(1..2, [5, 6]).is_err());
+    check(&vec);
+    assert!(spare.try_shrink_to_fit().is_err());
+    assert_eq!(spare, [1]);
+    assert_eq!(spare.capacity(), 8);
+}
+
 pub use super::single_or_vec::SingleOrVec;
 
 #[cfg(feature = "serde")]
@@ -14,7 +14,7 @@
 
 use core::{
     ops::Deref,
-    sync::atomic::{AtomicU8, Ordering},
+    sync::atomic::{AtomicBool, AtomicPtr, AtomicU8, Ordering},
 };
 /// Used in `#[stabby::import(canaries)]`
 #[crate::stabby]
@@ -47,6 +47,30 @@ impl<F> Deref for CanariedImport<F> {
     }
 }
 
+/// The build properties that `#[stabby::export(canaries)]` encodes in its canaries.
+///
+/// It is exported alongside the canaries, so that loaders can explain which property differs when a canary is missing.
+#[crate::stabby]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CanaryDescriptor {
+    /// The version of `rustc`, followed by the first characters of its commit hash.
+    pub rustc: crate::str::Str<'static>,
+    /// The optimization level.
+    pub opt_level: crate::str::Str<'static>,
+    /// Whether or not debug symbols were enabled.
+    pub debug: bool,
+    /// The target triple.
+    pub target: crate::str::Str<'static>,
+    /// The number of jobs used for the build.
+    pub num_jobs: crate::str::Str<'static>,
+    /// The host triple.
+    pub host: crate::str::Str<'static>,
+}
+impl CanaryDescriptor {
+    /// The properties of the current build.
+    pub const LOCAL: Self = crate::canary_descriptor!();
+}
+
 /// Used in `#[stabby::import]`
 #[crate::stabby]
 pub struct CheckedImport<F> {
@@ -73,7 +97,26 @@ pub struct ReportMismatch {
 }
 impl core::fmt::Display for ReportMismatch {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        core::fmt::Debug::fmt(&self, f)
+        let Self { local, loaded } = self;
+        write!(
+            f,
+            "Report mismatch between local `{}::{}` and loaded `{}::{}`:",
+            local.module, local.name, loaded.module, loaded.name
+        )?;
+        let mut result = Ok(());
+        let mut found = false;
+        local.visit_diff(loaded, |path, kind| {
+            found = true;
+            if result.is_ok() {
+                result = write!(f, "\n  - {path}: {kind}");
+            }
+        });
+        result?;
+        if !found {
+            // The reports are structurally identical, so the mismatch must come from their IDs (which also encode the target architecture).
+            write!(f, "\n  local: {local}\n  loaded: {loaded}")?;
+        }
+        Ok(())
     }
 }
 #[cfg(feature = "std")]
@@ -142,6 +185,37 @@ impl<F> CheckedImport<F> {
         }
     }
 }
+/// Used in `#[stabby::import]` for statics.
+///
+/// The report of the imported static is checked on first access, after which the static is accessible through [`Deref`].
+pub struct CheckedStatic<T: 'static> {
+    inner: CheckedImport<&'static T>,
+}
+impl<T: Sync + 'static> CheckedStatic<T> {
+    /// Used by `#[stabby::import]` proc-macro
+    #[allow(improper_ctypes_definitions)]
+    pub const fn new(
+        checker: unsafe extern "C" fn(&crate::report::TypeReport) -> Option<&'static T>,
+        get_report: unsafe extern "C" fn() -> &'static crate::report::TypeReport,
+        local_report: &'static crate::report::TypeReport,
+    ) -> Self {
+        Self {
+            inner: CheckedImport::new(checker, get_report, local_report),
+        }
+    }
+    /// # Errors
+    /// Returns a [`ReportMismatch`] if the local and loaded reports differ.
+    pub fn as_ref(&self) -> Result<&'static T, ReportMismatch> {
+        self.inner.as_ref().copied()
+    }
+}
+impl<T: Sync + 'static> Deref for CheckedStatic<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        self.as_ref().unwrap()
+    }
+}
+
 impl<F> core::ops::Deref for CheckedImport<F> {
     type Target = F;
     fn deref(&self) -> &Self::Target {
@@ -155,3 +229,212 @@ impl<F> Drop for CheckedImport<F> {
         }
     }
 }
+
+/// An import registered by `#[stabby::import]`, so that [`imports`] can check it eagerly.
+pub struct ImportRegistration {
+    check: fn() -> Result<(), ReportMismatch>,
+    next: AtomicPtr<ImportRegistration>,
+    registered: AtomicBool,
+}
+impl ImportRegistration {
+    /// Used by `#[stabby::import]` proc-macro
+    pub const fn new(check: fn() -> Result<(), ReportMismatch>) -> Self {
+        Self {
+            check,
+            next: AtomicPtr::new(core::ptr::null_mut()),
+            registered: AtomicBool::new(false),
+        }
+    }
+    /// Checks the import, caching the result like dereferencing it would.
+    ///
+    /// # Errors
+    /// Returns a [`ReportMismatch`] if the local and loaded reports differ.
+    pub fn check(&self) -> Result<(), ReportMismatch> {
+        (self.check)()
+    }
+}
+static IMPORTS: AtomicPtr<ImportRegistration> = AtomicPtr::new(core::ptr::null_mut());
+static IMPORTS_LOCK: AtomicBool = AtomicBool::new(false);
+/// Holds the lock on the list of registered imports until dropped.
+struct ImportsGuard;
+impl ImportsGuard {
+    fn lock() -> Self {
+        while IMPORTS_LOCK
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        Self
+    }
+}
+impl Drop for ImportsGuard {
+    fn drop(&mut self) {
+        IMPORTS_LOCK.store(false, Ordering::Release);
+    }
+}
+/// Adds `registration` to the list of imports returned by [`imports`], returning `false` if it already was in it.
+///
+/// This is called before `main` for each symbol imported with `#[stabby::import]` on platforms that support it
+/// (those using ELF binaries, which can also unregister them when unloaded).
+///
+/// # Safety
+/// `registration` must stay valid until it's removed with [`unregister_import`]: if it lives in a library,
+/// it must be unregistered before that library is unloaded.
+#[doc(hidden)]
+pub unsafe fn register_import(registration: &ImportRegistration) -> bool {
+    let _guard = ImportsGuard::lock();
+    if registration.registered.swap(true, Ordering::Relaxed) {
+        return false;
+    }
+    registration
+        .next
+        .store(IMPORTS.load(Ordering::Relaxed), Ordering::Relaxed);
+    IMPORTS.store(
+        registration as *const ImportRegistration as *mut ImportRegistration,
+        Ordering::Relaxed,
+    );
+    true
+}
+/// Removes `registration` from the list of imports returned by [`imports`], returning `false` if it wasn't in it.
+///
+/// This is called when the binary that registered it is unloaded, on platforms using ELF binaries.
+#[doc(hidden)]
+pub fn unregister_import(registration: &ImportRegistration) -> bool {
+    let _guard = ImportsGuard::lock();
+    if !registration.registered.swap(false, Ordering::Relaxed) {
+        return false;
+    }
+    let target = registration as *const ImportRegistration as *mut ImportRegistration;
+    let mut link = &IMPORTS;
+    loop {
+        let current = link.load(Ordering::Relaxed);
+        if current.is_null() {
+            return false;
+        }
+        if current == target {
+            link.store(registration.next.load(Ordering::Relaxed), Ordering::Relaxed);
+            return true;
+        }
+        // SAFETY: registered imports stay valid until they're unregistered, which requires the lock.
+        link = unsafe { &(*current).next };
+    }
+}
+/// The imports registered by `#[stabby::import]`, as returned by [`imports`].
+///
+/// Registering or unregistering imports (which happens when libraries are loaded or unloaded) waits until this
+/// is dropped, so don't keep it around.
+pub struct Imports {
+    _guard: ImportsGuard,
+}
+impl Imports {
+    /// Iterates over the registered imports.
+    pub fn iter(&self) -> impl Iterator<Item = &ImportRegistration> + '_ {
+        // SAFETY: registered imports stay valid until they're unregistered, which requires the lock held by `self`.
+        let mut next = unsafe { IMPORTS.load(Ordering::Relaxed).as_ref() };
+        core::iter::from_fn(move || {
+            let current = next?;
+            // SAFETY: see above.
+            next = unsafe { current.next.load(Ordering::Relaxed).as_ref() };
+            Some(current)
+        })
+    }
+}
+/// Locks the list of imports registered by `#[stabby::import]`, letting you iterate over it with [`Imports::iter`].
+pub fn imports() -> Imports {
+    Imports {
+        _guard: ImportsGuard::lock(),
+    }
+}
+
+/// Used by `#[stabby::import]` to register an import before `main` is called.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register_import {
+    ($import: path) => {
+        const _: () = {
+            static REGISTRATION: $crate::checked_import::ImportRegistration =
+                $crate::checked_import::ImportRegistration::new(|| $import.as_ref().map(|_| ()));
+            extern "C" fn register() {
+                // SAFETY: `unregister` removes it before its binary is unloaded.
+                unsafe { $crate::checked_import::register_import(&REGISTRATION) };
+            }
+            extern "C" fn unregister() {
+                $crate::checked_import::unregister_import(&REGISTRATION);
+            }
+            #[used]
+            #[cfg_attr(
+                any(
+                    target_os = "linux",
+                    target_os = "android",
+                    target_os = "freebsd",
+                    target_os = "netbsd",
+                    target_os = "openbsd",
+                    target_os = "dragonfly",
+                    target_os = "illumos",
+                    target_os = "solaris"
+                ),
+                link_section = ".init_array"
+            )]
+            // Only targets that can also unregister imports when their binary is unloaded may register them.
+            static REGISTER: extern "C" fn() = register;
+            #[used]
+            #[cfg_attr(
+                any(
+                    target_os = "linux",
+                    target_os = "android",
+                    target_os = "freebsd",
+                    target_os = "netbsd",
+                    target_os = "openbsd",
+                    target_os = "dragonfly",
+                    target_os = "illumos",
+                    target_os = "solaris"
+                ),
+                link_section = ".fini_array"
+            )]
+            static UNREGISTER: extern "C" fn() = unregister;
+        };
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IStable;
+
+    #[test]
+    fn registered_imports() {
+        static FAILING: ImportRegistration = ImportRegistration::new(|| {
+            Err(ReportMismatch {
+                local: u8::REPORT,
+                loaded: u16::REPORT,
+            })
+        });
+        static PASSING: ImportRegistration = ImportRegistration::new(|| Ok(()));
+        let registered = |registration: &ImportRegistration| {
+            imports()
+                .iter()
+                .filter(|r| core::ptr::eq(*r, registration))
+                .count()
+        };
+        unsafe {
+            assert!(register_import(&FAILING));
+            assert!(register_import(&PASSING));
+            // Registering twice would make the list loop.
+            assert!(!register_import(&FAILING));
+        }
+        assert_eq!(registered(&FAILING), 1);
+        assert_eq!(registered(&PASSING), 1);
+        let failures = imports()
+            .iter()
+            .filter(|r| core::ptr::eq(*r, &FAILING) || core::ptr::eq(*r, &PASSING))
+            .filter_map(|r| r.check().err())
+            .count();
+        assert_eq!(failures, 1);
+        assert!(unregister_import(&FAILING));
+        assert!(!unregister_import(&FAILING));
+        assert!(unregister_import(&PASSING));
+        assert_eq!(registered(&FAILING), 0);
+        assert_eq!(registered(&PASSING), 0);
+    }
+}
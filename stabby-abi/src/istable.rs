@@ -214,6 +214,10 @@ impl<Offset: Unsigned, T, Rest> Default for Array<Offset, T, Rest> {
 pub trait IBitMask {
     /// Expose the bitmask at runtime.
     const TUPLE: Self::Tuple;
+    /// The number of bits set in the mask.
+    ///
+    /// Defaults to [`usize::MAX`], meaning "unknown", for implementations that predate this constant.
+    const BIT_COUNT: usize = usize::MAX;
     /// The type of the runtime-exposed mask.
     type Tuple: core::fmt::Debug;
     /// `Self[O]`
@@ -235,6 +239,7 @@ pub trait IBitMask {
 }
 impl IBitMask for End {
     const TUPLE: Self::Tuple = ();
+    const BIT_COUNT: usize = 0;
     type Tuple = ();
     type ByteAt<O: Unsigned> = U0;
     type BitOr<T: IBitMask> = T;
@@ -247,6 +252,7 @@ impl IBitMask for End {
 }
 impl<Offset: Unsigned, T: NonZero, Rest: IBitMask> IBitMask for Array<Offset, T, Rest> {
     const TUPLE: Self::Tuple = ((Offset::USIZE, T::USIZE), Rest::TUPLE);
+    const BIT_COUNT: usize = (T::U8.count_ones() as usize).saturating_add(Rest::BIT_COUNT);
     type Tuple = ((usize, usize), Rest::Tuple);
     type ByteAt<O: Unsigned> = <Offset::Equal<O> as Bit>::UTernary<T, Rest::ByteAt<O>>;
     type BitAnd<Mask: IBitMask> =
@@ -272,6 +278,12 @@ impl<Offset: Unsigned, T: NonZero, Rest: IBitMask> IBitMask for Array<Offset, T,
 }
 /// A set of possibly multi-byte forbidden values.
 pub trait IForbiddenValues {
+    /// The number of values in the set, saturating at [`usize::MAX`].
+    ///
+    /// Overlapping values may be counted several times.
+    ///
+    /// Defaults to [`usize::MAX`], meaning "unknown", for implementations that predate this constant.
+    const COUNT: usize = usize::MAX;
     /// Shift all values in the set by `O` bytes
     type Shift<O: Unsigned>: IForbiddenValues;
     /// `union(Self, T)`
@@ -298,6 +310,7 @@ pub trait ISingleForbiddenValue {
     type Resolve: ISingleForbiddenValue;
 }
 impl IForbiddenValues for End {
+    const COUNT: usize = 0;
     type Shift<O: Unsigned> = End;
     type Or<T: IForbiddenValues> = T;
     type SelectFrom<Mask: IBitMask> = End;
@@ -329,6 +342,8 @@ impl<Offset: Unsigned, T, Rest: ISingleForbiddenValue> ISingleForbiddenValue
 impl<Offset: Unsigned, T: Unsigned, Rest: IForbiddenValues> IForbiddenValues
     for Array<Offset, T, Rest>
 {
+    // An `Array` is a single value spanning several bytes (see `is_invalid`): sets of values are built with `Or`.
+    const COUNT: usize = 1;
     type Shift<O: Unsigned> = Array<Offset::Add<O>, T, Rest::Shift<O>>;
     type Or<O: IForbiddenValues> = Or<O, Self>;
     type SelectFrom<Mask: IBitMask> =
@@ -341,6 +356,7 @@ impl<Offset: Unsigned, T: Unsigned, Rest: IForbiddenValues> IForbiddenValues
     }
 }
 impl<A: IForbiddenValues, B: IForbiddenValues> IForbiddenValues for Or<A, B> {
+    const COUNT: usize = A::COUNT.saturating_add(B::COUNT);
     type Shift<O: Unsigned> = Or<A::Shift<O>, B::Shift<O>>;
     type Or<T: IForbiddenValues> = Or<T, Self>;
     type SelectFrom<Mask: IBitMask> =
@@ -357,6 +373,7 @@ pub struct ForbiddenRange<Min: Unsigned, Max: Unsigned<Greater<Min> = B1>, Offse
 impl<Min: Unsigned, Max: Unsigned<Greater<Min> = B1>, Offset: Unsigned> IForbiddenValues
     for ForbiddenRange<Min, Max, Offset>
 {
+    const COUNT: usize = Max::USIZE.saturating_sub(Min::USIZE).saturating_add(1);
     type Shift<O: Unsigned> = ForbiddenRange<Min, Max, Offset::Add<O>>;
     type Or<T: IForbiddenValues> = Or<Self, T>;
     type SelectFrom<Mask: IBitMask> =
@@ -35,7 +35,9 @@ pub mod alloc;
 /// Extending [Non-Zero Types](core::num) to enable niches for other values than 0.
 pub mod num;
 
-pub use stabby_macros::{canary_suffixes, dynptr, export, import, stabby, vtable as vtmacro};
+pub use stabby_macros::{
+    canary_descriptor, canary_suffixes, dynptr, export, import, plugin, stabby, vtable as vtmacro,
+};
 use typenum2::unsigned::Alignment;
 
 use core::fmt::{Debug, Display};
@@ -49,15 +51,25 @@ pub use tuple::Tuple2 as Tuple;
 /// Generate the [`IStable::REPORT`] and [`IStable::ID`] fields for an implementation of [`IStable`].
 #[macro_export]
 macro_rules! primitive_report {
+    (pointer $name: expr, $ty: ty) => {
+        $crate::primitive_report!(@with $name, $ty, true);
+    };
     ($name: expr, $ty: ty) => {
+        $crate::primitive_report!(@with $name, $ty, false);
+    };
+    (@with $name: expr, $ty: ty, $is_pointer: expr) => {
         const REPORT: &'static $crate::report::TypeReport = &$crate::report::TypeReport {
             name: $crate::str::Str::new($name),
             module: $crate::str::Str::new(core::module_path!()),
             fields: $crate::StableLike::new(Some(&$crate::report::FieldReport {
                 name: $crate::str::Str::new("inner"),
                 ty: <$ty as $crate::IStable>::REPORT,
+                offset: 0,
                 next_field: $crate::StableLike::new(None),
             })),
+            layout: $crate::report::LayoutReport::of::<Self>(),
+            compatible_with: $crate::report::VersionRange::NONE,
+            is_pointer: $is_pointer,
             version: 0,
             tyty: $crate::report::TyTy::Struct,
         };
@@ -68,6 +80,9 @@ macro_rules! primitive_report {
             name: $crate::str::Str::new($name),
             module: $crate::str::Str::new(core::module_path!()),
             fields: $crate::StableLike::new(None),
+            layout: $crate::report::LayoutReport::of::<Self>(),
+            compatible_with: $crate::report::VersionRange::NONE,
+            is_pointer: false,
             version: 0,
             tyty: $crate::report::TyTy::Struct,
         };
@@ -395,6 +410,8 @@ pub use result::Result;
 /// Like [`core::option::Option`], but ABI-stable with niche optimizations!
 pub mod option;
 pub use option::Option;
+/// Exporting and loading a whole plugin through a single, checked symbol.
+pub mod plugin;
 /// A very simple ABI-stable reflection framework.
 pub mod report;
 /// ABI-stable slices.
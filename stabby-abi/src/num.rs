@@ -85,6 +85,9 @@ macro_rules! define_non_max {
                 name: $crate::str::Str::new(stringify!($NonMaxU8)),
                 module: $crate::str::Str::new(core::module_path!()),
                 fields: $crate::StableLike::new(None),
+                layout: $crate::report::LayoutReport::of::<Self>(),
+                compatible_with: $crate::report::VersionRange::NONE,
+                is_pointer: false,
                 version: 0,
                 tyty: $crate::report::TyTy::Struct,
             };
@@ -166,6 +169,9 @@ macro_rules! define_non_x {
                 name: $crate::str::Str::new(stringify!($NonMaxU8)),
                 module: $crate::str::Str::new(core::module_path!()),
                 fields: $crate::StableLike::new(None),
+                layout: $crate::report::LayoutReport::of::<Self>(),
+                compatible_with: $crate::report::VersionRange::NONE,
+                is_pointer: false,
                 version: 0,
                 tyty: $crate::report::TyTy::Struct,
             };
@@ -0,0 +1,229 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   Pierre Avital, <pierre.avital@me.com>
+//
+
+use crate::{report::TypeReport, str::Str, IStable};
+
+/// A version of `stabby`'s ABI, as listed in the changelog (`abi=MAJOR.MINOR.PATCH`).
+#[crate::stabby]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AbiVersion {
+    /// Incremented when the layout of existing types changes.
+    pub major: u16,
+    /// Incremented when new types are added.
+    pub minor: u16,
+    /// Incremented for fixes that don't affect layouts.
+    pub patch: u16,
+}
+impl AbiVersion {
+    /// Whether or not types built with `self` and `other` can be exchanged safely.
+    pub const fn is_compatible(&self, other: &Self) -> bool {
+        self.major == other.major
+    }
+}
+impl core::fmt::Display for AbiVersion {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let Self {
+            major,
+            minor,
+            patch,
+        } = self;
+        write!(f, "{major}.{minor}.{patch}")
+    }
+}
+
+/// Parses one of the numbers of a crate's version, as found in `CARGO_PKG_VERSION_MAJOR`.
+const fn parse_version(version: &str) -> u64 {
+    let mut digits = version.as_bytes();
+    let mut value = 0u64;
+    while let [digit, rest @ ..] = digits {
+        value = value
+            .wrapping_mul(10)
+            .wrapping_add(digit.wrapping_sub(b'0') as u64);
+        digits = rest;
+    }
+    value
+}
+/// Counts how many times `base` divides `value`.
+///
+/// `stabby`'s versions encode its API version in their factors of 2, and its ABI version in their factors of 3:
+/// `72.1.16` stands for `api=3.0.4, abi=2.0.0`.
+const fn factor(mut value: u64, base: u64) -> u16 {
+    let mut count = 0u16;
+    while value > 1 {
+        match (value.checked_rem(base), value.checked_div(base)) {
+            (Some(0), Some(quotient)) => {
+                value = quotient;
+                count = count.wrapping_add(1);
+            }
+            _ => break,
+        }
+    }
+    count
+}
+impl AbiVersion {
+    /// The ABI version encoded in a version of `stabby` (its factors of 3).
+    pub const fn from_crate_version(major: &str, minor: &str, patch: &str) -> Self {
+        Self {
+            major: factor(parse_version(major), 3),
+            minor: factor(parse_version(minor), 3),
+            patch: factor(parse_version(patch), 3),
+        }
+    }
+}
+
+/// The version of `stabby`'s ABI used by this build, derived from the version of `stabby-abi`.
+pub const ABI_VERSION: AbiVersion = AbiVersion::from_crate_version(
+    env!("CARGO_PKG_VERSION_MAJOR"),
+    env!("CARGO_PKG_VERSION_MINOR"),
+    env!("CARGO_PKG_VERSION_PATCH"),
+);
+
+/// The name of the symbol exported by `#[stabby::plugin]`, which returns a `&'static PluginDescriptor`.
+pub const PLUGIN_SYMBOL: &str = "stabby_plugin_v1";
+
+/// The descriptor a library exports through `#[stabby::plugin]`, letting hosts check it in a single call.
+#[crate::stabby]
+#[derive(Debug, Clone, Copy)]
+pub struct PluginDescriptor {
+    /// The version of `stabby`'s ABI the plugin was built with.
+    pub abi_version: AbiVersion,
+    /// The name of the plugin.
+    pub name: Str<'static>,
+    /// The report of `&'static T`, where `T` is the type of the plugin's static.
+    pub report: &'static TypeReport,
+    /// A pointer to the plugin's static.
+    pub entry: *const (),
+}
+// SAFETY: `entry` always points to a `static`, which must be `Sync`.
+unsafe impl Send for PluginDescriptor {}
+// SAFETY: `entry` always points to a `static`, which must be `Sync`.
+unsafe impl Sync for PluginDescriptor {}
+impl PluginDescriptor {
+    /// Used by `#[stabby::plugin]`.
+    pub const fn new<T: IStable + Sync>(name: &'static str, entry: &'static T) -> Self {
+        Self {
+            abi_version: ABI_VERSION,
+            name: Str::new(name),
+            report: <&'static T as IStable>::REPORT,
+            entry: entry as *const T as *const (),
+        }
+    }
+    /// Checks the plugin's ABI version and report, returning its static if they match the local ones.
+    ///
+    /// Reports are compared using [`TypeReport::is_evolution_compatible`].
+    ///
+    /// # Errors
+    /// If the ABI versions aren't compatible, or if the reports don't match.
+    pub fn get<T: IStable + Sync>(&self) -> Result<&'static T, PluginMismatch> {
+        if !ABI_VERSION.is_compatible(&self.abi_version) {
+            return Err(PluginMismatch::AbiVersion {
+                local: ABI_VERSION,
+                loaded: self.abi_version,
+            });
+        }
+        let local = <&'static T as IStable>::REPORT;
+        if !local.is_evolution_compatible(self.report) {
+            return Err(PluginMismatch::Report(
+                crate::checked_import::ReportMismatch {
+                    local,
+                    loaded: self.report,
+                },
+            ));
+        }
+        // SAFETY: the reports match, so `entry` points to a `T` (or a compatible evolution of it).
+        Ok(unsafe { &*self.entry.cast::<T>() })
+    }
+}
+
+/// The reasons why a [`PluginDescriptor`] may be rejected.
+#[derive(Debug, Clone, Copy)]
+pub enum PluginMismatch {
+    /// The plugin was built with an incompatible version of `stabby`'s ABI.
+    AbiVersion {
+        /// The local ABI version.
+        local: AbiVersion,
+        /// The plugin's ABI version.
+        loaded: AbiVersion,
+    },
+    /// The plugin's report doesn't match the expected one.
+    Report(crate::checked_import::ReportMismatch),
+}
+impl core::fmt::Display for PluginMismatch {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::AbiVersion { local, loaded } => write!(
+                f,
+                "Plugin built with stabby ABI {loaded}, which is incompatible with the local ABI {local}"
+            ),
+            Self::Report(mismatch) => write!(f, "{mismatch}"),
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for PluginMismatch {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abi_version() {
+        assert_eq!(
+            AbiVersion::from_crate_version("72", "1", "16"),
+            AbiVersion {
+                major: 2,
+                minor: 0,
+                patch: 0
+            }
+        );
+        assert_eq!(
+            AbiVersion::from_crate_version("432", "3", "1"),
+            AbiVersion {
+                major: 3,
+                minor: 1,
+                patch: 0
+            }
+        );
+        assert_eq!(
+            ABI_VERSION,
+            AbiVersion::from_crate_version(
+                env!("CARGO_PKG_VERSION_MAJOR"),
+                env!("CARGO_PKG_VERSION_MINOR"),
+                env!("CARGO_PKG_VERSION_PATCH"),
+            )
+        );
+    }
+
+    #[test]
+    fn descriptor_checks() {
+        static VALUE: u32 = 42;
+        static DESCRIPTOR: PluginDescriptor = PluginDescriptor::new("answer", &VALUE);
+        assert_eq!(DESCRIPTOR.get::<u32>().ok(), Some(&42));
+        assert!(matches!(
+            DESCRIPTOR.get::<u64>(),
+            Err(PluginMismatch::Report(_))
+        ));
+        let future = PluginDescriptor {
+            abi_version: AbiVersion {
+                major: ABI_VERSION.major.wrapping_add(1),
+                ..ABI_VERSION
+            },
+            ..DESCRIPTOR
+        };
+        assert!(matches!(
+            future.get::<u32>(),
+            Err(PluginMismatch::AbiVersion { .. })
+        ));
+    }
+}
@@ -12,9 +12,14 @@
 //   Pierre Avital, <pierre.avital@me.com>
 //
 
-use crate::{str::Str, StableLike};
+use crate::istable::{IBitMask, IForbiddenValues, ISaturatingAdd, SaturatingAddValue};
+use crate::{str::Str, IStable, StableLike, Unsigned};
 use sha2_const_stable::Sha256;
 
+/// Owned versions of the reports, which may be serialized to compare them offline.
+#[cfg(feature = "alloc-rs")]
+pub mod owned;
+
 /// A type
 type NextField = StableLike<Option<&'static FieldReport>, usize>;
 
@@ -30,10 +35,101 @@ pub struct TypeReport {
     pub fields: NextField,
     /// How the type was declared
     pub tyty: TyTy,
+    /// The type's memory layout.
+    pub layout: LayoutReport,
+    /// The versions of this type that [`TypeReport::is_evolution_compatible`] may accept in place of this one.
+    ///
+    /// This is [`VersionRange::NONE`] unless the type opted in with `#[stabby::stabby(compatible_with = "M..N")]`.
+    pub compatible_with: VersionRange,
+    /// Whether the type is a pointer (`&`, `&mut`, `*const`, `*mut` or `NonNull`), whose only field is its pointee.
+    ///
+    /// [`TypeReport::is_evolution_compatible`] relaxes its checks for types reached through pointers.
+    pub is_pointer: bool,
     /// The version of the type's invariants.
     pub version: u32,
 }
 
+/// A half-open range of versions of a type, as declared with `#[stabby::stabby(compatible_with = "M..N")]`.
+#[crate::stabby]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VersionRange {
+    /// The first version in the range.
+    pub start: u32,
+    /// The first version after the range.
+    pub end: u32,
+}
+impl VersionRange {
+    /// The empty range, used by types that didn't opt into version-tolerant compatibility.
+    pub const NONE: Self = Self { start: 0, end: 0 };
+    /// Whether or not `version` is part of the range.
+    pub const fn contains(&self, version: u32) -> bool {
+        self.start <= version && version < self.end
+    }
+    /// Whether or not the range is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+}
+
+/// A summary of a type's memory layout, as computed by [`IStable`].
+#[crate::stabby]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LayoutReport {
+    /// The type's size in bytes, including its end padding.
+    pub size: usize,
+    /// The type's alignment in bytes.
+    pub align: usize,
+    /// How many values the type may never take, according to [`IStable::ForbiddenValues`].
+    pub forbidden_values: usize,
+    /// How many bits of the type are padding, according to [`IStable::UnusedBits`].
+    pub padding_bits: usize,
+    /// How many niches rustc knows about in the type, according to [`IStable::HasExactlyOneNiche`]: `2` means "more than one".
+    pub rustc_niches: u8,
+}
+impl LayoutReport {
+    /// Computes the layout report for `T`.
+    pub const fn of<T: IStable>() -> Self {
+        let align = <T::Align as Unsigned>::USIZE;
+        Self {
+            size: aligned_offset(<T::Size as Unsigned>::USIZE, align),
+            align,
+            forbidden_values: <T::ForbiddenValues as IForbiddenValues>::COUNT,
+            padding_bits: <T::UnusedBits as IBitMask>::BIT_COUNT,
+            rustc_niches: match <T::HasExactlyOneNiche as ISaturatingAdd>::VALUE {
+                SaturatingAddValue::B0 => 0,
+                SaturatingAddValue::B1 => 1,
+                SaturatingAddValue::Saturator => 2,
+            },
+        }
+    }
+}
+impl core::fmt::Display for LayoutReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let Self {
+            size,
+            align,
+            forbidden_values,
+            padding_bits,
+            rustc_niches,
+        } = self;
+        write!(f, "size {size}, align {align}, {forbidden_values} forbidden values, {padding_bits} padding bits, ")?;
+        match rustc_niches {
+            0 | 1 => write!(f, "{rustc_niches} rustc niches"),
+            _ => write!(f, "several rustc niches"),
+        }
+    }
+}
+
+/// Returns the first offset at or after `start` that is a multiple of `align`.
+///
+/// `align` must be a power of 2. This is used by `stabby`'s proc-macros to compute field offsets.
+pub const fn aligned_offset(start: usize, align: usize) -> usize {
+    let mask = align.wrapping_sub(1);
+    start.wrapping_add(mask) & !mask
+}
+
 impl core::fmt::Display for TypeReport {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let Self {
@@ -41,11 +137,18 @@ impl core::fmt::Display for TypeReport {
             module,
             version,
             tyty,
+            layout: LayoutReport { size, align, .. },
             ..
         } = self;
-        write!(f, "{tyty:?} {module} :: {name} (version{version}) {{")?;
-        for FieldReport { name, ty, .. } in self.fields() {
-            write!(f, "{name}: {ty}, ")?
+        write!(
+            f,
+            "{tyty:?} {module} :: {name} (version{version}, size{size}, align{align}) {{"
+        )?;
+        for FieldReport {
+            name, ty, offset, ..
+        } in self.fields()
+        {
+            write!(f, "{name}@{offset}: {ty}, ")?
         }
         write!(f, "}}")
     }
@@ -59,6 +162,9 @@ impl core::hash::Hash for TypeReport {
         }
         self.version.hash(state);
         self.tyty.hash(state);
+        self.layout.hash(state);
+        self.compatible_with.hash(state);
+        self.is_pointer.hash(state);
     }
 }
 
@@ -69,10 +175,58 @@ impl TypeReport {
             && self.module == other.module
             && self.version == other.version
             && self.tyty == other.tyty
+            && self.layout == other.layout
             && self
                 .fields()
                 .zip(other.fields())
-                .all(|(s, o)| s.name == o.name && s.ty.is_compatible(o.ty))
+                .all(|(s, o)| s.name == o.name && s.offset == o.offset && s.ty.is_compatible(o.ty))
+    }
+    /// Like [`TypeReport::is_compatible`], but tolerates the evolutions of types that opted into it with
+    /// `#[stabby::stabby(version = N, compatible_with = "M..N")]`.
+    ///
+    /// When such a type is reached through a pointer (`&`, `&mut`, `*const`, `*mut` or `NonNull`),
+    /// and both reports opted in, the two reports are compatible if:
+    /// - they have the same name, module, declaration kind and alignment,
+    /// - their versions are equal, or one of them is in the other's [`TypeReport::compatible_with`] range,
+    /// - the fields of one are a prefix of the other's, in declaration order: either side may have extra trailing fields.
+    ///
+    /// This check is symmetric: the side with the most recent version is responsible for not accessing
+    /// fields that the other side doesn't have, for example by checking the version at runtime.
+    ///
+    /// Types reached by value are always checked with [`TypeReport::is_compatible`]'s rules, since their size matters.
+    pub fn is_evolution_compatible(&self, other: &Self) -> bool {
+        self.evolution_compatible_at(other, false)
+    }
+    fn evolution_compatible_at(&self, other: &Self, behind_pointer: bool) -> bool {
+        let fields_behind_pointer = self.is_pointer;
+        let fields_compatible = |s: &FieldReport, o: &FieldReport| {
+            s.name == o.name
+                && s.offset == o.offset
+                && s.ty.evolution_compatible_at(o.ty, fields_behind_pointer)
+        };
+        if !(self.name == other.name && self.module == other.module && self.tyty == other.tyty) {
+            return false;
+        }
+        if behind_pointer && !self.compatible_with.is_empty() && !other.compatible_with.is_empty() {
+            let self_count = self.fields().count();
+            let other_count = other.fields().count();
+            // Fields are listed in reverse declaration order, so any extra trailing fields come first.
+            return (self.version == other.version
+                || self.compatible_with.contains(other.version)
+                || other.compatible_with.contains(self.version))
+                && self.layout.align == other.layout.align
+                && self
+                    .fields()
+                    .skip(self_count.saturating_sub(other_count))
+                    .zip(other.fields().skip(other_count.saturating_sub(self_count)))
+                    .all(|(s, o)| fields_compatible(s, o));
+        }
+        self.version == other.version
+            && self.layout == other.layout
+            && self
+                .fields()
+                .zip(other.fields())
+                .all(|(s, o)| fields_compatible(s, o))
     }
 }
 
@@ -99,11 +253,16 @@ pub struct FieldReport {
     pub ty: &'static TypeReport,
     /// The next field in the [`TypeReport`]
     pub next_field: NextField,
+    /// The field's offset in bytes from the start of the type.
+    ///
+    /// Since the variants of enums and the fields of unions share their storage, their offset is always 0.
+    pub offset: usize,
 }
 impl core::hash::Hash for FieldReport {
     fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
         self.name.hash(state);
         self.ty.hash(state);
+        self.offset.hash(state);
     }
 }
 
@@ -115,6 +274,7 @@ impl TypeReport {
 }
 /// An iterator over a type's fields.
 #[crate::stabby]
+#[derive(Clone)]
 pub struct Fields(Option<&'static FieldReport>);
 impl Fields {
     /// A `const` compatible alternative to [`Iterator::next`]
@@ -134,6 +294,306 @@ impl Iterator for Fields {
     }
 }
 
+/// What differs between two [`TypeReport`]s at a given [`DiffPath`].
+///
+/// By convention, `local` refers to the report [`TypeReport::diff`] was called on, and `loaded` to its argument.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum DiffKind {
+    /// The type at this path has a different name.
+    TypeRenamed {
+        /// The type's name in the local report.
+        local: Str<'static>,
+        /// The type's name in the loaded report.
+        loaded: Str<'static>,
+    },
+    /// The type at this path was declared in a different module.
+    ModuleChanged {
+        /// The type's module in the local report.
+        local: Str<'static>,
+        /// The type's module in the loaded report.
+        loaded: Str<'static>,
+    },
+    /// The type at this path was declared differently.
+    TyTyChanged {
+        /// The type's declaration kind in the local report.
+        local: TyTy,
+        /// The type's declaration kind in the loaded report.
+        loaded: TyTy,
+    },
+    /// The type at this path has a different invariants version.
+    VersionChanged {
+        /// The type's version in the local report.
+        local: u32,
+        /// The type's version in the loaded report.
+        loaded: u32,
+    },
+    /// The type at this path has a different memory layout.
+    LayoutChanged {
+        /// The type's layout in the local report.
+        local: LayoutReport,
+        /// The type's layout in the loaded report.
+        loaded: LayoutReport,
+    },
+    /// The field at this path is at a different offset in the loaded report.
+    OffsetChanged {
+        /// The field's offset in the local report.
+        local: usize,
+        /// The field's offset in the loaded report.
+        loaded: usize,
+    },
+    /// The field at this path has a different name in the loaded report.
+    FieldRenamed {
+        /// The field's name in the local report.
+        local: Str<'static>,
+        /// The field's name in the loaded report.
+        loaded: Str<'static>,
+    },
+    /// The field at this path only exists in the loaded report.
+    FieldAdded,
+    /// The field at this path only exists in the local report.
+    FieldRemoved,
+}
+impl core::fmt::Display for DiffKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DiffKind::TypeRenamed { local, loaded } => {
+                write!(f, "type changed from `{local}` to `{loaded}`")
+            }
+            DiffKind::ModuleChanged { local, loaded } => {
+                write!(f, "module changed from `{local}` to `{loaded}`")
+            }
+            DiffKind::TyTyChanged { local, loaded } => {
+                write!(f, "declaration changed from {local:?} to {loaded:?}")
+            }
+            DiffKind::VersionChanged { local, loaded } => {
+                write!(f, "version changed from {local} to {loaded}")
+            }
+            DiffKind::LayoutChanged { local, loaded } => {
+                write!(f, "layout changed from ({local}) to ({loaded})")
+            }
+            DiffKind::OffsetChanged { local, loaded } => {
+                write!(f, "offset changed from {local} to {loaded}")
+            }
+            DiffKind::FieldRenamed { local, loaded } => {
+                write!(f, "field renamed from `{local}` to `{loaded}`")
+            }
+            DiffKind::FieldAdded => write!(f, "field added"),
+            DiffKind::FieldRemoved => write!(f, "field removed"),
+        }
+    }
+}
+
+/// The path to a field within a [`TypeReport`], starting with the name of the root type.
+///
+/// Paths are built on the stack while walking reports, and display as `Config.inner.timeout.secs`.
+#[derive(Debug, Clone, Copy)]
+pub struct DiffPath<'a> {
+    /// The last segment of the path.
+    pub segment: Str<'static>,
+    /// The path to the type containing this segment, if any.
+    pub parent: Option<&'a DiffPath<'a>>,
+}
+impl core::fmt::Display for DiffPath<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if let Some(parent) = self.parent {
+            write!(f, "{parent}.")?;
+        }
+        write!(f, "{}", self.segment)
+    }
+}
+
+fn visit_field_diff(
+    local: &FieldReport,
+    loaded: &FieldReport,
+    path: &DiffPath<'_>,
+    f: &mut dyn FnMut(&DiffPath<'_>, DiffKind),
+) {
+    if local.offset != loaded.offset {
+        f(
+            path,
+            DiffKind::OffsetChanged {
+                local: local.offset,
+                loaded: loaded.offset,
+            },
+        );
+    }
+    local.ty.visit_diff_at(loaded.ty, path, f);
+}
+
+/// A single difference between two [`TypeReport`]s, as returned by [`TypeReport::diff`].
+#[cfg(feature = "alloc-rs")]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct ReportDiff {
+    /// The path to the differing type or field, starting with the name of the root type.
+    pub path: alloc_rs::vec::Vec<Str<'static>>,
+    /// What differs at that path.
+    pub kind: DiffKind,
+}
+#[cfg(feature = "alloc-rs")]
+impl core::fmt::Display for ReportDiff {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut segments = self.path.iter();
+        if let Some(first) = segments.next() {
+            write!(f, "{first}")?;
+        }
+        for segment in segments {
+            write!(f, ".{segment}")?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+impl TypeReport {
+    /// Walks `self` and `loaded` side by side, calling `f` for every difference found along the way.
+    ///
+    /// Fields are matched by name, and fields whose name only appears on one side are then matched in declaration
+    /// order, to be reported as renamed. When a type's name or declaration kind differ, its fields
+    /// are not compared further, as they are likely to be unrelated.
+    ///
+    /// This is the allocation-free counterpart of [`TypeReport::diff`].
+    pub fn visit_diff<F: FnMut(&DiffPath<'_>, DiffKind)>(&self, loaded: &Self, mut f: F) {
+        let root = DiffPath {
+            segment: self.name,
+            parent: None,
+        };
+        self.visit_diff_at(loaded, &root, &mut f)
+    }
+    fn visit_diff_at(
+        &self,
+        loaded: &Self,
+        path: &DiffPath<'_>,
+        f: &mut dyn FnMut(&DiffPath<'_>, DiffKind),
+    ) {
+        if core::ptr::eq(self, loaded) {
+            return;
+        }
+        let mut comparable = true;
+        if self.name != loaded.name {
+            comparable = false;
+            f(
+                path,
+                DiffKind::TypeRenamed {
+                    local: self.name,
+                    loaded: loaded.name,
+                },
+            );
+        }
+        if self.module != loaded.module {
+            f(
+                path,
+                DiffKind::ModuleChanged {
+                    local: self.module,
+                    loaded: loaded.module,
+                },
+            );
+        }
+        if self.tyty != loaded.tyty {
+            comparable = false;
+            f(
+                path,
+                DiffKind::TyTyChanged {
+                    local: self.tyty,
+                    loaded: loaded.tyty,
+                },
+            );
+        }
+        if self.version != loaded.version {
+            f(
+                path,
+                DiffKind::VersionChanged {
+                    local: self.version,
+                    loaded: loaded.version,
+                },
+            );
+        }
+        if self.layout != loaded.layout {
+            f(
+                path,
+                DiffKind::LayoutChanged {
+                    local: self.layout,
+                    loaded: loaded.layout,
+                },
+            );
+        }
+        if !comparable {
+            return;
+        }
+        // Fields that kept their name are compared with one another.
+        for l in self.fields() {
+            if let Some(r) = loaded.field(l.name) {
+                let path = DiffPath {
+                    segment: l.name,
+                    parent: Some(path),
+                };
+                visit_field_diff(l, r, &path, f);
+            }
+        }
+        // The remaining ones are paired in declaration order, which is the reverse of the reports' field order:
+        // the first unpaired fields in the reports are the last declared ones, and are reported as removed or added.
+        let local_unmatched = self.fields().filter(|l| loaded.field(l.name).is_none());
+        let loaded_unmatched = loaded.fields().filter(|r| self.field(r.name).is_none());
+        let local_count = local_unmatched.clone().count();
+        let loaded_count = loaded_unmatched.clone().count();
+        let removed = local_count.saturating_sub(loaded_count);
+        let added = loaded_count.saturating_sub(local_count);
+        for l in local_unmatched.clone().take(removed) {
+            let path = DiffPath {
+                segment: l.name,
+                parent: Some(path),
+            };
+            f(&path, DiffKind::FieldRemoved)
+        }
+        for r in loaded_unmatched.clone().take(added) {
+            let path = DiffPath {
+                segment: r.name,
+                parent: Some(path),
+            };
+            f(&path, DiffKind::FieldAdded)
+        }
+        for (l, r) in local_unmatched
+            .skip(removed)
+            .zip(loaded_unmatched.skip(added))
+        {
+            let path = DiffPath {
+                segment: l.name,
+                parent: Some(path),
+            };
+            f(
+                &path,
+                DiffKind::FieldRenamed {
+                    local: l.name,
+                    loaded: r.name,
+                },
+            );
+            visit_field_diff(l, r, &path, f);
+        }
+    }
+    fn field(&self, name: Str<'static>) -> Option<&'static FieldReport> {
+        self.fields().find(|field| field.name == name)
+    }
+    /// Lists the differences between `self` and `loaded`, with a path to each of them.
+    ///
+    /// Refer to [`TypeReport::visit_diff`] for how both reports are walked.
+    #[cfg(feature = "alloc-rs")]
+    pub fn diff(&self, loaded: &Self) -> alloc_rs::vec::Vec<ReportDiff> {
+        let mut diffs = alloc_rs::vec::Vec::new();
+        self.visit_diff(loaded, |path, kind| {
+            let mut segments = alloc_rs::vec::Vec::new();
+            let mut node = Some(path);
+            while let Some(DiffPath { segment, parent }) = node {
+                segments.push(*segment);
+                node = *parent;
+            }
+            segments.reverse();
+            diffs.push(ReportDiff {
+                path: segments,
+                kind,
+            })
+        });
+        diffs
+    }
+}
+
 const fn hash_report(mut hash: Sha256, report: &TypeReport) -> Sha256 {
     hash = hash
         .update(report.module.as_str().as_bytes())
@@ -143,10 +603,27 @@ const fn hash_report(mut hash: Sha256, report: &TypeReport) -> Sha256 {
         crate::report::TyTy::Union => hash.update(&[1]),
         crate::report::TyTy::Enum(s) => hash.update(s.as_str().as_bytes()),
     };
+    let LayoutReport {
+        size,
+        align,
+        forbidden_values,
+        padding_bits,
+        rustc_niches,
+    } = report.layout;
+    hash = hash
+        .update(&(size as u64).to_le_bytes())
+        .update(&(align as u64).to_le_bytes())
+        .update(&(forbidden_values as u64).to_le_bytes())
+        .update(&(padding_bits as u64).to_le_bytes())
+        .update(&[rustc_niches]);
     let mut fields = report.fields();
     while let (new, Some(next)) = fields.next_const() {
         fields = new;
-        hash = hash_report(hash.update(next.name.as_str().as_bytes()), next.ty)
+        hash = hash_report(
+            hash.update(next.name.as_str().as_bytes())
+                .update(&(next.offset as u64).to_le_bytes()),
+            next.ty,
+        )
     }
     hash
 }
@@ -0,0 +1,598 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   Pierre Avital, <pierre.avital@me.com>
+//
+
+//! Owned mirrors of [`TypeReport`], [`FieldReport`] and [`TyTy`].
+//!
+//! Unlike their `'static` counterparts, these can be built at runtime, which lets reports be saved to disk
+//! (using [`OwnedTypeReport::to_bytes`], or JSON through the `serde` feature) and compared with the reports
+//! of another build later on, without having to load it.
+
+use super::{FieldReport, LayoutReport, TyTy, TypeReport, VersionRange};
+use alloc_rs::{collections::BTreeMap, string::String, vec::Vec};
+use core::hash::{Hash, Hasher};
+
+/// An owned equivalent of [`TypeReport`].
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedTypeReport {
+    /// The type's name.
+    pub name: String,
+    /// The type's parent module's path.
+    pub module: String,
+    /// The fields of this type, in the same order as [`TypeReport::fields`].
+    pub fields: Vec<OwnedFieldReport>,
+    /// How the type was declared
+    pub tyty: OwnedTyTy,
+    /// The type's memory layout.
+    pub layout: LayoutReport,
+    /// The versions of this type that may be accepted in place of this one, see [`TypeReport::compatible_with`].
+    pub compatible_with: VersionRange,
+    /// Whether the type is a pointer, see [`TypeReport::is_pointer`].
+    pub is_pointer: bool,
+    /// The version of the type's invariants.
+    pub version: u32,
+}
+
+/// An owned equivalent of [`FieldReport`].
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedFieldReport {
+    /// The field's name.
+    pub name: String,
+    /// The field's type.
+    pub ty: OwnedTypeReport,
+    /// The field's offset in bytes from the start of the type.
+    pub offset: usize,
+}
+
+/// An owned equivalent of [`TyTy`].
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OwnedTyTy {
+    /// As a struct
+    Struct,
+    /// As an enum (with which calling convention)
+    Enum(String),
+    /// As a union.
+    Union,
+}
+
+impl From<&TyTy> for OwnedTyTy {
+    fn from(value: &TyTy) -> Self {
+        match value {
+            TyTy::Struct => Self::Struct,
+            TyTy::Enum(repr) => Self::Enum(repr.as_str().into()),
+            TyTy::Union => Self::Union,
+        }
+    }
+}
+impl From<&FieldReport> for OwnedFieldReport {
+    fn from(value: &FieldReport) -> Self {
+        Self {
+            name: value.name.as_str().into(),
+            ty: value.ty.into(),
+            offset: value.offset,
+        }
+    }
+}
+impl From<&TypeReport> for OwnedTypeReport {
+    fn from(value: &TypeReport) -> Self {
+        Self {
+            name: value.name.as_str().into(),
+            module: value.module.as_str().into(),
+            fields: value.fields().map(OwnedFieldReport::from).collect(),
+            tyty: (&value.tyty).into(),
+            layout: value.layout,
+            compatible_with: value.compatible_with,
+            is_pointer: value.is_pointer,
+            version: value.version,
+        }
+    }
+}
+
+impl PartialEq<TyTy> for OwnedTyTy {
+    fn eq(&self, other: &TyTy) -> bool {
+        match (self, other) {
+            (Self::Struct, TyTy::Struct) | (Self::Union, TyTy::Union) => true,
+            (Self::Enum(s), TyTy::Enum(o)) => s.as_str() == o.as_str(),
+            _ => false,
+        }
+    }
+}
+
+impl core::fmt::Display for OwnedTypeReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let Self {
+            name,
+            module,
+            version,
+            tyty,
+            layout: LayoutReport { size, align, .. },
+            fields,
+            ..
+        } = self;
+        write!(
+            f,
+            "{tyty:?} {module} :: {name} (version{version}, size{size}, align{align}) {{"
+        )?;
+        for OwnedFieldReport { name, ty, offset } in fields {
+            write!(f, "{name}@{offset}: {ty}, ")?
+        }
+        write!(f, "}}")
+    }
+}
+
+impl OwnedTypeReport {
+    /// Whether or not two reports correspond to the same type, with the same layout and invariants.
+    ///
+    /// This follows the same rules as [`TypeReport::is_compatible`].
+    pub fn is_compatible(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.module == other.module
+            && self.version == other.version
+            && self.tyty == other.tyty
+            && self.layout == other.layout
+            && self
+                .fields
+                .iter()
+                .zip(&other.fields)
+                .all(|(s, o)| s.name == o.name && s.offset == o.offset && s.ty.is_compatible(&o.ty))
+    }
+    /// Whether or not `self` corresponds to the same type as `other`, with the same layout and invariants.
+    ///
+    /// This follows the same rules as [`TypeReport::is_compatible`].
+    pub fn is_compatible_with(&self, other: &TypeReport) -> bool {
+        self.name == other.name.as_str()
+            && self.module == other.module.as_str()
+            && self.version == other.version
+            && self.tyty == other.tyty
+            && self.layout == other.layout
+            && self.fields.iter().zip(other.fields()).all(|(s, o)| {
+                s.name == o.name.as_str() && s.offset == o.offset && s.ty.is_compatible_with(o.ty)
+            })
+    }
+
+    /// Encodes the report in `stabby`'s compact binary format.
+    ///
+    /// The format starts with [`MAGIC`] and [`FORMAT_VERSION`], and writes identical sub-reports only once,
+    /// referring back to them by index afterwards.
+    ///
+    /// Note that [`OwnedTypeReport::from_bytes`] rejects reports nested deeper than [`MAX_DEPTH`],
+    /// or that expand to more than [`MAX_NODES`] sub-reports.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = Vec::from(MAGIC);
+        buffer.push(FORMAT_VERSION);
+        self.encode(&mut buffer, &mut Known::default());
+        buffer
+    }
+    fn encode<'a>(&'a self, buffer: &mut Vec<u8>, known: &mut Known<'a>) {
+        let hash = known.hash(self);
+        if let Some(index) = known.find(self, hash) {
+            buffer.push(REFERENCE);
+            write_varint(buffer, index);
+            return;
+        }
+        buffer.push(DEFINITION);
+        write_str(buffer, &self.name);
+        write_str(buffer, &self.module);
+        match &self.tyty {
+            OwnedTyTy::Struct => buffer.push(TYTY_STRUCT),
+            OwnedTyTy::Union => buffer.push(TYTY_UNION),
+            OwnedTyTy::Enum(repr) => {
+                buffer.push(TYTY_ENUM);
+                write_str(buffer, repr);
+            }
+        }
+        let LayoutReport {
+            size,
+            align,
+            forbidden_values,
+            padding_bits,
+            rustc_niches,
+        } = self.layout;
+        for value in [size, align, forbidden_values, padding_bits] {
+            write_varint(buffer, value);
+        }
+        buffer.push(rustc_niches);
+        buffer.extend_from_slice(&self.version.to_le_bytes());
+        let VersionRange { start, end } = self.compatible_with;
+        for bound in [start, end] {
+            write_varint(buffer, bound as usize);
+        }
+        buffer.push(u8::from(self.is_pointer));
+        write_varint(buffer, self.fields.len());
+        for field in &self.fields {
+            write_str(buffer, &field.name);
+            write_varint(buffer, field.offset);
+            field.ty.encode(buffer, known);
+        }
+        // Sub-reports are registered once fully written, so that the decoder assigns them the same index.
+        known.insert(self, hash);
+    }
+
+    /// Decodes a report that was encoded using [`OwnedTypeReport::to_bytes`].
+    ///
+    /// # Errors
+    /// If `bytes` isn't a valid encoding of a report in a supported version of the format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader(bytes);
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(DecodeError::BadMagic);
+        }
+        match reader.byte()? {
+            FORMAT_VERSION => {}
+            version => return Err(DecodeError::UnsupportedVersion(version)),
+        }
+        let report = Self::decode(&mut reader, &mut Vec::new(), 0, &mut 0)?;
+        if !reader.0.is_empty() {
+            return Err(DecodeError::TrailingBytes(reader.0.len()));
+        }
+        Ok(report)
+    }
+    /// Decodes a sub-report, keeping `known` as the list of already decoded definitions along with
+    /// the number of nodes each expands to, and `nodes` as the number of nodes decoded so far.
+    fn decode(
+        reader: &mut Reader<'_>,
+        known: &mut Vec<(Self, usize)>,
+        depth: usize,
+        nodes: &mut usize,
+    ) -> Result<Self, DecodeError> {
+        if depth >= MAX_DEPTH {
+            return Err(DecodeError::TooDeep);
+        }
+        let mut count = |added: usize| {
+            *nodes = nodes
+                .checked_add(added)
+                .filter(|nodes| *nodes <= MAX_NODES)
+                .ok_or(DecodeError::TooLarge)?;
+            Ok(())
+        };
+        match reader.byte()? {
+            REFERENCE => {
+                let index = reader.varint()?;
+                let (report, size) = known
+                    .get(index)
+                    .ok_or(DecodeError::InvalidReference(index))?;
+                // References are expanded, so they are counted before being cloned.
+                count(*size)?;
+                return Ok(report.clone());
+            }
+            DEFINITION => count(1)?,
+            tag => return Err(DecodeError::InvalidTag(tag)),
+        }
+        let first_node = nodes.wrapping_sub(1);
+        let name = reader.str()?;
+        let module = reader.str()?;
+        let tyty = match reader.byte()? {
+            TYTY_STRUCT => OwnedTyTy::Struct,
+            TYTY_UNION => OwnedTyTy::Union,
+            TYTY_ENUM => OwnedTyTy::Enum(reader.str()?),
+            tag => return Err(DecodeError::InvalidTag(tag)),
+        };
+        let layout = LayoutReport {
+            size: reader.varint()?,
+            align: reader.varint()?,
+            forbidden_values: reader.varint()?,
+            padding_bits: reader.varint()?,
+            rustc_niches: reader.byte()?,
+        };
+        let mut version = [0; 4];
+        version.copy_from_slice(reader.take(4)?);
+        let compatible_with = VersionRange {
+            start: reader
+                .varint()?
+                .try_into()
+                .map_err(|_| DecodeError::IntegerOverflow)?,
+            end: reader
+                .varint()?
+                .try_into()
+                .map_err(|_| DecodeError::IntegerOverflow)?,
+        };
+        let is_pointer = match reader.byte()? {
+            0 => false,
+            1 => true,
+            tag => return Err(DecodeError::InvalidTag(tag)),
+        };
+        let field_count = reader.varint()?;
+        // Every field takes at least 3 bytes, which prevents malicious lengths from causing huge allocations.
+        let mut fields = Vec::with_capacity(field_count.min(reader.0.len() / 3));
+        for _ in 0..field_count {
+            let name = reader.str()?;
+            let offset = reader.varint()?;
+            let ty = Self::decode(reader, known, depth.wrapping_add(1), nodes)?;
+            fields.push(OwnedFieldReport { name, ty, offset });
+        }
+        let report = Self {
+            name,
+            module,
+            fields,
+            tyty,
+            layout,
+            compatible_with,
+            is_pointer,
+            version: u32::from_le_bytes(version),
+        };
+        known.push((report.clone(), nodes.wrapping_sub(first_node)));
+        Ok(report)
+    }
+}
+impl From<&OwnedTypeReport> for Vec<u8> {
+    fn from(value: &OwnedTypeReport) -> Self {
+        value.to_bytes()
+    }
+}
+impl TryFrom<&[u8]> for OwnedTypeReport {
+    type Error = DecodeError;
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(value)
+    }
+}
+
+/// The bytes that start any report encoded by [`OwnedTypeReport::to_bytes`].
+pub const MAGIC: &[u8; 4] = b"STBR";
+/// The version of the binary format produced by [`OwnedTypeReport::to_bytes`].
+///
+/// Version 2 added the layout, [`TypeReport::compatible_with`] and [`TypeReport::is_pointer`] to each report.
+pub const FORMAT_VERSION: u8 = 2;
+/// The maximum nesting of sub-reports [`OwnedTypeReport::from_bytes`] accepts.
+///
+/// This keeps crafted inputs from overflowing the stack, as decoding is recursive.
+pub const MAX_DEPTH: usize = 256;
+/// The maximum number of sub-reports [`OwnedTypeReport::from_bytes`] accepts once references are expanded.
+///
+/// Since each reference is decoded into a copy of the sub-report it refers to, this keeps crafted inputs
+/// that chain references from expanding exponentially.
+pub const MAX_NODES: usize = 1 << 18;
+const DEFINITION: u8 = 0;
+const REFERENCE: u8 = 1;
+const TYTY_STRUCT: u8 = 0;
+const TYTY_UNION: u8 = 1;
+const TYTY_ENUM: u8 = 2;
+
+fn write_varint(buffer: &mut Vec<u8>, mut value: usize) {
+    // LEB128
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buffer.push(byte);
+            return;
+        }
+        buffer.push(byte | 0x80);
+    }
+}
+fn write_str(buffer: &mut Vec<u8>, s: &str) {
+    write_varint(buffer, s.len());
+    buffer.extend_from_slice(s.as_bytes());
+}
+
+/// The sub-reports that were already encoded, indexed by hash to find duplicates quickly.
+#[derive(Default)]
+struct Known<'a> {
+    count: usize,
+    by_hash: BTreeMap<u64, Vec<(&'a OwnedTypeReport, usize)>>,
+}
+impl<'a> Known<'a> {
+    fn hash(&self, report: &OwnedTypeReport) -> u64 {
+        let mut hasher = Fnv::default();
+        report.hash(&mut hasher);
+        hasher.finish()
+    }
+    fn find(&self, report: &OwnedTypeReport, hash: u64) -> Option<usize> {
+        self.by_hash
+            .get(&hash)?
+            .iter()
+            .find_map(|(known, index)| (*known == report).then_some(*index))
+    }
+    fn insert(&mut self, report: &'a OwnedTypeReport, hash: u64) {
+        self.by_hash
+            .entry(hash)
+            .or_default()
+            .push((report, self.count));
+        self.count = self.count.wrapping_add(1);
+    }
+}
+
+/// FNV-1a, which is enough to find duplicate sub-reports.
+struct Fnv(u64);
+impl Default for Fnv {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+impl<'a> Reader<'a> {
+    #[rustversion::attr(since(1.83), const)]
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        if self.0.len() < n {
+            return Err(DecodeError::UnexpectedEof);
+        }
+        let (taken, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(taken)
+    }
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        let (&byte, rest) = self.0.split_first().ok_or(DecodeError::UnexpectedEof)?;
+        self.0 = rest;
+        Ok(byte)
+    }
+    fn varint(&mut self) -> Result<usize, DecodeError> {
+        let mut value = 0usize;
+        let mut shift = 0u32;
+        loop {
+            let byte = self.byte()?;
+            let bits = usize::from(byte & 0x7f);
+            value |= bits
+                .checked_shl(shift)
+                .filter(|shifted| shifted.checked_shr(shift) == Some(bits))
+                .ok_or(DecodeError::IntegerOverflow)?;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+            shift = shift.saturating_add(7);
+        }
+    }
+    fn str(&mut self) -> Result<String, DecodeError> {
+        let len = self.varint()?;
+        let bytes = self.take(len)?;
+        core::str::from_utf8(bytes)
+            .map(String::from)
+            .map_err(|_| DecodeError::InvalidUtf8)
+    }
+}
+
+/// The ways decoding an [`OwnedTypeReport`] can fail.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DecodeError {
+    /// The input ended before the report was fully decoded.
+    UnexpectedEof,
+    /// The input didn't start with [`MAGIC`].
+    BadMagic,
+    /// The input was encoded with an unsupported version of the format.
+    UnsupportedVersion(u8),
+    /// A tag byte had an unexpected value.
+    InvalidTag(u8),
+    /// A sub-report referred to a sub-report that wasn't decoded yet.
+    InvalidReference(usize),
+    /// An integer didn't fit in its expected type.
+    IntegerOverflow,
+    /// A string wasn't valid UTF-8.
+    InvalidUtf8,
+    /// The report was followed by this many unexpected bytes.
+    TrailingBytes(usize),
+    /// The report nested sub-reports deeper than [`MAX_DEPTH`].
+    TooDeep,
+    /// The report expanded to more than [`MAX_NODES`] sub-reports.
+    TooLarge,
+}
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeError::UnexpectedEof => write!(f, "unexpected end of input"),
+            DecodeError::BadMagic => write!(f, "input is not a stabby report"),
+            DecodeError::UnsupportedVersion(v) => {
+                write!(f, "unsupported report format version {v}")
+            }
+            DecodeError::InvalidTag(t) => write!(f, "invalid tag {t}"),
+            DecodeError::InvalidReference(i) => write!(f, "reference to unknown sub-report {i}"),
+            DecodeError::IntegerOverflow => write!(f, "integer overflow"),
+            DecodeError::InvalidUtf8 => write!(f, "string is not valid UTF-8"),
+            DecodeError::TrailingBytes(n) => write!(f, "{n} trailing bytes after report"),
+            DecodeError::TooDeep => write!(f, "sub-reports nested deeper than {MAX_DEPTH}"),
+            DecodeError::TooLarge => {
+                write!(f, "report expands to more than {MAX_NODES} sub-reports")
+            }
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IStable;
+    type Sample = crate::result::Result<crate::option::Option<crate::tuple::Tuple2<u32, u64>>, u8>;
+
+    #[test]
+    fn binary_roundtrip() {
+        let report = OwnedTypeReport::from(Sample::REPORT);
+        assert!(report.is_compatible_with(Sample::REPORT));
+        let bytes = report.to_bytes();
+        let decoded = OwnedTypeReport::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, report);
+        assert!(decoded.is_compatible(&report));
+        assert!(!decoded.is_compatible(&OwnedTypeReport::from(u32::REPORT)));
+        let mut truncated = bytes.clone();
+        while truncated.pop().is_some() {
+            assert!(OwnedTypeReport::from_bytes(&truncated).is_err());
+        }
+    }
+
+    #[test]
+    fn deep_nesting_is_rejected() {
+        // A chain of single-field definitions, nested one level deeper than allowed.
+        let mut bytes = Vec::from(MAGIC);
+        bytes.push(FORMAT_VERSION);
+        for _ in 0..=MAX_DEPTH {
+            // tag, name, module, tyty, layout, version, compatible_with, is_pointer, then 1 field named "f" at offset 0.
+            bytes.extend_from_slice(&[DEFINITION, 0, 0, TYTY_STRUCT, 0, 0, 0, 0, 0]);
+            bytes.extend_from_slice(&[0; 4]);
+            bytes.extend_from_slice(&[0, 0, 0, 1, 1, b'f', 0]);
+        }
+        assert_eq!(
+            OwnedTypeReport::from_bytes(&bytes),
+            Err(DecodeError::TooDeep)
+        );
+    }
+
+    #[test]
+    fn reference_bombs_are_rejected() {
+        // Each definition's first field is the next definition, and its second field refers back to it,
+        // which doubles the size of the decoded report with each level.
+        let chain = |levels: u8| {
+            let mut bytes = Vec::from(MAGIC);
+            bytes.push(FORMAT_VERSION);
+            for level in (0..=levels).rev() {
+                // tag, name, module, tyty, layout, version, compatible_with, is_pointer, then the field count.
+                bytes.extend_from_slice(&[DEFINITION, 0, 0, TYTY_STRUCT, 0, 0, 0, 0, 0]);
+                bytes.extend_from_slice(&[0; 4]);
+                bytes.extend_from_slice(&[0, 0, 0, if level == 0 { 0 } else { 2 }]);
+                if level != 0 {
+                    // The first field's name and offset, followed by the next definition.
+                    bytes.extend_from_slice(&[1, b'a', 0]);
+                }
+            }
+            // Definitions are indexed once complete, so the innermost one has index 0.
+            for index in 0..levels {
+                bytes.extend_from_slice(&[1, b'b', 0, REFERENCE, index]);
+            }
+            bytes
+        };
+        fn count(report: &OwnedTypeReport) -> usize {
+            report
+                .fields
+                .iter()
+                .fold(1, |sum, field| sum.wrapping_add(count(&field.ty)))
+        }
+        let report = OwnedTypeReport::from_bytes(&chain(4)).unwrap();
+        assert_eq!(count(&report), 31);
+        assert_eq!(
+            OwnedTypeReport::from_bytes(&chain(64)),
+            Err(DecodeError::TooLarge)
+        );
+    }
+
+    #[test]
+    fn duplicates_are_encoded_once() {
+        type Repeated =
+            crate::tuple::Tuple2<crate::tuple::Tuple2<u32, u64>, crate::tuple::Tuple2<u32, u64>>;
+        let report = OwnedTypeReport::from(Repeated::REPORT);
+        let bytes = report.to_bytes();
+        let single = OwnedTypeReport::from(<crate::tuple::Tuple2<u32, u64>>::REPORT).to_bytes();
+        assert!(bytes.len() < single.len() * 2);
+        assert_eq!(OwnedTypeReport::from_bytes(&bytes).unwrap(), report);
+    }
+}
@@ -66,13 +66,18 @@ where
         name: Str::new("Result"),
         module: Str::new("stabby_abi::result"),
         tyty: crate::report::TyTy::Enum(Str::new("stabby")),
+        layout: crate::report::LayoutReport::of::<Self>(),
+        compatible_with: crate::report::VersionRange::NONE,
+        is_pointer: false,
         version: 1,
         fields: crate::StableLike::new(Some(&FieldReport {
             name: Str::new("Ok"),
             ty: Ok::REPORT,
+            offset: 0,
             next_field: crate::StableLike::new(Some(&FieldReport {
                 name: Str::new("Err"),
                 ty: Err::REPORT,
+                offset: 0,
                 next_field: crate::StableLike::new(None),
             })),
         })),
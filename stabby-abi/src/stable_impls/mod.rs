@@ -553,19 +553,19 @@ unsafe impl<T: IStable> IStable for core::cell::UnsafeCell<T> {
 
 // SAFETY: Automatic checks verify this.
 unsafe impl<T: IStable> IStable for *const T {
-    same_as!(usize, "*const", T);
+    same_as!(usize, pointer "*const", T);
     type HasExactlyOneNiche = B0;
     type ContainsIndirections = B1;
 }
 // SAFETY: Automatic checks verify this.
 unsafe impl<T: IStable> IStable for *mut T {
-    same_as!(usize, "*mut", T);
+    same_as!(usize, pointer "*mut", T);
     type HasExactlyOneNiche = B0;
     type ContainsIndirections = B1;
 }
 // SAFETY: Automatic checks verify this.
 unsafe impl<T: IStable> IStable for core::ptr::NonNull<T> {
-    same_as!(core::num::NonZeroUsize, "core::ptr::NonNull", T);
+    same_as!(core::num::NonZeroUsize, pointer "core::ptr::NonNull", T);
     type HasExactlyOneNiche = B1;
     type ContainsIndirections = B1;
 }
@@ -663,13 +663,13 @@ unsafe impl IStable for core::sync::atomic::AtomicUsize {
 check!(core::sync::atomic::AtomicUsize);
 // SAFETY: Automatic checks verify this.
 unsafe impl<T: IStable> IStable for &T {
-    same_as!(core::num::NonZeroUsize, "&", T);
+    same_as!(core::num::NonZeroUsize, pointer "&", T);
     type HasExactlyOneNiche = B1;
     type ContainsIndirections = B1;
 }
 // SAFETY: Automatic checks verify this.
 unsafe impl<T: IStable> IStable for &mut T {
-    same_as!(core::num::NonZeroUsize, "&mut", T);
+    same_as!(core::num::NonZeroUsize, pointer "&mut", T);
     type HasExactlyOneNiche = B1;
     type ContainsIndirections = B1;
 }
@@ -720,8 +720,12 @@ unsafe impl<T: IStable> IStable for HasExactlyOneNiche<core::option::Option<T>,
         fields: StableLike::new(Some(&report::FieldReport {
             name: Str::new("Some"),
             ty: T::REPORT,
+            offset: 0,
             next_field: StableLike::new(None),
         })),
+        layout: crate::report::LayoutReport::of::<Self>(),
+        compatible_with: crate::report::VersionRange::NONE,
+        is_pointer: false,
         version: 0,
         tyty: report::TyTy::Enum(Str::new("rust")),
     };
@@ -765,8 +769,12 @@ unsafe impl<Ok: IStable, Err: IStable> IStable
         fields: StableLike::new(Some(&report::FieldReport {
             name: Str::new("Ok"),
             ty: Ok::REPORT,
+            offset: 0,
             next_field: StableLike::new(None),
         })),
+        layout: crate::report::LayoutReport::of::<Self>(),
+        compatible_with: crate::report::VersionRange::NONE,
+        is_pointer: false,
         version: 0,
         tyty: report::TyTy::Enum(Str::new("rust")),
     };
@@ -802,8 +810,12 @@ unsafe impl<Ok: IStable, Err: IStable, T> IStable
         fields: StableLike::new(Some(&report::FieldReport {
             name: Str::new("Err"),
             ty: Err::REPORT,
+            offset: 0,
             next_field: StableLike::new(None),
         })),
+        layout: crate::report::LayoutReport::of::<Self>(),
+        compatible_with: crate::report::VersionRange::NONE,
+        is_pointer: false,
         version: 0,
         tyty: report::TyTy::Enum(Str::new("rust")),
     };
@@ -827,12 +839,17 @@ unsafe impl<L: IStable, R: IStable> IStable for NameAggregator<L, R> {
         fields: StableLike::new(Some(&report::FieldReport {
             name: Str::new("_"),
             ty: L::REPORT,
+            offset: 0,
             next_field: StableLike::new(Some(&report::FieldReport {
                 name: Str::new("_"),
                 ty: R::REPORT,
+                offset: 0,
                 next_field: StableLike::new(None),
             })),
         })),
+        layout: crate::report::LayoutReport::of::<Self>(),
+        compatible_with: crate::report::VersionRange::NONE,
+        is_pointer: false,
         version: 0,
         tyty: report::TyTy::Struct,
     };
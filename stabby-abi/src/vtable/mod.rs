@@ -44,20 +44,172 @@ pub trait IConstConstructor<'a, Source>: 'a + Copy {
 #[cfg(all(not(stabby_default_alloc = "disabled"), feature = "test"))]
 pub use internal::{VTableRegistry, VtBtree, VtVec};
 
+/// Statistics about the vtables registered by this copy of `stabby`, as returned by [`registry_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct VTableRegistryStats {
+    /// The number of registered vtables.
+    pub count: usize,
+    /// The number of bytes allocated to store them, including their reference-counting headers.
+    pub bytes: usize,
+}
+
+/// Returns the number of vtables registered by this copy of `stabby`, and the memory they use.
+///
+/// On configurations where vtables are stored in static memory rather than a registry, this always returns zeroes.
+pub fn registry_stats() -> VTableRegistryStats {
+    #[cfg(not(stabby_default_alloc = "disabled"))]
+    if let Some(registry) = internal::registry() {
+        let mut stats = VTableRegistryStats::default();
+        registry.for_each(&mut |vtable| {
+            stats.count = stats.count.saturating_add(1);
+            stats.bytes = stats
+                .bytes
+                .saturating_add(internal::VTable::allocation_size(vtable));
+        });
+        return stats;
+    }
+    VTableRegistryStats::default()
+}
+
+/// Returns the base address of the library (or executable) that `address` belongs to.
+///
+/// Registered vtables are tagged with the library their first entry (the `drop` function of the type they were built
+/// for) belongs to, which is the library that constructed the `Dyn` objects using them.
+///
+/// Libraries can only be found on Unix platforms with the `std` feature enabled: elsewhere, this returns a null pointer.
+pub fn library_of(address: *const ()) -> *const () {
+    #[cfg(all(feature = "std", unix))]
+    {
+        use core::ffi::{c_char, c_int, c_void};
+        #[repr(C)]
+        struct DlInfo {
+            fname: *const c_char,
+            fbase: *mut c_void,
+            sname: *const c_char,
+            saddr: *mut c_void,
+        }
+        extern "C" {
+            fn dladdr(address: *const c_void, info: *mut DlInfo) -> c_int;
+        }
+        let mut info = DlInfo {
+            fname: core::ptr::null(),
+            fbase: core::ptr::null_mut(),
+            sname: core::ptr::null(),
+            saddr: core::ptr::null_mut(),
+        };
+        // SAFETY: `dladdr` only writes to `info`, and accepts addresses that don't belong to any library.
+        if unsafe { dladdr(address.cast(), &mut info) } != 0 {
+            return info.fbase.cast_const().cast();
+        }
+    }
+    let _ = address;
+    core::ptr::null()
+}
+
+/// Removes the vtables registered for `library` (as identified by [`library_of`]) from the registry of this copy of
+/// `stabby`, returning how many were removed.
+///
+/// A copy of `stabby` linked statically into a library only holds that library's vtables, while a copy shared between
+/// several libraries holds all of theirs: when unloading a library, `stabby::libloading` calls this on the library's
+/// copy through the [`UNREGISTER_LIBRARY_SYMBOL`] it exports, as well as on its own.
+///
+/// # Safety
+/// No `Dyn` object constructed by `library` may be used after this is called, as if it had been unloaded.
+pub unsafe fn unregister_library(library: *const ()) -> usize {
+    #[cfg(not(stabby_default_alloc = "disabled"))]
+    if let Some(registry) = internal::registry() {
+        // SAFETY: guaranteed by the caller.
+        return unsafe { registry.remove(&mut |_, registered| registered == library) };
+    }
+    0
+}
+
+/// The name of the symbol through which every library linking `stabby` exports [`unregister_library`], with the
+/// signature `unsafe extern "C" fn(*const ()) -> usize`.
+///
+/// It ends with the major version of `stabby`, so that several versions may be linked into the same library.
+pub const UNREGISTER_LIBRARY_SYMBOL: &str = concat!(
+    "stabby_unregister_library_v",
+    env!("CARGO_PKG_VERSION_MAJOR")
+);
+
+/// [`unregister_library`], exported as [`UNREGISTER_LIBRARY_SYMBOL`].
+///
+/// # Safety
+/// See [`unregister_library`].
+#[export_name = concat!("stabby_unregister_library_v", env!("CARGO_PKG_VERSION_MAJOR"))]
+pub unsafe extern "C" fn stabby_unregister_library(library: *const ()) -> usize {
+    // SAFETY: guaranteed by the caller.
+    unsafe { unregister_library(library) }
+}
+
 #[cfg(not(stabby_default_alloc = "disabled"))]
 pub(crate) mod internal {
-    use crate::alloc::{boxed::BoxedSlice, collections::arc_btree::AtomicArcBTreeSet};
+    use crate::alloc::{
+        collections::arc_btree::{ArcBTreeSet, AtomicArcBTreeSet},
+        sync::ArcSlice,
+        AllocPrefix,
+    };
     use core::ptr::NonNull;
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-    pub struct VTable(&'static [*const ()]);
+    /// A vtable interned in a registry.
+    ///
+    /// VTables are reference-counted so that removing them from a registry only frees them once no snapshot of the
+    /// registry may still be reading them.
+    ///
+    /// They are tagged with the library they were registered for, as found by [`library_of`](super::library_of).
+    #[derive(Clone, Debug)]
+    pub struct VTable {
+        vtable: ArcSlice<*const (), DefaultAllocator>,
+        library: *const (),
+    }
+    impl VTable {
+        const fn as_slice(&self) -> &[*const ()] {
+            self.vtable.as_slice()
+        }
+        const fn as_ptr(&self) -> NonNull<*const ()> {
+            // SAFETY: slices' pointers are never null.
+            unsafe { NonNull::new_unchecked(self.as_slice().as_ptr().cast_mut()) }
+        }
+        /// The size of the allocation used to store `vtable` in a registry.
+        pub(crate) const fn allocation_size(vtable: &[*const ()]) -> usize {
+            core::mem::size_of::<AllocPrefix<DefaultAllocator>>()
+                .saturating_add(core::mem::size_of::<*const ()>().saturating_mul(vtable.len()))
+        }
+    }
+    impl From<&[*const ()]> for VTable {
+        fn from(value: &[*const ()]) -> Self {
+            Self {
+                vtable: ArcSlice::from(value),
+                library: value
+                    .first()
+                    .map_or(core::ptr::null(), |drop| super::library_of(drop.cast())),
+            }
+        }
+    }
+    impl PartialEq for VTable {
+        fn eq(&self, other: &Self) -> bool {
+            self.as_slice().eq(other.as_slice())
+        }
+    }
+    impl Eq for VTable {}
+    impl PartialOrd for VTable {
+        fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+    impl Ord for VTable {
+        fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+            self.as_slice().cmp(other.as_slice())
+        }
+    }
     impl PartialOrd<&[*const ()]> for VTable {
         fn partial_cmp(&self, other: &&[*const ()]) -> Option<core::cmp::Ordering> {
-            Some(self.0.cmp(*other))
+            Some(self.as_slice().cmp(*other))
         }
     }
     impl PartialEq<&[*const ()]> for VTable {
         fn eq(&self, other: &&[*const ()]) -> bool {
-            self.0.eq(*other)
+            self.as_slice().eq(*other)
         }
     }
     // SAFETY: VTables are always `Send + Sync`
@@ -81,7 +233,10 @@ pub(crate) mod internal {
         /// Inserts a raw vtable in the registry.
         fn insert(&self, vtable: &[*const ()]) -> NonNull<*const ()>;
         /// Inserts a vtable in the registry.
-        fn insert_typed<'a, Vt: Copy>(&self, vtable: &Vt) -> &'a Vt {
+        fn insert_typed<'a, Vt: Copy>(&self, vtable: &Vt) -> &'a Vt
+        where
+            Self: Sized,
+        {
             unsafe {
                 let vtable = core::slice::from_raw_parts(
                     (vtable as *const Vt).cast(),
@@ -98,11 +253,21 @@ pub(crate) mod internal {
                 vt
             }
         }
+        /// Removes the vtables for which `remove` returns `true` from the registry, returning how many were removed.
+        ///
+        /// `remove` is passed each vtable, along with the library it was registered for.
+        ///
+        /// # Safety
+        /// The removed vtables may be freed as soon as this returns: the references previously returned by
+        /// [`VTableRegistry::insert`] for them must not be used anymore.
+        unsafe fn remove(&self, remove: &mut dyn FnMut(&[*const ()], *const ()) -> bool) -> usize;
+        /// Calls `f` on each of the vtables in the registry.
+        fn for_each(&self, f: &mut dyn FnMut(&[*const ()]));
     }
     impl VTableRegistry for VtVec {
         fn insert(&self, vtable: &[*const ()]) -> NonNull<*const ()> {
             let mut search_start = 0;
-            let mut allocated_slice: Option<BoxedSlice<_, DefaultAllocator>> = None;
+            let mut allocated: Option<VTable> = None;
             let mut vtables = self.load(core::sync::atomic::Ordering::SeqCst);
             loop {
                 let vts = match vtables.as_ref() {
@@ -113,32 +278,29 @@ pub(crate) mod internal {
                         .iter()
                         .find(|e| **e == vtable)
                     {
-                        Some(vt) => {
-                            // SAFETY: since `vt.0` is a reference, it is guaranteed to be non-null.
-                            return unsafe { NonNull::new_unchecked(vt.0.as_ptr().cast_mut()) };
-                        }
+                        Some(vt) => return vt.as_ptr(),
                         None => vts,
                     },
                 };
-                let avt = allocated_slice.unwrap_or_else(|| BoxedSlice::from(vtable));
-                // SAFETY::`avt` will be leaked if inserting `vt` succeeds.
-                let vt = unsafe { core::mem::transmute::<&[_], &'static [_]>(avt.as_slice()) };
-                allocated_slice = Some(avt);
+                let vt = allocated.get_or_insert_with(|| VTable::from(vtable));
                 if let Err(updated) =
                     self.is(vtables.as_ref(), core::sync::atomic::Ordering::SeqCst)
                 {
                     vtables = updated;
+                    search_start = 0;
                     continue;
                 }
                 let mut vec = Vec::with_capacity(vts.len().wrapping_add(1));
-                vec.copy_extend(vts);
-                vec.push(VTable(vt));
+                vec.extend(vts.iter().cloned());
+                vec.push(vt.clone());
                 if let Err(updated) =
                     self.is(vtables.as_ref(), core::sync::atomic::Ordering::SeqCst)
                 {
                     vtables = updated;
+                    search_start = 0;
                     continue;
                 }
+                let searched = vts.len();
                 let vec = Some(crate::alloc::sync::Arc::new(vec));
                 match self.compare_exchange(
                     vtables.as_ref(),
@@ -146,62 +308,93 @@ pub(crate) mod internal {
                     core::sync::atomic::Ordering::SeqCst,
                     core::sync::atomic::Ordering::SeqCst,
                 ) {
-                    Ok(_) => {
-                        core::mem::forget(allocated_slice);
-                        return unsafe { NonNull::new_unchecked(vt.as_ptr().cast_mut()) };
-                    }
+                    Ok(_) => return vt.as_ptr(),
                     Err(new_vtables) => {
-                        search_start = vts.len();
+                        // Vtables are only appended, unless some were removed in the meantime.
+                        search_start = match new_vtables.as_ref() {
+                            Some(new) if new.len() > searched => searched,
+                            _ => 0,
+                        };
                         vtables = new_vtables;
                     }
                 }
             }
         }
+        unsafe fn remove(&self, remove: &mut dyn FnMut(&[*const ()], *const ()) -> bool) -> usize {
+            let mut vtables = self.load(core::sync::atomic::Ordering::SeqCst);
+            loop {
+                let Some(vts) = vtables.as_ref() else {
+                    return 0;
+                };
+                let mut kept = Vec::with_capacity(vts.len());
+                kept.extend(
+                    vts.iter()
+                        .filter(|vt| !remove(vt.as_slice(), vt.library))
+                        .cloned(),
+                );
+                let removed = vts.len().saturating_sub(kept.len());
+                if removed == 0 {
+                    return 0;
+                }
+                match self.compare_exchange(
+                    vtables.as_ref(),
+                    Some(crate::alloc::sync::Arc::new(kept)),
+                    core::sync::atomic::Ordering::SeqCst,
+                    core::sync::atomic::Ordering::SeqCst,
+                ) {
+                    Ok(_) => return removed,
+                    Err(new_vtables) => vtables = new_vtables,
+                }
+            }
+        }
+        fn for_each(&self, f: &mut dyn FnMut(&[*const ()])) {
+            if let Some(vts) = self.load(core::sync::atomic::Ordering::SeqCst).as_ref() {
+                for vt in vts.iter() {
+                    f(vt.as_slice())
+                }
+            }
+        }
     }
-    #[allow(unknown_lints)]
-    #[allow(clippy::missing_transmute_annotations)]
     impl<const SIZE: usize> VTableRegistry for VtBtree<SIZE> {
         fn insert(&self, vtable: &[*const ()]) -> NonNull<*const ()> {
             let mut ret = None;
-            let mut allocated_slice: Option<BoxedSlice<_, DefaultAllocator>> = None;
+            let mut allocated: Option<VTable> = None;
             self.edit(|tree| {
                 let mut tree = tree.clone();
                 if let Some(vt) = tree.get(&vtable) {
-                    ret = unsafe { Some(NonNull::new_unchecked(vt.0.as_ptr().cast_mut())) };
+                    ret = Some(vt.as_ptr());
                     return tree;
                 }
-                let vt = match &allocated_slice {
-                    Some(vt) => unsafe { core::mem::transmute(vt.as_slice()) },
-                    None => {
-                        let vt = BoxedSlice::from(vtable);
-                        let slice = unsafe { core::mem::transmute(vt.as_slice()) };
-                        allocated_slice = Some(vt);
-                        slice
-                    }
-                };
-                tree.insert(vt);
+                let vt = allocated.get_or_insert_with(|| VTable::from(vtable));
+                ret = Some(vt.as_ptr());
+                tree.insert(vt.clone());
                 tree
             });
-            if ret.is_none() {
-                let ret = &mut ret;
-                self.get(&vtable, move |vt| {
-                    let start = unsafe {
-                        NonNull::new_unchecked(
-                            vt.expect("VTable should've been inserted by now")
-                                .0
-                                .as_ptr()
-                                .cast_mut(),
-                        )
-                    };
-                    if let Some(allocated) = allocated_slice {
-                        if allocated.slice.start.ptr == start {
-                            core::mem::forget(allocated);
-                        }
+            // SAFETY: `edit` calls its closure at least once, which always sets `ret`.
+            unsafe { ret.unwrap_unchecked() }
+        }
+        unsafe fn remove(&self, remove: &mut dyn FnMut(&[*const ()], *const ()) -> bool) -> usize {
+            let mut removed = 0usize;
+            self.edit(|tree| {
+                removed = 0;
+                let mut kept = ArcBTreeSet::from_alloc(DefaultAllocator::default());
+                tree.for_each(|vt| {
+                    if remove(vt.as_slice(), vt.library) {
+                        removed = removed.saturating_add(1);
+                    } else {
+                        kept.insert(vt.clone());
                     }
-                    *ret = Some(start);
                 });
-            }
-            unsafe { ret.unwrap_unchecked() }
+                if removed == 0 {
+                    tree
+                } else {
+                    kept
+                }
+            });
+            removed
+        }
+        fn for_each(&self, f: &mut dyn FnMut(&[*const ()])) {
+            self.load().for_each(|vt| f(vt.as_slice()))
         }
     }
     #[cfg(stabby_vtables = "vec")]
@@ -216,6 +409,99 @@ pub(crate) mod internal {
     #[rustversion::nightly]
     #[cfg(stabby_vtables = "btree")]
     pub(crate) static VTABLES: AtomicArcBTreeSet<VTable, false, 5> = AtomicArcBTreeSet::new();
+
+    /// The registry vtables are inserted into, if this configuration uses one.
+    #[cfg(any(stabby_vtables = "vec", stabby_vtables = "btree", not(stabby_vtables)))]
+    #[rustversion::all(not(nightly), since(1.78.0))]
+    pub(crate) fn registry() -> Option<&'static dyn VTableRegistry> {
+        Some(&VTABLES)
+    }
+    /// The registry vtables are inserted into, if this configuration uses one.
+    #[rustversion::nightly]
+    #[cfg(stabby_vtables = "btree")]
+    pub(crate) fn registry() -> Option<&'static dyn VTableRegistry> {
+        Some(&VTABLES)
+    }
+    /// The registry vtables are inserted into, if this configuration uses one.
+    #[cfg(not(any(stabby_vtables = "vec", stabby_vtables = "btree", not(stabby_vtables))))]
+    #[rustversion::all(not(nightly), since(1.78.0))]
+    pub(crate) const fn registry() -> Option<&'static dyn VTableRegistry> {
+        None
+    }
+    /// The registry vtables are inserted into, if this configuration uses one.
+    #[rustversion::nightly]
+    #[cfg(not(stabby_vtables = "btree"))]
+    pub(crate) const fn registry() -> Option<&'static dyn VTableRegistry> {
+        None
+    }
+    /// The registry vtables are inserted into, if this configuration uses one.
+    #[rustversion::all(not(nightly), before(1.78.0))]
+    pub(crate) const fn registry() -> Option<&'static dyn VTableRegistry> {
+        None
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn check_registry(registry: &dyn VTableRegistry) {
+            let a = [check_registry as *const (), 4usize as *const ()];
+            let b = [6usize as *const (), 8usize as *const ()];
+            let inserted = registry.insert(&a);
+            assert_eq!(registry.insert(&a), inserted);
+            assert_ne!(inserted, NonNull::from(&a).cast());
+            registry.insert(&b);
+            let mut count = 0usize;
+            registry.for_each(&mut |_| count = count.saturating_add(1));
+            assert_eq!(count, 2);
+            // SAFETY: the pointers returned for `b` are never used after this.
+            let removed = unsafe {
+                registry.remove(&mut |vt, library| {
+                    let first = vt.first().copied().unwrap_or(core::ptr::null());
+                    assert_eq!(library, crate::vtable::library_of(first));
+                    vt == b
+                })
+            };
+            assert_eq!(removed, 1);
+            let mut remaining = Vec::new();
+            registry.for_each(&mut |vt| remaining.push(vt.to_vec()));
+            assert_eq!(remaining.len(), 1);
+            assert_eq!(
+                remaining.first().map(|vt| vt.as_slice()),
+                Some(a.as_slice())
+            );
+            assert_eq!(unsafe { registry.remove(&mut |vt, _| vt == b) }, 0);
+        }
+
+        #[cfg(all(feature = "std", unix))]
+        #[test]
+        fn libraries() {
+            let here = crate::vtable::library_of(check_registry as *const ());
+            assert!(!here.is_null());
+            assert_eq!(crate::vtable::library_of(libraries as *const ()), here);
+            assert!(crate::vtable::library_of(8usize as *const ()).is_null());
+            let registry = VtVec::new(None);
+            registry.insert(&[check_registry as *const ()]);
+            registry.insert(&[8usize as *const ()]);
+            // SAFETY: the pointers returned by `insert` are never used.
+            assert_eq!(
+                unsafe { registry.remove(&mut |_, library| library == here) },
+                1
+            );
+            let mut remaining = 0usize;
+            registry.for_each(&mut |vt| {
+                assert_eq!(vt, [8usize as *const ()]);
+                remaining = remaining.saturating_add(1);
+            });
+            assert_eq!(remaining, 1);
+        }
+
+        #[test]
+        fn registries() {
+            check_registry(&VtVec::new(None));
+            check_registry(&VtBtree::<5>::new());
+        }
+    }
 }
 
 #[cfg(all(
@@ -295,9 +295,32 @@ impl core::fmt::Display for CanarySpec {
     }
 }
 
+/// The build properties encoded by the canaries, as a `stabby::abi::checked_import::CanaryDescriptor`.
+pub fn canary_descriptor() -> proc_macro2::TokenStream {
+    let st = crate::tl_mod();
+    let rustc = format!(
+        "{RUSTC_MAJOR}.{RUSTC_MINOR}.{RUSTC_PATCH} ({})",
+        RUSTC_COMMIT.get(..8).unwrap_or(RUSTC_COMMIT)
+    );
+    let debug = DEBUG.parse::<bool>().expect("Couldn't parse DEBUG as bool");
+    quote! {
+        #st::checked_import::CanaryDescriptor {
+            rustc: #st::str::Str::new(#rustc),
+            opt_level: #st::str::Str::new(#OPT_LEVEL),
+            debug: #debug,
+            target: #st::str::Str::new(#TARGET),
+            num_jobs: #st::str::Str::new(#NUM_JOBS),
+            host: #st::str::Str::new(#HOST),
+        }
+    }
+}
+
 fn export_canaried(fn_spec: syn::ItemFn) -> proc_macro2::TokenStream {
+    let st = crate::tl_mod();
     let canaries = (0..=5)
         .map(|i| quote::format_ident!("{}{}", fn_spec.sig.ident, CanarySpec(1 << i).to_string()));
+    let descriptor = quote::format_ident!("{}_canary_descriptor_v1", fn_spec.sig.ident);
+    let descriptor_value = canary_descriptor();
     quote! {
         #[no_mangle]
         #[allow(improper_ctypes_definitions)]
@@ -306,6 +329,11 @@ fn export_canaried(fn_spec: syn::ItemFn) -> proc_macro2::TokenStream {
             #[no_mangle]
             pub extern "C" fn #canaries() {}
         )*
+        #[no_mangle]
+        pub extern "C" fn #descriptor() -> &'static #st::checked_import::CanaryDescriptor {
+            const DESCRIPTOR: #st::checked_import::CanaryDescriptor = #descriptor_value;
+            &DESCRIPTOR
+        }
     }
 }
 
@@ -320,7 +348,7 @@ fn export_with_report(fn_spec: syn::ItemFn) -> proc_macro2::TokenStream {
         ..
     } = fn_spec.sig.clone();
     let st = crate::tl_mod();
-    let stabbied = quote::format_ident!("{ident}_stabbied_v3");
+    let stabbied = quote::format_ident!("{ident}_stabbied_v4");
     let report = quote::format_ident!("{stabbied}_report");
     let def = stabby(Attrs::default(), fn_spec);
     let signature = quote!(#asyncness #unsafety #abi fn(#inputs) #output);
@@ -328,7 +356,7 @@ fn export_with_report(fn_spec: syn::ItemFn) -> proc_macro2::TokenStream {
         Attrs::default(),
         syn::parse2(quote::quote! {
             extern "C" fn #stabbied(report: &#st::report::TypeReport) -> Option<#signature> {
-                <#signature as #st::IStable>::REPORT.is_compatible(report).then_some(#ident)
+                <#signature as #st::IStable>::REPORT.is_evolution_compatible(report).then_some(#ident)
             }
         })
         .unwrap_or_else(|e| panic!("Couldn't parse {stabbied}'s expansion: {e:?}")),
@@ -352,6 +380,48 @@ fn export_with_report(fn_spec: syn::ItemFn) -> proc_macro2::TokenStream {
     )
 }
 
+fn export_static(static_spec: syn::ItemStatic) -> proc_macro2::TokenStream {
+    let syn::ItemStatic {
+        ident,
+        ty,
+        mutability,
+        ..
+    } = &static_spec;
+    if !matches!(mutability, syn::StaticMutability::None) {
+        panic!("`stabby::export` doesn't support `static mut`, as it can't be shared safely")
+    }
+    let st = crate::tl_mod();
+    let stabbied = quote::format_ident!("{ident}_stabbied_v4");
+    let report = quote::format_ident!("{stabbied}_report");
+    let signature = quote!(&'static #ty);
+    let stabbied = stabby(
+        Attrs::default(),
+        syn::parse2(quote::quote! {
+            extern "C" fn #stabbied(report: &#st::report::TypeReport) -> Option<#signature> {
+                <#signature as #st::IStable>::REPORT.is_evolution_compatible(report).then_some(&#ident)
+            }
+        })
+        .unwrap_or_else(|e| panic!("Couldn't parse {stabbied}'s expansion: {e:?}")),
+    );
+    let report = stabby(
+        Attrs::default(),
+        syn::parse2(quote! {
+            extern "C" fn #report() -> &'static #st::report::TypeReport {
+                <#signature as #st::IStable>::REPORT
+            }
+        })
+        .unwrap_or_else(|e| panic!("Couldn't parse {report}'s expansion: {e:?}")),
+    );
+    quote::quote!(
+        #[no_mangle]
+        #static_spec
+        #[no_mangle]
+        #stabbied
+        #[no_mangle]
+        #report
+    )
+}
+
 struct ExportArgs {
     canaried: bool,
 }
@@ -371,16 +441,63 @@ impl syn::parse::Parse for ExportArgs {
     }
 }
 
-pub fn export(
-    macro_attrs: proc_macro::TokenStream,
-    fn_spec: syn::ItemFn,
-) -> proc_macro2::TokenStream {
+pub fn export(macro_attrs: proc_macro::TokenStream, item: syn::Item) -> proc_macro2::TokenStream {
     let args =
         syn::parse::<ExportArgs>(macro_attrs).expect("Couldn't parse `export(...)`'s content");
-    if args.canaried {
-        export_canaried(fn_spec)
-    } else {
-        export_with_report(fn_spec)
+    match item {
+        syn::Item::Fn(fn_spec) if args.canaried => export_canaried(fn_spec),
+        syn::Item::Fn(fn_spec) => export_with_report(fn_spec),
+        syn::Item::Static(_) if args.canaried => {
+            panic!(
+                "`stabby::export(canaries)` doesn't support statics, use `stabby::export` instead"
+            )
+        }
+        syn::Item::Static(static_spec) => export_static(static_spec),
+        item => panic!(
+            "`stabby::export` only supports functions and statics: {}",
+            quote!(#item)
+        ),
+    }
+}
+
+pub fn plugin(
+    macro_attrs: proc_macro::TokenStream,
+    static_spec: syn::ItemStatic,
+) -> proc_macro2::TokenStream {
+    let mut name = quote!(::core::env!("CARGO_PKG_NAME"));
+    let args = syn::parse::Parser::parse(
+        syn::punctuated::Punctuated::<IdentEqStr, syn::Token!(,)>::parse_terminated,
+        macro_attrs,
+    )
+    .expect("Couldn't parse `plugin(...)`'s content");
+    for IdentEqStr { ident, str } in args {
+        if ident == "name" {
+            name = quote!(#str);
+        } else {
+            panic!("Unsupported argument to `stabby::plugin`: `name` is the only currently supported arg.")
+        }
+    }
+    let syn::ItemStatic {
+        ident,
+        ty,
+        mutability,
+        ..
+    } = &static_spec;
+    if !matches!(mutability, syn::StaticMutability::None) {
+        panic!("`stabby::plugin` doesn't support `static mut`, as it can't be shared safely")
+    }
+    let st = crate::tl_mod();
+    let symbol = quote::format_ident!("stabby_plugin_v1");
+    quote! {
+        #static_spec
+        const _: () = {
+            static DESCRIPTOR: #st::plugin::PluginDescriptor =
+                #st::plugin::PluginDescriptor::new::<#ty>(#name, &#ident);
+            #[no_mangle]
+            pub extern "C" fn #symbol() -> &'static #st::plugin::PluginDescriptor {
+                &DESCRIPTOR
+            }
+        };
     }
 }
 
@@ -401,18 +518,35 @@ impl syn::parse::Parse for IdentEqStr {
 
 struct ImportArgs {
     canaries: Option<CanarySpec>,
+    dynamic: Option<syn::Ident>,
     link_args: proc_macro2::TokenStream,
 }
 impl syn::parse::Parse for ImportArgs {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let mut args = ImportArgs {
             canaries: None,
+            dynamic: None,
             link_args: quote!(),
         };
+        if input.peek(syn::Ident) && (input.peek2(syn::Token!(,)) || input.peek2(syn::parse::End)) {
+            let ident: syn::Ident = input.parse()?;
+            if ident != "dynamic" {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "expected `dynamic`, or `key = \"value\"` pairs",
+                ));
+            }
+            args.dynamic = Some(syn::Ident::new("Library", ident.span()));
+            if !input.is_empty() {
+                input.parse::<syn::Token!(,)>()?;
+            }
+        }
         for IdentEqStr { ident, str } in
             input.parse_terminated(IdentEqStr::parse, syn::Token!(,))?
         {
-            if ident == "canaries" {
+            if ident == "dynamic" {
+                args.dynamic = Some(str.parse()?)
+            } else if ident == "canaries" {
                 args.canaries = Some(
                     CanarySpec::from_str(&str.value())
                         .unwrap_or_else(|e| panic!("Failed to parse CanarySpec: {e}")),
@@ -425,14 +559,170 @@ impl syn::parse::Parse for ImportArgs {
     }
 }
 
+/// Generates a struct that loads and checks all of the extern block's symbols from a library at runtime.
+/// The attributes of an `extern` block or item that still make sense once it's turned into Rust code: linkage
+/// attributes such as `#[link]` or `#[link_name]` are dropped.
+fn forwarded_attrs(attrs: &[syn::Attribute]) -> impl Iterator<Item = &syn::Attribute> {
+    attrs.iter().filter(|attr| {
+        ["doc", "cfg", "allow", "warn", "deny", "expect"]
+            .iter()
+            .any(|name| attr.path().is_ident(name))
+    })
+}
+fn cfg_attrs(attrs: &[syn::Attribute]) -> impl Iterator<Item = &syn::Attribute> {
+    attrs.iter().filter(|attr| attr.path().is_ident("cfg"))
+}
+
+fn import_dynamic(name: syn::Ident, fn_decl: syn::ItemForeignMod) -> proc_macro2::TokenStream {
+    let syn::ItemForeignMod {
+        attrs, abi, items, ..
+    } = &fn_decl;
+    let attrs = forwarded_attrs(attrs);
+    let stabby = crate::stabby_mod();
+    let mut fields = Vec::new();
+    let mut accessors = Vec::new();
+    let mut loads = Vec::new();
+    let mut field_ids = Vec::new();
+    for item in items {
+        let (attrs, vis, ident, ty, accessor_ty, accessor) = match item {
+            syn::ForeignItem::Fn(syn::ForeignItemFn {
+                attrs,
+                vis,
+                sig:
+                    syn::Signature {
+                        ident,
+                        inputs,
+                        output,
+                        asyncness,
+                        unsafety,
+                        generics,
+                        ..
+                    },
+                ..
+            }) => {
+                assert!(
+                    asyncness.is_none(),
+                    "the async keyword is not supported in `stabby::import(dynamic)`"
+                );
+                let ty = quote!(#unsafety #abi fn #generics(#inputs)#output);
+                (
+                    attrs,
+                    vis,
+                    ident,
+                    ty.clone(),
+                    quote!(&#ty),
+                    quote!(&self.#ident),
+                )
+            }
+            syn::ForeignItem::Static(syn::ForeignItemStatic {
+                attrs,
+                vis,
+                ident,
+                ty,
+                mutability,
+                ..
+            }) => {
+                assert!(
+                    matches!(mutability, syn::StaticMutability::None),
+                    "`static mut` can't be imported by stabby, as it can't be shared safely"
+                );
+                (
+                    attrs,
+                    vis,
+                    ident,
+                    quote!(&'static #ty),
+                    quote!(&#ty),
+                    quote!(self.#ident),
+                )
+            }
+            _ => panic!(
+                "Unsupported item in a `stabby::import(dynamic)`: {}",
+                quote!(#item)
+            ),
+        };
+        let symbol = syn::LitByteStr::new(ident.to_string().as_bytes(), ident.span());
+        let cfgs = cfg_attrs(attrs).collect::<Vec<_>>();
+        let attrs = forwarded_attrs(attrs);
+        fields.push(quote! {
+            #(#cfgs)*
+            #ident: #ty
+        });
+        // The symbols are only lent out for as long as `self` keeps the library loaded.
+        accessors.push(quote! {
+            #(#attrs)*
+            #vis fn #ident(&self) -> #accessor_ty {
+                #accessor
+            }
+        });
+        loads.push(quote! {
+            #(#cfgs)*
+            let #ident = *#stabby::libloading::StabbyLibrary::get_stabbied::<#ty>(&*library, #symbol)?;
+        });
+        field_ids.push(quote!(#(#cfgs)* #ident));
+    }
+    quote! {
+        #(#attrs)*
+        #[allow(non_snake_case)]
+        pub struct #name {
+            #(#fields,)*
+            __stabby_library: #stabby::libloading::StabbyLibraryHandle,
+        }
+        #[allow(non_snake_case)]
+        impl #name {
+            #(#accessors)*
+            /// Loads the library at `path`, checking the reports of all of its symbols.
+            ///
+            /// # Safety
+            /// Loading a library may run arbitrary code, and checking the reports calls foreign code.
+            ///
+            /// # Errors
+            /// If the library couldn't be loaded, or any of its symbols is missing or mismatched.
+            pub unsafe fn load(
+                path: impl AsRef<::std::ffi::OsStr>,
+            ) -> ::core::result::Result<Self, #stabby::libloading::LoadError> {
+                Self::from_library(#stabby::libloading::StabbyLibraryHandle::open(path)?)
+            }
+            /// Loads all of the symbols from `library`, checking their reports.
+            ///
+            /// # Safety
+            /// Checking the reports calls foreign code.
+            ///
+            /// # Errors
+            /// If any of the symbols is missing or mismatched.
+            #[allow(non_snake_case)]
+            pub unsafe fn from_library(
+                library: #stabby::libloading::StabbyLibraryHandle,
+            ) -> ::core::result::Result<Self, #stabby::libloading::LoadError> {
+                #(#loads)*
+                Ok(Self {
+                    #(#field_ids,)*
+                    __stabby_library: library,
+                })
+            }
+            /// The library the symbols were loaded from, which stays loaded for as long as `self` is alive.
+            pub fn library(&self) -> &#stabby::libloading::StabbyLibraryHandle {
+                &self.__stabby_library
+            }
+        }
+    }
+}
+
 pub fn import(
     macro_attrs: proc_macro::TokenStream,
     fn_decl: syn::ItemForeignMod,
 ) -> proc_macro2::TokenStream {
     let ImportArgs {
         canaries,
+        dynamic,
         link_args,
     } = syn::parse(macro_attrs).expect("Couldn't parse `import(...)`'s content");
+    if let Some(name) = dynamic {
+        assert!(
+            canaries.is_none() && link_args.is_empty(),
+            "`stabby::import(dynamic)` doesn't support canaries nor link arguments"
+        );
+        return import_dynamic(name, fn_decl);
+    }
     let syn::ItemForeignMod {
         attrs, abi, items, ..
     } = &fn_decl;
@@ -472,7 +762,7 @@ pub fn import(
                         });
                         intern_ids.push(quote!(#vis use #modid::#canaried as #ident;))
                     },
-                    syn::ForeignItem::Static(_) => todo!("stabby doesn't support importing statics yet. Move this to a separate extern block, and use `#[link]`"),
+                    syn::ForeignItem::Static(_) => panic!("statics can't be imported with canaries, as they can only be exported with `stabby::export`: remove `canaries` from this extern block's `stabby::import`"),
                     syn::ForeignItem::Type(_) => {externs.push(quote!(#item))},
                     _ => todo!("Unsupported item in a stabby import: {}", quote!(#item)),
                 }
@@ -483,7 +773,7 @@ pub fn import(
                 match item {
                     syn::ForeignItem::Fn(syn::ForeignItemFn { sig: syn::Signature { ident,  inputs, output, asyncness, unsafety, generics, .. }, vis, ..}) => {
                         assert!(asyncness.is_none(), "the async keyword is not supported in non-canaried extern blocks");
-                        let stabbied = quote::format_ident!("{ident}_stabbied_v3");
+                        let stabbied = quote::format_ident!("{ident}_stabbied_v4");
                         let report = quote::format_ident!("{stabbied}_report");
                         let signature = quote!(#unsafety #abi fn #generics(#inputs)#output);
                         externs.push(quote!{
@@ -493,20 +783,37 @@ pub fn import(
                         interns.push(quote!{
                             #[allow(non_upper_case_globals)]
                             pub static #ident: #st::checked_import::CheckedImport<#signature> = #st::checked_import::CheckedImport::new(#stabbied, #report, <#signature as #st::IStable>::REPORT);
+                            #st::__register_import!(#ident);
+                        });
+                        intern_ids.push(quote!(#vis use #modid::#ident;));
+                    },
+                    syn::ForeignItem::Static(syn::ForeignItemStatic { mutability: syn::StaticMutability::Mut(_), .. }) => panic!("`static mut` can't be imported by stabby, as it can't be shared safely: declare it as an immutable `static` instead"),
+                    syn::ForeignItem::Static(syn::ForeignItemStatic { ident, ty, vis, .. }) => {
+                        let stabbied = quote::format_ident!("{ident}_stabbied_v4");
+                        let report = quote::format_ident!("{stabbied}_report");
+                        let signature = quote!(&'static #ty);
+                        externs.push(quote!{
+                            fn #report() -> &'static #st::report::TypeReport;
+                            fn #stabbied(report: & #st::report::TypeReport) -> Option<#signature>;
+                        });
+                        interns.push(quote!{
+                            #[allow(non_upper_case_globals)]
+                            pub static #ident: #st::checked_import::CheckedStatic<#ty> = #st::checked_import::CheckedStatic::new(#stabbied, #report, <#signature as #st::IStable>::REPORT);
+                            #st::__register_import!(#ident);
                         });
                         intern_ids.push(quote!(#vis use #modid::#ident;));
                     },
-                    syn::ForeignItem::Static(_) => todo!("stabby doesn't support importing statics yet. Move this to a separate extern block, and use `#[link]`"),
                     syn::ForeignItem::Type(_) => {externs.push(quote!(#item))},
                     _ => todo!("Unsupported item in a stabby import: {}", quote!(#item)),
                 }
             }
         }
     }
+    let link = (!link_args.is_empty()).then(|| quote!(#[link(#link_args)]));
     quote! {
         mod #modid {
             #(#attrs)*
-            #[link(#link_args)]
+            #link
             #abi {
                 #(#externs)*
             }
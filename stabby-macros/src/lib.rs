@@ -69,6 +69,19 @@ pub(crate) fn tl_mod() -> proc_macro2::TokenStream {
     }
 }
 
+/// The path to the `stabby` crate, for items that aren't part of `stabby-abi`.
+pub(crate) fn stabby_mod() -> proc_macro2::TokenStream {
+    match proc_macro_crate::crate_name("stabby")
+        .expect("Couldn't find `stabby` in your dependencies")
+    {
+        proc_macro_crate::FoundCrate::Itself => quote!(crate),
+        proc_macro_crate::FoundCrate::Name(crate_name) => {
+            let crate_name = Ident::new(&crate_name, Span::call_site());
+            quote!(::#crate_name)
+        }
+    }
+}
+
 /// The lifeblood of stabby. [Click for the tutorial](https://docs.rs/stabby/latest/stabby/_tutorial_/index.html)
 /// # Panics
 /// If stabby doesn't support some of passed code
@@ -306,6 +319,7 @@ pub(crate) struct Report {
     version: u32,
     module: proc_macro2::TokenStream,
     pub tyty: Tyty,
+    pub compatible_with: (u32, u32),
 }
 impl Report {
     pub fn r#struct(
@@ -323,6 +337,7 @@ impl Report {
                 module
             },
             tyty: Tyty::Struct,
+            compatible_with: (0, 0),
         }
     }
     pub fn r#enum(name: impl Into<String>, version: u32, module: proc_macro2::TokenStream) -> Self {
@@ -336,6 +351,7 @@ impl Report {
                 module
             },
             tyty: Tyty::Enum(enums::Repr::Stabby),
+            compatible_with: (0, 0),
         }
     }
     pub fn r#union(
@@ -353,6 +369,7 @@ impl Report {
                 module
             },
             tyty: Tyty::Union,
+            compatible_with: (0, 0),
         }
     }
     pub fn add_field(&mut self, name: String, ty: impl Into<Type>) {
@@ -428,51 +445,98 @@ impl Report {
         }
     }
 }
-impl ToTokens for Report {
-    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+impl Report {
+    /// A type whose layout is that of the reported type, used for reports that aren't attached to an actual type
+    /// (such as those of the variants of enums).
+    fn layout(&self) -> proc_macro2::TokenStream {
+        let st = crate::tl_mod();
+        let fields = self.fields.iter().map(|(_, ty)| match ty {
+            Type::Syn(ty) => quote!(#ty),
+            Type::Report(re) => re.layout(),
+        });
+        match self.tyty {
+            Tyty::Struct => {
+                let mut layout = None;
+                for ty in fields {
+                    layout = Some(layout.map_or_else(
+                        || quote!(#ty),
+                        |layout| quote!(#st::FieldPair<#layout, #ty>),
+                    ));
+                }
+                layout.map_or_else(|| quote!(()), |layout| quote!(#st::Struct<#layout>))
+            }
+            Tyty::Union | Tyty::Enum(_) => {
+                let mut layout = quote!(());
+                for ty in fields {
+                    layout = quote!(#st::Union<#ty, #layout>);
+                }
+                layout
+            }
+        }
+    }
+    /// Generates the report, using `layout` as the type whose layout is reported.
+    fn to_tokens_with_layout(&self, layout: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
         let st = crate::tl_mod();
         let mut fields = quote!(None);
-
+        // Struct fields are laid out one after the other, while variants and union fields all start at 0.
+        let mut end = quote!(0);
         for (name, ty) in &self.fields {
-            fields = match ty {
-                Type::Syn(ty) => quote! {
-                    Some(& #st::report::FieldReport {
-                        name: #st::str::Str::new(#name),
-                        ty: <#ty as #st::IStable>::REPORT,
-                        next_field: #st::StableLike::new(#fields)
-                    })
-                },
-                Type::Report(re) => quote! {
-                    Some(& #st::report::FieldReport {
-                        name: #st::str::Str::new(#name),
-                        ty: &#re,
-                        next_field: #st::StableLike::new(#fields)
-                    })
-                },
-            }
+            let (ty, report) = match ty {
+                Type::Syn(ty) => (quote!(#ty), quote!(<#ty as #st::IStable>::REPORT)),
+                Type::Report(re) => {
+                    let layout = re.layout();
+                    let report = re.to_tokens_with_layout(layout.clone());
+                    (layout, quote!(&#report))
+                }
+            };
+            let offset = match self.tyty {
+                Tyty::Struct => {
+                    let offset = quote!(#st::report::aligned_offset(#end, <<#ty as #st::IStable>::Align as #st::Unsigned>::USIZE));
+                    end = quote!((#offset + <<#ty as #st::IStable>::Size as #st::Unsigned>::USIZE));
+                    offset
+                }
+                Tyty::Union | Tyty::Enum(_) => quote!(0),
+            };
+            fields = quote! {
+                Some(& #st::report::FieldReport {
+                    name: #st::str::Str::new(#name),
+                    ty: #report,
+                    next_field: #st::StableLike::new(#fields),
+                    offset: #offset,
+                })
+            };
         }
         let Self {
             name,
             version,
             tyty,
             module,
+            compatible_with: (start, end),
             ..
         } = self;
-        tokens.extend(quote!(#st::report::TypeReport {
+        quote!(#st::report::TypeReport {
             name: #st::str::Str::new(#name),
             module: #st::str::Str::new(#module),
             fields: unsafe{#st::StableLike::new(#fields)},
+            layout: #st::report::LayoutReport::of::<#layout>(),
+            compatible_with: #st::report::VersionRange { start: #start, end: #end },
+            is_pointer: false,
             version: #version,
             tyty: #tyty,
-        }));
+        })
+    }
+}
+impl ToTokens for Report {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        tokens.extend(self.to_tokens_with_layout(quote!(Self)));
     }
 }
 
 /// # Panics
 /// If stabby doesn't support some of passed code
 #[proc_macro_attribute]
-pub fn export(attrs: TokenStream, fn_spec: TokenStream) -> TokenStream {
-    crate::functions::export(attrs, syn::parse(fn_spec).unwrap()).into()
+pub fn export(attrs: TokenStream, item: TokenStream) -> TokenStream {
+    crate::functions::export(attrs, syn::parse(item).unwrap()).into()
 }
 
 /// # Panics
@@ -482,6 +546,13 @@ pub fn import(attrs: TokenStream, fn_spec: TokenStream) -> TokenStream {
     crate::functions::import(attrs, syn::parse(fn_spec).unwrap()).into()
 }
 
+/// # Panics
+/// If stabby doesn't support some of passed code
+#[proc_macro_attribute]
+pub fn plugin(attrs: TokenStream, item: TokenStream) -> TokenStream {
+    crate::functions::plugin(attrs, syn::parse(item).unwrap()).into()
+}
+
 #[proc_macro]
 pub fn canary_suffixes(_: TokenStream) -> TokenStream {
     let mut stream = quote::quote!();
@@ -493,6 +564,11 @@ pub fn canary_suffixes(_: TokenStream) -> TokenStream {
     stream.into()
 }
 
+#[proc_macro]
+pub fn canary_descriptor(_: TokenStream) -> TokenStream {
+    functions::canary_descriptor().into()
+}
+
 trait Unself {
     fn unself(&self, this: &syn::Ident) -> Self;
 }
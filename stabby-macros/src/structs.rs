@@ -22,6 +22,7 @@ struct Args {
     optimize: bool,
     version: u32,
     module: proc_macro2::TokenStream,
+    compatible_with: (u32, u32),
 }
 impl syn::parse::Parse for Args {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
@@ -29,6 +30,7 @@ impl syn::parse::Parse for Args {
             optimize: true,
             version: 0,
             module: quote!(),
+            compatible_with: (0, 0),
         };
         while !input.is_empty() {
             let ident: Ident = input.parse()?;
@@ -38,6 +40,11 @@ impl syn::parse::Parse for Args {
                     input.parse::<syn::Token!(=)>()?;
                     this.version = input.parse::<syn::LitInt>()?.to_string().parse().unwrap();
                 }
+                "compatible_with" => {
+                    input.parse::<syn::Token!(=)>()?;
+                    let range = input.parse::<syn::LitStr>()?;
+                    this.compatible_with = parse_version_range(&range)?;
+                }
                 "module" => {
                     input.parse::<syn::Token!(=)>()?;
                     while !input.is_empty() {
@@ -55,6 +62,29 @@ impl syn::parse::Parse for Args {
         Ok(this)
     }
 }
+/// Parses `"M..N"` or `"M..=N"` into a half-open range of versions.
+fn parse_version_range(range: &syn::LitStr) -> syn::Result<(u32, u32)> {
+    let value = range.value();
+    let error = || {
+        syn::Error::new(
+            range.span(),
+            "`compatible_with` expects a range of versions, such as \"1..3\" or \"1..=2\"",
+        )
+    };
+    let parse = |s: &str| s.trim().parse::<u32>().map_err(|_| error());
+    let (start, end) = match value.split_once("..=") {
+        Some((start, end)) => (parse(start)?, parse(end)?.checked_add(1).ok_or_else(error)?),
+        None => {
+            let (start, end) = value.split_once("..").ok_or_else(error)?;
+            (parse(start)?, parse(end)?)
+        }
+    };
+    if start >= end {
+        return Err(error());
+    }
+    Ok((start, end))
+}
+
 #[derive(Copy, Clone)]
 enum AllowedRepr {
     C,
@@ -109,6 +139,7 @@ pub fn stabby(
         mut optimize,
         version,
         module,
+        compatible_with,
     } = syn::parse(stabby_attrs.clone()).unwrap();
     optimize &= generics.params.is_empty();
     let st = crate::tl_mod();
@@ -118,6 +149,7 @@ pub fn stabby(
     let clauses = where_clause.as_ref().map(|w| &w.predicates);
     let mut layout = None;
     let mut report = crate::Report::r#struct(ident.to_string(), version, module);
+    report.compatible_with = compatible_with;
     let repr = attrs.iter().find_map(|attr| {
         attr.meta.require_list().ok().and_then(|meta| {
             match (meta.path.is_ident("repr"), &meta.delimiter) {
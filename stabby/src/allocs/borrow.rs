@@ -14,7 +14,10 @@
 
 //! Stable Cows!
 
-use core::borrow::Borrow;
+use core::{borrow::Borrow, fmt, ops::Deref};
+
+#[cfg(not(feature = "std"))]
+use alloc_rs::borrow::ToOwned;
 
 use crate::{
     abi::{IDeterminantProvider, IStable},
@@ -22,12 +25,17 @@ use crate::{
     string::String,
 };
 
+/// An ABI-stable equivalent to [`std::borrow::Cow`](https://doc.rust-lang.org/std/borrow/enum.Cow.html).
+///
+/// Since [`IStable`] types are [`Sized`], this can't represent `Cow<'a, str>`: use [`CowStr`] instead.
 #[crate::stabby]
 pub enum Cow<'a, Borrowed: IStable + ToOwned>
 where
     <Borrowed as ToOwned>::Owned: IStable,
 {
+    /// Borrowed data.
     Borrowed(&'a Borrowed),
+    /// Owned data.
     Owned(<Borrowed as ToOwned>::Owned),
 }
 
@@ -36,12 +44,25 @@ where
     <Borrowed as ToOwned>::Owned: IStable,
     for<'a> &'a Borrowed: IDeterminantProvider<<Borrowed as ToOwned>::Owned>,
 {
+    /// Extracts the owned data, cloning it if it was borrowed.
     pub fn into_owned(self) -> <Borrowed as ToOwned>::Owned {
         self.match_owned(|b| b.to_owned(), |o| o)
     }
-    pub fn to_owned(self) -> Cow<'static, Borrowed> {
+    /// Detaches `self` from the data it borrowed, cloning it if necessary.
+    pub fn to_owned(self) -> Cow<'static, Borrowed>
+    where
+        Borrowed: 'static,
+    {
         Cow::Owned(self.into_owned())
     }
+    /// Returns `true` if the data is borrowed.
+    pub fn is_borrowed(&self) -> bool {
+        self.match_ref(|_| true, |_| false)
+    }
+    /// Returns `true` if the data is owned.
+    pub fn is_owned(&self) -> bool {
+        !self.is_borrowed()
+    }
 }
 impl<Borrowed: IStable + ToOwned> Borrow<Borrowed> for Cow<'_, Borrowed>
 where
@@ -49,12 +70,206 @@ where
     for<'a> &'a Borrowed: IDeterminantProvider<<Borrowed as ToOwned>::Owned>,
 {
     fn borrow(&self) -> &Borrowed {
+        self
+    }
+}
+impl<Borrowed: IStable + ToOwned> Deref for Cow<'_, Borrowed>
+where
+    <Borrowed as ToOwned>::Owned: IStable,
+    for<'a> &'a Borrowed: IDeterminantProvider<<Borrowed as ToOwned>::Owned>,
+{
+    type Target = Borrowed;
+    fn deref(&self) -> &Borrowed {
         self.match_ref(|&b| b, |o| o.borrow())
     }
 }
+impl<Borrowed: IStable + ToOwned> AsRef<Borrowed> for Cow<'_, Borrowed>
+where
+    <Borrowed as ToOwned>::Owned: IStable,
+    for<'a> &'a Borrowed: IDeterminantProvider<<Borrowed as ToOwned>::Owned>,
+{
+    fn as_ref(&self) -> &Borrowed {
+        self
+    }
+}
+impl<Borrowed: IStable + ToOwned> Clone for Cow<'_, Borrowed>
+where
+    <Borrowed as ToOwned>::Owned: IStable,
+    for<'a> &'a Borrowed: IDeterminantProvider<<Borrowed as ToOwned>::Owned>,
+{
+    fn clone(&self) -> Self {
+        self.match_ref(|&b| Cow::Borrowed(b), |o| Cow::Owned(o.borrow().to_owned()))
+    }
+}
+impl<Borrowed: IStable + ToOwned + fmt::Debug> fmt::Debug for Cow<'_, Borrowed>
+where
+    <Borrowed as ToOwned>::Owned: IStable,
+    for<'a> &'a Borrowed: IDeterminantProvider<<Borrowed as ToOwned>::Owned>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+impl<Borrowed: IStable + ToOwned + fmt::Display> fmt::Display for Cow<'_, Borrowed>
+where
+    <Borrowed as ToOwned>::Owned: IStable,
+    for<'a> &'a Borrowed: IDeterminantProvider<<Borrowed as ToOwned>::Owned>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+impl<'b, Borrowed: IStable + ToOwned + PartialEq> PartialEq<Cow<'b, Borrowed>> for Cow<'_, Borrowed>
+where
+    <Borrowed as ToOwned>::Owned: IStable,
+    for<'a> &'a Borrowed: IDeterminantProvider<<Borrowed as ToOwned>::Owned>,
+{
+    fn eq(&self, other: &Cow<'b, Borrowed>) -> bool {
+        **self == **other
+    }
+}
+impl<Borrowed: IStable + ToOwned + Eq> Eq for Cow<'_, Borrowed>
+where
+    <Borrowed as ToOwned>::Owned: IStable,
+    for<'a> &'a Borrowed: IDeterminantProvider<<Borrowed as ToOwned>::Owned>,
+{
+}
+impl<'a, Borrowed: IStable + ToOwned> From<&'a Borrowed> for Cow<'a, Borrowed>
+where
+    <Borrowed as ToOwned>::Owned: IStable,
+    for<'b> &'b Borrowed: IDeterminantProvider<<Borrowed as ToOwned>::Owned>,
+{
+    fn from(value: &'a Borrowed) -> Self {
+        Cow::Borrowed(value)
+    }
+}
 
+/// An ABI-stable equivalent to `std::borrow::Cow<'a, str>`.
 #[crate::stabby]
 pub enum CowStr<'a> {
+    /// A borrowed string.
     Borrowed(Str<'a>),
+    /// An owned string.
     Owned(String),
 }
+impl CowStr<'_> {
+    /// Extracts the owned string, cloning it if it was borrowed.
+    pub fn into_owned(self) -> String {
+        self.match_owned(String::from, |o| o)
+    }
+    /// Detaches `self` from the string it borrowed, cloning it if necessary.
+    pub fn to_owned(self) -> CowStr<'static> {
+        CowStr::Owned(self.into_owned())
+    }
+    /// Returns `true` if the string is borrowed.
+    pub fn is_borrowed(&self) -> bool {
+        self.match_ref(|_| true, |_| false)
+    }
+    /// Returns `true` if the string is owned.
+    pub fn is_owned(&self) -> bool {
+        !self.is_borrowed()
+    }
+}
+impl Deref for CowStr<'_> {
+    type Target = str;
+    fn deref(&self) -> &str {
+        self.match_ref(|b| b.as_str(), |o| o)
+    }
+}
+impl Borrow<str> for CowStr<'_> {
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+impl AsRef<str> for CowStr<'_> {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+impl Clone for CowStr<'_> {
+    fn clone(&self) -> Self {
+        self.match_ref(|&b| CowStr::Borrowed(b), |o| CowStr::Owned(o.clone()))
+    }
+}
+impl fmt::Debug for CowStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+impl fmt::Display for CowStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+impl<Rhs: AsRef<str> + ?Sized> PartialEq<Rhs> for CowStr<'_> {
+    fn eq(&self, other: &Rhs) -> bool {
+        **self == *other.as_ref()
+    }
+}
+impl Eq for CowStr<'_> {}
+impl core::hash::Hash for CowStr<'_> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+impl<'a> From<&'a str> for CowStr<'a> {
+    fn from(value: &'a str) -> Self {
+        CowStr::Borrowed(value.into())
+    }
+}
+impl<'a> From<Str<'a>> for CowStr<'a> {
+    fn from(value: Str<'a>) -> Self {
+        CowStr::Borrowed(value)
+    }
+}
+impl From<String> for CowStr<'_> {
+    fn from(value: String) -> Self {
+        CowStr::Owned(value)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    impl<Borrowed: IStable + ToOwned + Serialize> Serialize for Cow<'_, Borrowed>
+    where
+        <Borrowed as ToOwned>::Owned: IStable,
+        for<'a> &'a Borrowed: IDeterminantProvider<<Borrowed as ToOwned>::Owned>,
+    {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            (**self).serialize(serializer)
+        }
+    }
+    impl<'de, Borrowed: IStable + ToOwned> Deserialize<'de> for Cow<'_, Borrowed>
+    where
+        <Borrowed as ToOwned>::Owned: IStable + Deserialize<'de>,
+        for<'a> &'a Borrowed: IDeterminantProvider<<Borrowed as ToOwned>::Owned>,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            <Borrowed as ToOwned>::Owned::deserialize(deserializer).map(Cow::Owned)
+        }
+    }
+    impl Serialize for CowStr<'_> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            serializer.serialize_str(self)
+        }
+    }
+    impl<'de> Deserialize<'de> for CowStr<'_> {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            String::deserialize(deserializer).map(CowStr::Owned)
+        }
+    }
+}
@@ -22,10 +22,13 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![cfg_attr(not(doctest), doc = include_str!("../README.md"))]
 
+#[cfg(feature = "alloc-rs")]
+extern crate alloc as alloc_rs;
 extern crate core;
 
 pub use stabby_abi::{
-    assert_unchecked, dynptr, export, import, stabby, unreachable_unchecked, vtmacro as vtable,
+    assert_unchecked, dynptr, export, import, plugin, stabby, unreachable_unchecked,
+    vtmacro as vtable,
 };
 
 pub use stabby_abi as abi;
@@ -70,6 +73,41 @@ pub mod libloading;
 /// ABI-stable representations of durations and instants.
 pub mod time;
 
+/// ABI-stable key-value maps.
+pub mod map;
+
+mod allocs {
+    #[cfg(feature = "alloc-rs")]
+    pub mod borrow;
+}
+/// ABI-stable clone-on-write smart pointers.
+#[cfg(feature = "alloc-rs")]
+pub use allocs::borrow;
+
+/// Checks every symbol imported with `#[stabby::import]` (without `canaries`), returning all the mismatches found.
+///
+/// Imports are otherwise checked lazily, when first dereferenced: calling this at startup lets you refuse to start
+/// rather than panic later on.
+///
+/// Imports are registered before `main` is called on platforms that support it (those using ELF binaries, which
+/// also unregister them when their binary is unloaded).
+/// On other platforms, this function has nothing to check.
+///
+/// # Errors
+/// Returns every [`ReportMismatch`](crate::abi::checked_import::ReportMismatch) found.
+#[cfg(feature = "std")]
+pub fn verify_imports() -> Result<(), std::vec::Vec<crate::abi::checked_import::ReportMismatch>> {
+    let mismatches = crate::abi::checked_import::imports()
+        .iter()
+        .filter_map(|import| import.check().err())
+        .collect::<std::vec::Vec<_>>();
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(mismatches)
+    }
+}
+
 /// Like [`std::format`], but returning an ABI-stable [`String`](crate::string::String)
 #[macro_export]
 macro_rules! format {
@@ -85,8 +123,15 @@ macro_rules! format {
 pub mod _tutorial_ {}
 // #[cfg(test)]
 mod tests {
+    #[cfg(all(test, feature = "std"))]
+    mod collections;
     mod enums;
     mod layouts;
+    #[cfg(all(test, feature = "libloading", target_os = "linux"))]
+    mod libloading;
+    mod reports;
+    #[cfg(test)]
+    mod statics;
     mod traits;
     mod regressions {
         mod issue_103;
@@ -18,19 +18,24 @@ pub trait StabbyLibrary {
     ///
     /// The library must have a symbol with the appropriate type named the same way, and marked with `#[stabby::export]`.
     ///
+    /// Statics exported with `#[stabby::export]` are loaded as `&'static T`.
+    ///
     /// # Safety
     /// Since this function calls foreign code, it is inherently unsafe.
     ///
     /// # Errors
-    /// If the symbol is not found OR reflection indicated an ABI-mismatch.
+    /// If the symbol is not found ([`LoadError::SymbolNotFound`]) OR reflection indicated an ABI-mismatch ([`LoadError::ReportMismatch`]).
     ///
     /// The symbol missing can mean that the library was compiled with a different version of stabby, or that the symbol was not exported with `#[stabby::export]`.
     ///
-    /// In case of ABI-mismatch, the error will contain a message indicating the expected and found type layouts.
+    /// In case of ABI-mismatch, the error will be a [`LoadError::ReportMismatch`], listing the differences between the expected and found type layouts.
+    ///
+    /// Types that opted into it with `#[stabby::stabby(compatible_with = "M..N")]` may differ between both sides when passed by reference,
+    /// as described in [`TypeReport::is_evolution_compatible`](crate::abi::report::TypeReport::is_evolution_compatible).
     unsafe fn get_stabbied<'a, T: crate::IStable>(
         &'a self,
         symbol: &[u8],
-    ) -> Result<Symbol<'a, T>, Box<dyn std::error::Error + Send + Sync>>;
+    ) -> Result<Symbol<'a, T>, LoadError>;
     /// Gets `symbol` from the library, using stabby's canaries to check for compatibility.
     ///
     /// The library must have a symbol with the appropriate type named the same way, and marked with `#[stabby::export(canaries)]`.
@@ -42,11 +47,58 @@ pub trait StabbyLibrary {
     /// ABI than expected (although the canaries should greatly reduce that risk).
     ///
     /// # Errors
-    /// If the symbol is not found OR the canaries were not found.
+    /// If the symbol is not found ([`LoadError::SymbolNotFound`]) OR the canaries were not found ([`LoadError::CanaryMissing`]).
     unsafe fn get_canaried<'a, T>(
         &'a self,
         symbol: &[u8],
-    ) -> Result<libloading::Symbol<'a, T>, Box<dyn std::error::Error + Send + Sync>>;
+    ) -> Result<libloading::Symbol<'a, T>, LoadError>;
+    /// Lists the symbols exported with `#[stabby::export]` by the library, along with their reports.
+    ///
+    /// This is currently only supported on Linux, where the library's dynamic symbol table is read from its file.
+    ///
+    /// # Safety
+    /// Since this function calls foreign code, it is inherently unsafe.
+    ///
+    /// # Errors
+    /// If the platform is not supported ([`std::io::ErrorKind::Unsupported`]), or if the library's file couldn't be read.
+    unsafe fn exports(&self) -> std::io::Result<Vec<Export<'_>>>;
+    /// Gets the plugin the library exported with `#[stabby::plugin]`, checking its ABI version and report in a single call.
+    ///
+    /// # Safety
+    /// Since this function calls foreign code, it is inherently unsafe.
+    ///
+    /// # Errors
+    /// If the library isn't a plugin ([`LoadError::SymbolNotFound`]), if it was built with an incompatible version of
+    /// `stabby`'s ABI ([`LoadError::AbiVersionMismatch`]), or if its report doesn't match `T`'s ([`LoadError::ReportMismatch`]).
+    unsafe fn get_plugin<T: crate::IStable + Sync + 'static>(
+        &self,
+    ) -> Result<Plugin<'_, T>, LoadError>;
+}
+/// A plugin exported with `#[stabby::plugin]`, as returned by [`StabbyLibrary::get_plugin`].
+#[derive(Debug, Clone, Copy)]
+pub struct Plugin<'a, T> {
+    /// The name of the plugin.
+    pub name: &'a str,
+    /// The version of `stabby`'s ABI the plugin was built with.
+    pub abi_version: crate::abi::plugin::AbiVersion,
+    /// The plugin's static.
+    pub api: &'a T,
+}
+impl<T> core::ops::Deref for Plugin<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        self.api
+    }
+}
+/// A symbol exported with `#[stabby::export]`, as listed by [`StabbyLibrary::exports`].
+#[derive(Debug, Clone)]
+pub struct Export<'a> {
+    /// The name of the symbol, as it should be passed to [`StabbyLibrary::get_stabbied`].
+    pub name: String,
+    /// The report of the symbol's type, as exported by the library.
+    ///
+    /// Functions are reported as function pointers, and statics as `&'static T`.
+    pub report: &'a crate::abi::report::TypeReport,
 }
 /// A symbol bound to a library's lifetime.
 pub struct Symbol<'a, T> {
@@ -59,11 +111,574 @@ impl<T> core::ops::Deref for Symbol<'_, T> {
         &self.inner
     }
 }
+
+/// A handle to a [`libloading::Library`] that keeps it loaded for as long as any symbol obtained through it is alive.
+///
+/// Unlike those obtained through [`StabbyLibrary`], the symbols it provides don't borrow the library, making them
+/// easy to store next to it in long-lived structures.
+#[derive(Debug, Clone)]
+pub struct StabbyLibraryHandle {
+    library: std::sync::Arc<LoadedLibrary>,
+    generation: u64,
+}
+impl From<libloading::Library> for StabbyLibraryHandle {
+    fn from(library: libloading::Library) -> Self {
+        Self {
+            library: std::sync::Arc::new(LoadedLibrary::new(library, None)),
+            generation: 0,
+        }
+    }
+}
+impl core::ops::Deref for StabbyLibraryHandle {
+    type Target = libloading::Library;
+    fn deref(&self) -> &Self::Target {
+        &self.library
+    }
+}
+
+/// A loaded library, along with the copy it was loaded from if it was loaded by a [`ReloadableLibrary`].
+#[derive(Debug)]
+struct LoadedLibrary {
+    library: core::mem::ManuallyDrop<libloading::Library>,
+    shadow: Option<std::path::PathBuf>,
+    /// The address the library is loaded at, which tags the vtables it registered.
+    base: Option<usize>,
+}
+impl LoadedLibrary {
+    fn new(library: libloading::Library, shadow: Option<std::path::PathBuf>) -> Self {
+        #[cfg(target_os = "linux")]
+        let base = elf::library_base(&library).ok().map(|base| base as usize);
+        #[cfg(not(target_os = "linux"))]
+        let base = None;
+        Self {
+            library: core::mem::ManuallyDrop::new(library),
+            shadow,
+            base,
+        }
+    }
+}
+impl core::ops::Deref for LoadedLibrary {
+    type Target = libloading::Library;
+    fn deref(&self) -> &Self::Target {
+        &self.library
+    }
+}
+impl Drop for LoadedLibrary {
+    fn drop(&mut self) {
+        let base = self.base.map(|base| base as *const ());
+        if let (Some(base), Some(_)) = (base, &self.shadow) {
+            // Copies are only loaded by their `ReloadableLibrary`, so closing this handle will unload it: no `Dyn`
+            // object it constructed may be used anymore.
+            // SAFETY: `UNREGISTER_LIBRARY_SYMBOL` is exported with this signature by every copy of `stabby`.
+            if let Ok(unregister) = unsafe {
+                self.library
+                    .get::<unsafe extern "C" fn(*const ()) -> usize>(
+                        UNREGISTER_LIBRARY_SYMBOL.as_bytes(),
+                    )
+            } {
+                // SAFETY: see above.
+                unsafe { unregister(base) };
+            }
+        }
+        // SAFETY: `library` is never used again.
+        unsafe { core::mem::ManuallyDrop::drop(&mut self.library) };
+        if let Some(base) = base {
+            // Nothing is mapped at `base` anymore once the library is actually unloaded, in which case the vtables it
+            // registered in our own copy of `stabby` (if it shares it) point to unloaded code.
+            if crate::abi::vtable::library_of(base) != base {
+                // SAFETY: the library is unloaded.
+                unsafe { crate::abi::vtable::unregister_library(base) };
+            }
+        }
+        if let Some(shadow) = &self.shadow {
+            // The copy only needs to outlive the library, since some platforms keep it open while it's loaded.
+            let _ = std::fs::remove_file(shadow);
+        }
+    }
+}
+impl StabbyLibraryHandle {
+    /// Loads the library at `path`.
+    ///
+    /// # Safety
+    /// Loading a library may run arbitrary code, see [`libloading::Library::new`].
+    ///
+    /// # Errors
+    /// If the library couldn't be loaded ([`LoadError::LibraryLoad`]).
+    pub unsafe fn open(path: impl AsRef<std::ffi::OsStr>) -> Result<Self, LoadError> {
+        libloading::Library::new(path.as_ref())
+            .map(Self::from)
+            .map_err(LoadError::LibraryLoad)
+    }
+    /// The generation of the library, as counted by the [`ReloadableLibrary`] that loaded it.
+    ///
+    /// Libraries that weren't loaded through a [`ReloadableLibrary`] are always at generation 0.
+    pub const fn generation(&self) -> u64 {
+        self.generation
+    }
+    /// The number of handles, symbols and bound values that currently keep the library loaded, including `self`.
+    pub fn strong_count(&self) -> usize {
+        std::sync::Arc::strong_count(&self.library)
+    }
+    /// Gets `symbol` from the library, using stabby's reports to check for compatibility.
+    ///
+    /// The returned symbol keeps the library loaded until it is dropped.
+    ///
+    /// # Safety
+    /// Since this function calls foreign code, it is inherently unsafe.
+    ///
+    /// # Errors
+    /// See [`StabbyLibrary::get_stabbied`].
+    pub unsafe fn get_stabbied_owned<T: crate::IStable>(
+        &self,
+        symbol: &[u8],
+    ) -> Result<OwnedSymbol<T>, LoadError> {
+        let Symbol { inner, .. } = self.library.get_stabbied::<T>(symbol)?;
+        Ok(self.bind(inner))
+    }
+    /// Gets the plugin the library exported with `#[stabby::plugin]`, checking its ABI version and report.
+    ///
+    /// The returned plugin keeps the library loaded until it is dropped.
+    ///
+    /// # Safety
+    /// Since this function calls foreign code, it is inherently unsafe.
+    ///
+    /// # Errors
+    /// See [`StabbyLibrary::get_plugin`].
+    pub unsafe fn get_plugin_owned<T: crate::IStable + Sync + 'static>(
+        &self,
+    ) -> Result<OwnedSymbol<Plugin<'static, T>>, LoadError> {
+        let Plugin {
+            name,
+            abi_version,
+            api,
+        } = self.library.get_plugin::<T>()?;
+        // SAFETY: the returned `OwnedSymbol` keeps the library loaded for as long as these references are reachable.
+        let plugin = unsafe {
+            Plugin {
+                name: &*(name as *const str),
+                abi_version,
+                api: &*(api as *const T),
+            }
+        };
+        Ok(self.bind(plugin))
+    }
+    /// Binds `value` to the library, keeping it loaded until the returned [`OwnedSymbol`] is dropped.
+    ///
+    /// This is typically used for values that were constructed by the library, such as `Dyn` objects whose vtables
+    /// live in its memory.
+    pub fn bind<T>(&self, value: T) -> OwnedSymbol<T> {
+        OwnedSymbol {
+            inner: value,
+            library: self.library.clone(),
+            generation: self.generation,
+        }
+    }
+}
+
+/// A value that keeps the library it was obtained from loaded, as returned by [`StabbyLibraryHandle`].
+#[derive(Debug, Clone)]
+pub struct OwnedSymbol<T> {
+    // Declared before `library` so that it's dropped before the library may be unloaded.
+    inner: T,
+    library: std::sync::Arc<LoadedLibrary>,
+    generation: u64,
+}
+impl<T> OwnedSymbol<T> {
+    /// Transforms the value, keeping the library loaded for as long as the result is alive.
+    ///
+    /// This lets you call a function from the library and keep its result bound to it.
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> OwnedSymbol<U> {
+        let Self {
+            inner,
+            library,
+            generation,
+        } = self;
+        OwnedSymbol {
+            inner: f(inner),
+            library,
+            generation,
+        }
+    }
+    /// A handle to the library the value was obtained from.
+    pub fn library(&self) -> StabbyLibraryHandle {
+        StabbyLibraryHandle {
+            library: self.library.clone(),
+            generation: self.generation,
+        }
+    }
+    /// The generation of the library the value was obtained from.
+    ///
+    /// Comparing it to [`ReloadableLibrary::generation`] tells you whether a newer version of the library is available.
+    pub const fn generation(&self) -> u64 {
+        self.generation
+    }
+}
+impl<T> core::ops::Deref for OwnedSymbol<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+impl<T> core::ops::DerefMut for OwnedSymbol<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+/// A library that can be reloaded from its path, typically after it was rebuilt.
+///
+/// Every load bumps a generation counter, which is attached to the [`StabbyLibraryHandle`]s and [`OwnedSymbol`]s
+/// obtained through it. Since those keep the library loaded, the [`ReloadableLibrary`] refuses to unload it while
+/// any of them is alive, so that no `Dyn` object bound with [`StabbyLibraryHandle::bind`] may outlive the code its
+/// vtable points to.
+///
+/// On Linux, right before a generation is unloaded, the vtables it registered are removed from its copy of `stabby`'s registry,
+/// so that reloading it doesn't leak memory: make sure every `Dyn` object it constructed is bound to it.
+///
+/// Since most platforms only load a given path once per process, each generation is loaded from its own copy of the
+/// library, which is deleted once that generation is unloaded: a reload always reads the library from the disk again,
+/// even if something else (another handle, or the platform's loader) still keeps the previous image loaded.
+///
+/// `Dyn` objects returned by the library's functions can be tied to their generation by calling those functions
+/// through [`OwnedSymbol::map`], so that they're counted by [`ReloadableLibrary::live`].
+#[derive(Debug)]
+pub struct ReloadableLibrary {
+    path: std::ffi::OsString,
+    current: std::sync::Mutex<Option<StabbyLibraryHandle>>,
+    generation: std::sync::atomic::AtomicU64,
+}
+/// Distinguishes the copies made by the [`ReloadableLibrary`]s of this process.
+static SHADOW_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+impl ReloadableLibrary {
+    /// Loads the library at `path` as its first generation.
+    ///
+    /// # Safety
+    /// Loading a library may run arbitrary code, see [`libloading::Library::new`].
+    ///
+    /// # Errors
+    /// If the library couldn't be copied ([`LoadError::LibraryCopy`]) or loaded ([`LoadError::LibraryLoad`]).
+    pub unsafe fn open(path: impl AsRef<std::ffi::OsStr>) -> Result<Self, LoadError> {
+        let path = path.as_ref().to_owned();
+        let library = Self::load(&path, 1)?;
+        Ok(Self {
+            path,
+            current: std::sync::Mutex::new(Some(library)),
+            generation: std::sync::atomic::AtomicU64::new(1),
+        })
+    }
+    /// Loads `generation` from a fresh copy of the library at `path`.
+    unsafe fn load(
+        path: &std::ffi::OsStr,
+        generation: u64,
+    ) -> Result<StabbyLibraryHandle, LoadError> {
+        let path = std::path::Path::new(path);
+        let id = SHADOW_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let mut name = std::ffi::OsString::from(format!("stabby-{}-{id}-", std::process::id()));
+        name.push(path.file_name().unwrap_or_default());
+        let shadow = std::env::temp_dir().join(name);
+        std::fs::copy(path, &shadow).map_err(LoadError::LibraryCopy)?;
+        match libloading::Library::new(&shadow) {
+            Ok(library) => Ok(StabbyLibraryHandle {
+                library: std::sync::Arc::new(LoadedLibrary::new(library, Some(shadow))),
+                generation,
+            }),
+            Err(e) => {
+                let _ = std::fs::remove_file(&shadow);
+                Err(LoadError::LibraryLoad(e))
+            }
+        }
+    }
+    /// The path the library is loaded from.
+    pub fn path(&self) -> &std::ffi::OsStr {
+        &self.path
+    }
+    /// The current generation, starting at 1 and incremented by each successful reload.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(std::sync::atomic::Ordering::Acquire)
+    }
+    /// A handle to the current generation of the library.
+    ///
+    /// Returns `None` if the last reload unloaded the previous generation, but failed to load the new one.
+    pub fn current(&self) -> Option<StabbyLibraryHandle> {
+        self.lock().clone()
+    }
+    /// The number of handles, symbols and bound values that keep the current generation loaded.
+    pub fn live(&self) -> usize {
+        self.lock()
+            .as_ref()
+            .map_or(0, |current| current.strong_count().saturating_sub(1))
+    }
+    /// Unloads the current generation and loads the library from its path again.
+    ///
+    /// Returns the new generation.
+    ///
+    /// # Safety
+    /// Loading a library may run arbitrary code, see [`libloading::Library::new`].
+    ///
+    /// # Errors
+    /// - [`LoadError::LibraryInUse`] if handles, symbols or bound values from the current generation are still alive.
+    /// - [`LoadError::LibraryCopy`] or [`LoadError::LibraryLoad`] if the new generation couldn't be loaded, in which case [`ReloadableLibrary::current`]
+    ///   will return `None` until a reload succeeds.
+    pub unsafe fn try_reload(&self) -> Result<u64, LoadError> {
+        let mut current = self.lock();
+        if let Some(library) = current.take() {
+            let generation = library.generation;
+            match std::sync::Arc::try_unwrap(library.library) {
+                Ok(library) => drop(library),
+                Err(library) => {
+                    let live = std::sync::Arc::strong_count(&library).saturating_sub(1);
+                    *current = Some(StabbyLibraryHandle {
+                        library,
+                        generation,
+                    });
+                    return Err(LoadError::LibraryInUse { generation, live });
+                }
+            }
+        }
+        // Generations only change while `current` is locked.
+        let generation = self.generation().wrapping_add(1);
+        *current = Some(Self::load(&self.path, generation)?);
+        self.generation
+            .store(generation, std::sync::atomic::Ordering::Release);
+        Ok(generation)
+    }
+    /// Like [`ReloadableLibrary::try_reload`], but waits up to `timeout` for the current generation to stop being used.
+    ///
+    /// # Safety
+    /// Loading a library may run arbitrary code, see [`libloading::Library::new`].
+    ///
+    /// # Errors
+    /// See [`ReloadableLibrary::try_reload`]: [`LoadError::LibraryInUse`] is only returned once `timeout` has elapsed.
+    pub unsafe fn reload(&self, timeout: std::time::Duration) -> Result<u64, LoadError> {
+        let deadline = std::time::Instant::now().checked_add(timeout);
+        let mut backoff = std::time::Duration::from_micros(100);
+        loop {
+            match self.try_reload() {
+                Err(LoadError::LibraryInUse { .. })
+                    if deadline.map_or(true, |deadline| std::time::Instant::now() < deadline) =>
+                {
+                    std::thread::sleep(backoff);
+                    backoff = backoff
+                        .saturating_mul(2)
+                        .min(std::time::Duration::from_millis(10));
+                }
+                result => return result,
+            }
+        }
+    }
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<StabbyLibraryHandle>> {
+        self.current
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
 mod canaries {
     stabby_abi::canary_suffixes!();
 }
-const STABBIED_SUFFIX: &[u8] = b"_stabbied_v3";
-const REPORT_SUFFIX: &[u8] = b"_stabbied_v3_report";
+#[cfg(target_os = "linux")]
+mod elf;
+use crate::abi::checked_import::CanaryDescriptor;
+use crate::abi::plugin::{PluginDescriptor, PluginMismatch, PLUGIN_SYMBOL};
+use crate::abi::vtable::UNREGISTER_LIBRARY_SYMBOL;
+
+/// The canaries checked by [`StabbyLibrary::get_canaried`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Canary {
+    /// The version of `rustc`, up to its commit hash.
+    Rustc,
+    /// The optimization level.
+    OptLevel,
+    /// Whether or not debug symbols were enabled.
+    Debug,
+    /// The target triple.
+    Target,
+    /// The number of jobs used for the build.
+    NumJobs,
+}
+impl Canary {
+    /// All the canaries checked by [`StabbyLibrary::get_canaried`], in the order in which they are checked.
+    pub const ALL: [Self; 5] = [
+        Self::Rustc,
+        Self::OptLevel,
+        Self::Debug,
+        Self::Target,
+        Self::NumJobs,
+    ];
+    /// The suffix appended to the symbol's name to form this canary's symbol, as built by the current compiler.
+    pub const fn suffix(self) -> &'static str {
+        match self {
+            Self::Rustc => canaries::CANARY_RUSTC,
+            Self::OptLevel => canaries::CANARY_OPT_LEVEL,
+            Self::Debug => canaries::CANARY_DEBUG,
+            Self::Target => canaries::CANARY_TARGET,
+            Self::NumJobs => canaries::CANARY_NUM_JOBS,
+        }
+    }
+    /// The value of the build property this canary encodes, as recorded in `descriptor`.
+    pub fn value_in(self, descriptor: &CanaryDescriptor) -> String {
+        match self {
+            Self::Rustc => descriptor.rustc.as_str().into(),
+            Self::OptLevel => descriptor.opt_level.as_str().into(),
+            Self::Debug => descriptor.debug.to_string(),
+            Self::Target => descriptor.target.as_str().into(),
+            Self::NumJobs => descriptor.num_jobs.as_str().into(),
+        }
+    }
+}
+impl core::fmt::Display for Canary {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let name = match self {
+            Self::Rustc => "rustc",
+            Self::OptLevel => "opt_level",
+            Self::Debug => "debug",
+            Self::Target => "target",
+            Self::NumJobs => "num_jobs",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The ways loading a symbol through [`StabbyLibrary`] can fail.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The library itself couldn't be loaded.
+    LibraryLoad(libloading::Error),
+    /// A [`ReloadableLibrary`] couldn't copy the library before loading it.
+    LibraryCopy(std::io::Error),
+    /// The symbol (or its `_stabbied` companion) wasn't found in the library.
+    ///
+    /// This can mean that the library was compiled with a different version of stabby, or that the symbol was not exported with `#[stabby::export]`.
+    SymbolNotFound {
+        /// The name of the symbol that was looked up.
+        symbol: String,
+        /// The error reported by [`libloading`].
+        source: libloading::Error,
+    },
+    /// The symbol was found, but its report doesn't match the expected one.
+    ReportMismatch(crate::abi::checked_import::ReportMismatch),
+    /// The plugin was built with an incompatible version of `stabby`'s ABI.
+    AbiVersionMismatch {
+        /// The local ABI version.
+        local: crate::abi::plugin::AbiVersion,
+        /// The plugin's ABI version.
+        loaded: crate::abi::plugin::AbiVersion,
+    },
+    /// The symbol's report didn't match the expected one, but its `_stabbied_report` companion wasn't found to explain why.
+    ReportSymbolMissing {
+        /// The name of the report symbol that was looked up.
+        symbol: String,
+        /// The error reported by [`libloading`].
+        source: libloading::Error,
+    },
+    /// The symbol was found, but one of its canaries wasn't, indicating that it was built with a different configuration.
+    CanaryMissing {
+        /// The name of the canaried symbol.
+        symbol: String,
+        /// The first canary that wasn't found.
+        canary: Canary,
+        /// The value of the corresponding build property in the library, if it exported a [`CanaryDescriptor`].
+        found: Option<String>,
+        /// The value of the corresponding build property in the current build.
+        expected: String,
+        /// The error reported by [`libloading`].
+        source: libloading::Error,
+    },
+    /// A [`ReloadableLibrary`] couldn't be reloaded, because values from its current generation are still alive.
+    LibraryInUse {
+        /// The generation that couldn't be unloaded.
+        generation: u64,
+        /// The number of handles, symbols and bound values that kept it loaded.
+        live: usize,
+    },
+}
+impl LoadError {
+    fn symbol_not_found(symbol: &[u8], source: libloading::Error) -> Self {
+        Self::SymbolNotFound {
+            symbol: String::from_utf8_lossy(symbol).into_owned(),
+            source,
+        }
+    }
+}
+impl From<PluginMismatch> for LoadError {
+    fn from(value: PluginMismatch) -> Self {
+        match value {
+            PluginMismatch::AbiVersion { local, loaded } => {
+                Self::AbiVersionMismatch { local, loaded }
+            }
+            PluginMismatch::Report(mismatch) => Self::ReportMismatch(mismatch),
+        }
+    }
+}
+impl From<crate::abi::checked_import::ReportMismatch> for LoadError {
+    fn from(value: crate::abi::checked_import::ReportMismatch) -> Self {
+        Self::ReportMismatch(value)
+    }
+}
+impl core::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::LibraryLoad(source) => write!(f, "Couldn't load library: {source}"),
+            Self::LibraryCopy(source) => write!(f, "Couldn't copy library: {source}"),
+            Self::SymbolNotFound { symbol, source } => {
+                write!(f, "Symbol {symbol} not found: {source}")
+            }
+            Self::ReportMismatch(mismatch) => write!(f, "{mismatch}"),
+            Self::AbiVersionMismatch { local, loaded } => {
+                write!(f, "{}", PluginMismatch::AbiVersion { local: *local, loaded: *loaded })
+            }
+            Self::ReportSymbolMissing { symbol, source } => {
+                write!(
+                    f,
+                    "Reports mismatched, but report {symbol} not found: {source}"
+                )
+            }
+            Self::CanaryMissing {
+                symbol,
+                canary,
+                found: Some(found),
+                expected,
+                ..
+            } => write!(
+                f,
+                "Canary {symbol}{suffix} not found: library built with {canary} {found}, host is {expected}",
+                suffix = canary.suffix()
+            ),
+            Self::CanaryMissing {
+                symbol,
+                canary,
+                found: None,
+                source,
+                ..
+            } => write!(
+                f,
+                "Canary {symbol}{suffix} ({canary}) not found: {source}",
+                suffix = canary.suffix()
+            ),
+            Self::LibraryInUse { generation, live } => write!(
+                f,
+                "Couldn't unload generation {generation} of the library: {live} values still use it"
+            ),
+        }
+    }
+}
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::LibraryLoad(source)
+            | Self::SymbolNotFound { source, .. }
+            | Self::ReportSymbolMissing { source, .. }
+            | Self::CanaryMissing { source, .. } => Some(source),
+            Self::LibraryCopy(source) => Some(source),
+            Self::ReportMismatch(mismatch) => Some(mismatch),
+            Self::AbiVersionMismatch { .. } | Self::LibraryInUse { .. } => None,
+        }
+    }
+}
+
+const STABBIED_SUFFIX: &[u8] = b"_stabbied_v4";
+const REPORT_SUFFIX: &[u8] = b"_stabbied_v4_report";
+const DESCRIPTOR_SUFFIX: &[u8] = b"_canary_descriptor_v1";
 impl StabbyLibrary for libloading::Library {
     /// Gets `symbol` from the library, using stabby's reports to check for compatibility.
     ///
@@ -74,25 +689,32 @@ impl StabbyLibrary for libloading::Library {
     unsafe fn get_stabbied<'a, T: crate::IStable>(
         &'a self,
         symbol: &[u8],
-    ) -> Result<Symbol<'a, T>, Box<dyn std::error::Error + Send + Sync>> {
-        let stabbied = self.get::<extern "C" fn(&crate::abi::report::TypeReport) -> Option<T>>(
-            [symbol, STABBIED_SUFFIX].concat().as_slice(),
-        )?;
+    ) -> Result<Symbol<'a, T>, LoadError> {
+        let stabbied_symbol = [symbol, STABBIED_SUFFIX].concat();
+        let stabbied = self
+            .get::<extern "C" fn(&crate::abi::report::TypeReport) -> Option<T>>(
+                stabbied_symbol.as_slice(),
+            )
+            .map_err(|e| LoadError::symbol_not_found(&stabbied_symbol, e))?;
         match stabbied(T::REPORT) {
             Some(f) => Ok(Symbol {
                 inner: f,
                 lt: core::marker::PhantomData,
             }),
             None => {
+                let report_symbol = [symbol, REPORT_SUFFIX].concat();
                 let report = self
                     .get::<extern "C" fn() -> &'static crate::abi::report::TypeReport>(
-                        [symbol, REPORT_SUFFIX].concat().as_slice(),
-                    )?;
-                let report = report();
-                Err(format!(
-                    "Report mismatch: loader({loader}),  lib({report}",
-                    loader = T::REPORT
-                )
+                        report_symbol.as_slice(),
+                    )
+                    .map_err(|source| LoadError::ReportSymbolMissing {
+                        symbol: String::from_utf8_lossy(&report_symbol).into_owned(),
+                        source,
+                    })?;
+                Err(crate::abi::checked_import::ReportMismatch {
+                    local: T::REPORT,
+                    loaded: report(),
+                }
                 .into())
             }
         }
@@ -109,25 +731,66 @@ impl StabbyLibrary for libloading::Library {
     unsafe fn get_canaried<'a, T>(
         &'a self,
         symbol: &[u8],
-    ) -> Result<libloading::Symbol<'a, T>, Box<dyn std::error::Error + Send + Sync>> {
-        let stabbied = self.get::<T>(symbol)?;
-        for suffix in [
-            canaries::CANARY_RUSTC,
-            canaries::CANARY_OPT_LEVEL,
-            canaries::CANARY_DEBUG,
-            canaries::CANARY_TARGET,
-            canaries::CANARY_NUM_JOBS,
-        ] {
-            if let Err(e) =
-                self.get::<extern "C" fn()>([symbol, suffix.as_bytes()].concat().as_slice())
+    ) -> Result<libloading::Symbol<'a, T>, LoadError> {
+        let stabbied = self
+            .get::<T>(symbol)
+            .map_err(|e| LoadError::symbol_not_found(symbol, e))?;
+        for canary in Canary::ALL {
+            if let Err(source) = self
+                .get::<extern "C" fn()>([symbol, canary.suffix().as_bytes()].concat().as_slice())
             {
-                return Err(format!(
-                    "Canary {symbol}{suffix} not found: {e}",
-                    symbol = std::str::from_utf8_unchecked(symbol)
-                )
-                .into());
+                let descriptor = self
+                    .get::<extern "C" fn() -> &'static CanaryDescriptor>(
+                        [symbol, DESCRIPTOR_SUFFIX].concat().as_slice(),
+                    )
+                    .ok();
+                return Err(LoadError::CanaryMissing {
+                    symbol: String::from_utf8_lossy(symbol).into_owned(),
+                    canary,
+                    found: descriptor.map(|descriptor| canary.value_in(descriptor())),
+                    expected: canary.value_in(&CanaryDescriptor::LOCAL),
+                    source,
+                });
             }
         }
         Ok(stabbied)
     }
+    unsafe fn get_plugin<T: crate::IStable + Sync + 'static>(
+        &self,
+    ) -> Result<Plugin<'_, T>, LoadError> {
+        let entry = self
+            .get::<extern "C" fn() -> &'static PluginDescriptor>(PLUGIN_SYMBOL.as_bytes())
+            .map_err(|e| LoadError::symbol_not_found(PLUGIN_SYMBOL.as_bytes(), e))?;
+        let descriptor = entry();
+        let api = descriptor.get::<T>()?;
+        Ok(Plugin {
+            name: descriptor.name.as_str(),
+            abi_version: descriptor.abi_version,
+            api,
+        })
+    }
+    #[cfg(target_os = "linux")]
+    unsafe fn exports(&self) -> std::io::Result<Vec<Export<'_>>> {
+        let suffix = core::str::from_utf8(STABBIED_SUFFIX).unwrap_or_default();
+        let symbols = elf::defined_dynamic_symbols(&elf::library_path(self)?)?;
+        Ok(symbols
+            .iter()
+            .filter_map(|symbol| symbol.strip_suffix(suffix))
+            .filter_map(|name| {
+                let report = self
+                    .get::<extern "C" fn() -> &'static crate::abi::report::TypeReport>(
+                        [name.as_bytes(), REPORT_SUFFIX].concat().as_slice(),
+                    )
+                    .ok()?;
+                Some(Export {
+                    name: name.into(),
+                    report: report(),
+                })
+            })
+            .collect())
+    }
+    #[cfg(not(target_os = "linux"))]
+    unsafe fn exports(&self) -> std::io::Result<Vec<Export<'_>>> {
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
 }
@@ -0,0 +1,294 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   Pierre Avital, <pierre.avital@me.com>
+//
+
+//! A minimal reader for the dynamic symbol table of ELF files.
+
+use std::{
+    ffi::{c_char, c_int, c_void, CStr, OsStr},
+    fs::File,
+    io::{Error, ErrorKind, Read, Result, Seek, SeekFrom},
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
+
+const SHT_DYNSYM: u32 = 11;
+const SHN_UNDEF: u16 = 0;
+
+fn invalid(reason: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, reason)
+}
+
+#[derive(Clone, Copy)]
+struct Format {
+    is_64: bool,
+    is_le: bool,
+}
+impl Format {
+    fn u16(self, bytes: &[u8], at: usize) -> Result<u16> {
+        let bytes: [u8; 2] = at
+            .checked_add(2)
+            .and_then(|end| bytes.get(at..end))
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| invalid("truncated ELF structure"))?;
+        Ok(if self.is_le {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    }
+    fn u32(self, bytes: &[u8], at: usize) -> Result<u32> {
+        let bytes: [u8; 4] = at
+            .checked_add(4)
+            .and_then(|end| bytes.get(at..end))
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| invalid("truncated ELF structure"))?;
+        Ok(if self.is_le {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+    /// Reads a word whose size depends on the ELF class.
+    fn word(self, bytes: &[u8], at: usize) -> Result<u64> {
+        if !self.is_64 {
+            return self.u32(bytes, at).map(u64::from);
+        }
+        let bytes: [u8; 8] = at
+            .checked_add(8)
+            .and_then(|end| bytes.get(at..end))
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| invalid("truncated ELF structure"))?;
+        Ok(if self.is_le {
+            u64::from_le_bytes(bytes)
+        } else {
+            u64::from_be_bytes(bytes)
+        })
+    }
+}
+
+fn read_at(file: &mut File, offset: u64, len: u64) -> Result<Vec<u8>> {
+    // Lengths come from the file itself: check them before allocating.
+    let size = file.metadata()?.len();
+    if offset.checked_add(len).map_or(true, |end| end > size) {
+        return Err(invalid("ELF structure out of the file's bounds"));
+    }
+    let len = usize::try_from(len).map_err(|_| invalid("ELF section too large"))?;
+    let mut buffer = vec![0; len];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
+
+struct Section {
+    offset: u64,
+    size: u64,
+    link: u32,
+    entsize: u64,
+}
+
+const RTLD_DI_LINKMAP: c_int = 2;
+/// The head of glibc's and musl's `struct link_map`.
+#[repr(C)]
+struct LinkMap {
+    addr: usize,
+    name: *const c_char,
+    dynamic: *const c_void,
+}
+extern "C" {
+    fn dlinfo(handle: *mut c_void, request: c_int, info: *mut c_void) -> c_int;
+}
+
+/// Asks the dynamic loader for `library`'s own link map, as looking up one of its symbols could resolve to one of
+/// its dependencies instead.
+fn link_map(library: &libloading::Library) -> Result<&LinkMap> {
+    // SAFETY: `into_raw` doesn't close the handle, so this copy of it is only used to get its value.
+    let handle = libloading::os::unix::Library::from(unsafe {
+        core::ptr::read(library as *const libloading::Library)
+    })
+    .into_raw();
+    let mut map: *const LinkMap = core::ptr::null();
+    // SAFETY: `handle` is a valid handle, and `RTLD_DI_LINKMAP` writes a pointer to its link map in `map`.
+    if unsafe {
+        dlinfo(
+            handle,
+            RTLD_DI_LINKMAP,
+            (&mut map as *mut *const LinkMap).cast(),
+        )
+    } != 0
+    {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            "couldn't find the library's link map",
+        ));
+    }
+    // SAFETY: the link map lives as long as the library is loaded.
+    unsafe { map.as_ref() }
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, "couldn't find the library's link map"))
+}
+
+/// Finds the base address `library` is loaded at, as returned by [`crate::abi::vtable::library_of`].
+pub(crate) fn library_base(library: &libloading::Library) -> Result<*const ()> {
+    let base = crate::abi::vtable::library_of(link_map(library)?.dynamic.cast());
+    if base.is_null() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            "couldn't find the library's base address",
+        ));
+    }
+    Ok(base)
+}
+
+/// Finds the file `library` was loaded from.
+pub(crate) fn library_path(library: &libloading::Library) -> Result<PathBuf> {
+    let map = link_map(library)?;
+    let name = if map.name.is_null() {
+        &[]
+    } else {
+        // SAFETY: the link map's name is a nul-terminated string.
+        unsafe { CStr::from_ptr(map.name) }.to_bytes()
+    };
+    if name.is_empty() {
+        // The main program's link map has an empty name.
+        std::fs::read_link("/proc/self/exe")
+    } else {
+        Ok(Path::new(OsStr::from_bytes(name)).to_owned())
+    }
+}
+
+/// Lists the names of the symbols defined in the dynamic symbol table of the ELF file at `path`.
+pub(crate) fn defined_dynamic_symbols(path: &Path) -> Result<Vec<String>> {
+    let mut file = File::open(path)?;
+    let ident = read_at(&mut file, 0, 16)?;
+    if ident.get(..4) != Some(b"\x7fELF".as_slice()) {
+        return Err(invalid("not an ELF file"));
+    }
+    let format = Format {
+        is_64: ident.get(4) == Some(&2),
+        is_le: ident.get(5) == Some(&1),
+    };
+    let header = read_at(&mut file, 0, if format.is_64 { 64 } else { 52 })?;
+    let (shoff, shentsize, shnum) = if format.is_64 {
+        (
+            format.word(&header, 0x28)?,
+            format.u16(&header, 0x3a)?,
+            format.u16(&header, 0x3c)?,
+        )
+    } else {
+        (
+            format.word(&header, 0x20)?,
+            format.u16(&header, 0x2e)?,
+            format.u16(&header, 0x30)?,
+        )
+    };
+    let section_headers = read_at(
+        &mut file,
+        shoff,
+        u64::from(shentsize).saturating_mul(u64::from(shnum)),
+    )?;
+    let section = |index: u32| -> Result<(u32, Section)> {
+        let at = usize::try_from(index)
+            .ok()
+            .and_then(|index| index.checked_mul(usize::from(shentsize)))
+            .ok_or_else(|| invalid("invalid ELF section index"))?;
+        let field = |offset32: usize, offset64: usize| {
+            format.word(
+                &section_headers,
+                at.saturating_add(if format.is_64 { offset64 } else { offset32 }),
+            )
+        };
+        let ty = format.u32(&section_headers, at.saturating_add(4))?;
+        let link = format.u32(
+            &section_headers,
+            at.saturating_add(if format.is_64 { 0x28 } else { 0x18 }),
+        )?;
+        Ok((
+            ty,
+            Section {
+                offset: field(0x10, 0x18)?,
+                size: field(0x14, 0x20)?,
+                link,
+                entsize: field(0x24, 0x38)?,
+            },
+        ))
+    };
+    let mut names = Vec::new();
+    for index in 0..u32::from(shnum) {
+        let (ty, dynsym) = section(index)?;
+        if ty != SHT_DYNSYM {
+            continue;
+        }
+        let (_, dynstr) = section(dynsym.link)?;
+        let symbols = read_at(&mut file, dynsym.offset, dynsym.size)?;
+        let strings = read_at(&mut file, dynstr.offset, dynstr.size)?;
+        let entsize = usize::try_from(dynsym.entsize)
+            .ok()
+            .filter(|size| *size != 0)
+            .ok_or_else(|| invalid("invalid ELF symbol size"))?;
+        for symbol in symbols.chunks_exact(entsize) {
+            let name = format.u32(symbol, 0)?;
+            let shndx = format.u16(symbol, if format.is_64 { 6 } else { 14 })?;
+            if shndx == SHN_UNDEF {
+                continue;
+            }
+            let name = usize::try_from(name)
+                .ok()
+                .and_then(|name| strings.get(name..))
+                .ok_or_else(|| invalid("invalid ELF symbol name"))?;
+            let len = name.iter().position(|c| *c == 0).unwrap_or(name.len());
+            if let Some(name) = name.get(..len) {
+                names.push(String::from_utf8_lossy(name).into_owned());
+            }
+        }
+    }
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lengths_are_bounded_by_the_file() {
+        // A 64-bit little-endian header, claiming 65535 section headers of 65535 bytes each.
+        let header = [
+            b"\x7fELF\x02\x01".as_slice(),
+            &[0; 0x22],
+            &64u64.to_le_bytes(),
+            &[0; 0xa],
+            &[0xff; 4],
+            &[0; 2],
+        ]
+        .concat();
+        assert_eq!(header.len(), 64);
+        let path = std::env::temp_dir().join(format!("stabby-elf-{}.so", std::process::id()));
+        std::fs::write(&path, header).unwrap();
+        let error = defined_dynamic_symbols(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn base_tags_the_library_vtables() {
+        let this: libloading::Library = libloading::os::unix::Library::this().into();
+        let base = library_base(&this).unwrap();
+        assert_eq!(
+            crate::abi::vtable::library_of(base_tags_the_library_vtables as *const ()),
+            base
+        );
+        assert_eq!(
+            library_path(&this).unwrap(),
+            std::fs::read_link("/proc/self/exe").unwrap()
+        );
+    }
+}
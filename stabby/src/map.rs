@@ -1,4 +1,60 @@
-use stabby_abi::{IDeterminantProvider, IStable};
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   Pierre Avital, <pierre.avital@me.com>
+//
+
+use core::{
+    hash::{BuildHasher, Hash},
+    marker::PhantomData,
+    ptr::NonNull,
+};
+
+use crate::{
+    abi::{alloc::IAlloc, IDeterminantProvider, IStable, Tuple},
+    boxed::Box,
+    collections::{arc_btree::ArcBTreeMap, HashMap},
+};
+
+/// An ABI-stable iterator over the entries of an [`IMap`], borrowed from the map.
+#[crate::stabby]
+pub struct DynMapEntries<'a, K, V> {
+    // `Dyn`'s vtables must be `'static`, which iterating over references would require of `K` and `V`:
+    // the entries are passed as type-erased pointers instead.
+    entries: crate::dynptr!(Box<dyn Iterator<Item = Tuple<NonNull<()>, NonNull<()>>> + 'a>),
+    marker: PhantomData<&'a (K, V)>,
+}
+impl<'a, K, V> DynMapEntries<'a, K, V> {
+    /// Wraps an iterator over a map's entries.
+    pub fn new<I: Iterator<Item = (&'a K, &'a V)> + 'a>(entries: I) -> Self {
+        Self {
+            entries: Box::new(
+                entries.map(|(k, v)| Tuple(NonNull::from(k).cast(), NonNull::from(v).cast())),
+            )
+            .into(),
+            marker: PhantomData,
+        }
+    }
+}
+impl<'a, K, V> Iterator for DynMapEntries<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        let Tuple(k, v) = self.entries.next()?;
+        // SAFETY: `new` built these pointers from references that live for `'a`.
+        Some(unsafe { (k.cast().as_ref(), v.cast().as_ref()) })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
 
 #[crate::stabby]
 /// A trait for key-value maps, allowing to pass hashmaps and the likes accross FFI boundary.
@@ -9,38 +65,142 @@ pub trait IMap<K: IStable, V: IStable + IDeterminantProvider<()>> {
     extern "C" fn get_mut<'a>(&'a mut self, key: &K) -> crate::option::Option<&'a mut V>;
     /// Inserts `value`, associated to `key`, returning the previous associated value if it existed.
     extern "C" fn insert(&mut self, key: K, value: V) -> crate::option::Option<V>;
+    /// Removes the value associated to `key`, returning it if it existed.
+    extern "C" fn remove(&mut self, key: &K) -> crate::option::Option<V>;
+    /// Returns `true` if a value is associated to `key`.
+    extern "C" fn contains_key(&self, key: &K) -> bool;
+    /// Returns the number of entries in the map.
+    extern "C" fn len(&self) -> usize;
+    /// Returns `true` if the map contains no entries.
+    extern "C" fn is_empty(&self) -> bool;
+    /// Iterates over the map's entries, in the order specified by the implementation.
+    extern "C" fn entries<'a>(&'a self) -> DynMapEntries<'a, K, V>;
 }
 
-#[cfg(feature = "alloc")]
+#[cfg(feature = "alloc-rs")]
 impl<K: IStable + Ord, V: IStable + IDeterminantProvider<()>> IMap<K, V>
-    for std_alloc::collections::BTreeMap<K, V>
+    for alloc_rs::collections::BTreeMap<K, V>
 {
     extern "C" fn get<'a>(&'a self, key: &K) -> crate::option::Option<&'a V> {
         self.get(key).into()
     }
-
     extern "C" fn get_mut<'a>(&'a mut self, key: &K) -> crate::option::Option<&'a mut V> {
         self.get_mut(key).into()
     }
-
     extern "C" fn insert(&mut self, key: K, value: V) -> crate::option::Option<V> {
         self.insert(key, value).into()
     }
+    extern "C" fn remove(&mut self, key: &K) -> crate::option::Option<V> {
+        self.remove(key).into()
+    }
+    extern "C" fn contains_key(&self, key: &K) -> bool {
+        self.contains_key(key)
+    }
+    extern "C" fn len(&self) -> usize {
+        self.len()
+    }
+    extern "C" fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+    extern "C" fn entries<'a>(&'a self) -> DynMapEntries<'a, K, V> {
+        DynMapEntries::new(self.iter())
+    }
 }
 
 #[cfg(feature = "std")]
-impl<K: IStable + core::hash::Hash + Eq, V: IStable + IDeterminantProvider<()>> IMap<K, V>
-    for std::collections::HashMap<K, V>
+impl<K: IStable + Hash + Eq, V: IStable + IDeterminantProvider<()>, S: BuildHasher> IMap<K, V>
+    for std::collections::HashMap<K, V, S>
 {
     extern "C" fn get<'a>(&'a self, key: &K) -> crate::option::Option<&'a V> {
         self.get(key).into()
     }
+    extern "C" fn get_mut<'a>(&'a mut self, key: &K) -> crate::option::Option<&'a mut V> {
+        self.get_mut(key).into()
+    }
+    extern "C" fn insert(&mut self, key: K, value: V) -> crate::option::Option<V> {
+        self.insert(key, value).into()
+    }
+    extern "C" fn remove(&mut self, key: &K) -> crate::option::Option<V> {
+        self.remove(key).into()
+    }
+    extern "C" fn contains_key(&self, key: &K) -> bool {
+        self.contains_key(key)
+    }
+    extern "C" fn len(&self) -> usize {
+        self.len()
+    }
+    extern "C" fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+    extern "C" fn entries<'a>(&'a self) -> DynMapEntries<'a, K, V> {
+        DynMapEntries::new(self.iter())
+    }
+}
 
+impl<
+        K: IStable + Hash + Eq,
+        V: IStable + IDeterminantProvider<()>,
+        S: BuildHasher,
+        Alloc: IAlloc,
+    > IMap<K, V> for HashMap<K, V, S, Alloc>
+{
+    extern "C" fn get<'a>(&'a self, key: &K) -> crate::option::Option<&'a V> {
+        self.get(key).into()
+    }
     extern "C" fn get_mut<'a>(&'a mut self, key: &K) -> crate::option::Option<&'a mut V> {
         self.get_mut(key).into()
     }
+    extern "C" fn insert(&mut self, key: K, value: V) -> crate::option::Option<V> {
+        self.insert(key, value).into()
+    }
+    extern "C" fn remove(&mut self, key: &K) -> crate::option::Option<V> {
+        self.remove(key).into()
+    }
+    extern "C" fn contains_key(&self, key: &K) -> bool {
+        self.contains_key(key)
+    }
+    extern "C" fn len(&self) -> usize {
+        self.len()
+    }
+    extern "C" fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+    extern "C" fn entries<'a>(&'a self) -> DynMapEntries<'a, K, V> {
+        DynMapEntries::new(self.iter())
+    }
+}
 
+/// Since [`IMap::get_mut`], [`IMap::insert`] and [`IMap::remove`] may need to copy shared nodes,
+/// the allocator must be [`Clone`] too.
+impl<
+        K: IStable + Ord + Clone,
+        V: IStable + IDeterminantProvider<()> + Clone,
+        Alloc: IAlloc + Clone,
+        const SPLIT_LIMIT: usize,
+    > IMap<K, V> for ArcBTreeMap<K, V, Alloc, SPLIT_LIMIT>
+{
+    extern "C" fn get<'a>(&'a self, key: &K) -> crate::option::Option<&'a V> {
+        self.get(key).into()
+    }
+    extern "C" fn get_mut<'a>(&'a mut self, key: &K) -> crate::option::Option<&'a mut V> {
+        self.get_mut(key).into()
+    }
     extern "C" fn insert(&mut self, key: K, value: V) -> crate::option::Option<V> {
         self.insert(key, value).into()
     }
+    extern "C" fn remove(&mut self, key: &K) -> crate::option::Option<V> {
+        self.remove(key).into()
+    }
+    extern "C" fn contains_key(&self, key: &K) -> bool {
+        self.contains_key(key)
+    }
+    extern "C" fn len(&self) -> usize {
+        self.len()
+    }
+    extern "C" fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+    extern "C" fn entries<'a>(&'a self) -> DynMapEntries<'a, K, V> {
+        DynMapEntries::new(self.iter())
+    }
 }
@@ -0,0 +1,109 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   Pierre Avital, <pierre.avital@me.com>
+//
+
+use crate as stabby;
+use stabby::{
+    borrow::{Cow, CowStr},
+    boxed::Box,
+    collections::{arc_btree::ArcBTreeMap, HashMap},
+    map::{IMap, IMapDyn, IMapDynMut},
+    string::String,
+};
+
+fn exercise_map(mut map: stabby::dynptr!(Box<dyn IMap<u32, u64>>)) {
+    assert!(map.is_empty());
+    for i in 0..50u32 {
+        assert!(map.insert(i, u64::from(i).wrapping_mul(2)).is_none());
+    }
+    assert_eq!(map.len(), 50);
+    assert_eq!(map.insert(3, 7), stabby::option::Option::Some(6));
+    assert!(map.contains_key(&3));
+    let value: Option<&mut u64> = map.get_mut(&3).into();
+    *value.unwrap() = 8;
+    assert_eq!(map.get(&3), stabby::option::Option::Some(&8));
+    assert_eq!(map.remove(&3), stabby::option::Option::Some(8));
+    assert!(map.remove(&3).is_none());
+    assert!(!map.contains_key(&3));
+    let mut entries = map
+        .entries()
+        .map(|(k, v)| (*k, *v))
+        .collect::<std::vec::Vec<_>>();
+    entries.sort_unstable();
+    let expected = (0..50u32)
+        .filter(|&i| i != 3)
+        .map(|i| (i, u64::from(i).wrapping_mul(2)))
+        .collect::<std::vec::Vec<_>>();
+    assert_eq!(entries, expected);
+}
+
+#[test]
+fn dyn_maps() {
+    exercise_map(Box::new(HashMap::<u32, u64>::new()).into());
+    exercise_map(Box::new(ArcBTreeMap::<u32, u64>::new()).into());
+    exercise_map(Box::new(std::collections::BTreeMap::<u32, u64>::new()).into());
+    exercise_map(Box::new(std::collections::HashMap::<u32, u64>::new()).into());
+
+    // Entries are borrowed, so neither keys nor values need to be `Clone` or `'static`.
+    let keys = [1u32, 2];
+    let mut map = HashMap::<&u32, stabby::vec::Vec<u8>>::new();
+    for key in &keys {
+        map.insert(key, stabby::vec::Vec::new());
+    }
+    let mut entries = IMap::entries(&map)
+        .map(|(k, v)| (**k, v.len()))
+        .collect::<std::vec::Vec<_>>();
+    entries.sort_unstable();
+    assert_eq!(entries, [(1, 0), (2, 0)]);
+}
+
+#[test]
+fn cows() {
+    let value = 5u32;
+    let borrowed = Cow::<u32>::from(&value);
+    let owned = borrowed.clone().to_owned();
+    assert!(borrowed.is_borrowed());
+    assert!(owned.is_owned());
+    assert_eq!(borrowed, owned);
+    assert_eq!(*owned, 5);
+    assert_eq!(std::format!("{borrowed:?} {owned}"), "5 5");
+
+    let borrowed = CowStr::from("hello");
+    let owned = CowStr::from(String::from("hello"));
+    assert!(borrowed.is_borrowed());
+    assert!(owned.is_owned());
+    assert_eq!(borrowed, owned);
+    assert_eq!(borrowed, "hello");
+    assert_eq!(owned.len(), 5);
+    assert_eq!(std::format!("{borrowed:?} {owned}"), "\"hello\" hello");
+    assert_eq!(borrowed.clone().into_owned(), "hello");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn cows_serde() {
+    let value = 5u32;
+    let borrowed = Cow::<u32>::from(&value);
+    assert_eq!(serde_json::to_string(&borrowed).unwrap(), "5");
+    let deserialized: Cow<u32> = serde_json::from_str("5").unwrap();
+    assert!(deserialized.is_owned());
+    assert_eq!(deserialized, borrowed);
+
+    let borrowed = CowStr::from("hello");
+    let owned = CowStr::from(String::from("hello"));
+    assert_eq!(serde_json::to_string(&borrowed).unwrap(), "\"hello\"");
+    assert_eq!(serde_json::to_string(&owned).unwrap(), "\"hello\"");
+    let deserialized: CowStr = serde_json::from_str("\"hello\"").unwrap();
+    assert!(deserialized.is_owned());
+    assert_eq!(deserialized, borrowed);
+}
@@ -0,0 +1,225 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   Pierre Avital, <pierre.avital@me.com>
+//
+
+// The test binary is linked with `-rdynamic` (see `build.rs`), so it can load its own exports.
+
+use crate as stabby;
+use stabby::abi::checked_import::CanaryDescriptor;
+use stabby::abi::plugin::{AbiVersion, PluginDescriptor, ABI_VERSION};
+use stabby::abi::report::TypeReport;
+use stabby::libloading::{Canary, LoadError, StabbyLibrary, StabbyLibraryHandle};
+
+#[stabby::export]
+pub extern "C" fn stabby_tests_add(a: u32, b: u32) -> u32 {
+    a.wrapping_add(b)
+}
+
+#[stabby::export(canaries)]
+pub const extern "C" fn stabby_tests_canaried(a: u32) -> u32 {
+    a
+}
+
+#[no_mangle]
+pub const extern "C" fn stabby_tests_uncanaried(a: u32) -> u32 {
+    a
+}
+
+/// Looks like a stabby export, but lacks its `_stabbied_v4_report` companion.
+#[no_mangle]
+pub extern "C" fn stabby_tests_unreported_stabbied_v4(
+    _report: &TypeReport,
+) -> Option<extern "C" fn()> {
+    None
+}
+
+static PLUGIN_VALUE: u32 = 42;
+/// A plugin built with a future version of stabby's ABI.
+#[no_mangle]
+pub extern "C" fn stabby_plugin_v1() -> &'static PluginDescriptor {
+    static DESCRIPTOR: PluginDescriptor = PluginDescriptor {
+        abi_version: AbiVersion {
+            major: ABI_VERSION.major.wrapping_add(1),
+            ..ABI_VERSION
+        },
+        ..PluginDescriptor::new("future", &PLUGIN_VALUE)
+    };
+    &DESCRIPTOR
+}
+
+fn this() -> libloading::Library {
+    libloading::os::unix::Library::this().into()
+}
+
+#[test]
+fn load_errors() {
+    let lib = this();
+    unsafe {
+        let add = lib
+            .get_stabbied::<extern "C" fn(u32, u32) -> u32>(b"stabby_tests_add")
+            .unwrap();
+        assert_eq!(add(1, 2), 3);
+        assert_eq!(
+            lib.get_canaried::<extern "C" fn(u32) -> u32>(b"stabby_tests_canaried")
+                .unwrap()(4),
+            4
+        );
+
+        let error = StabbyLibraryHandle::open("/stabby/does/not/exist.so").unwrap_err();
+        assert!(matches!(error, LoadError::LibraryLoad(_)), "{error:?}");
+        assert!(error.to_string().starts_with("Couldn't load library: "));
+
+        let error = lib
+            .get_stabbied::<extern "C" fn()>(b"stabby_tests_missing")
+            .err()
+            .unwrap();
+        assert!(
+            matches!(&error, LoadError::SymbolNotFound { symbol, .. } if symbol == "stabby_tests_missing_stabbied_v4"),
+            "{error:?}"
+        );
+        assert!(std::error::Error::source(&error).is_some());
+
+        let error = lib
+            .get_stabbied::<extern "C" fn(u64, u64) -> u64>(b"stabby_tests_add")
+            .err()
+            .unwrap();
+        let LoadError::ReportMismatch(mismatch) = &error else {
+            panic!("{error:?}")
+        };
+        assert_eq!(
+            mismatch.local,
+            <extern "C" fn(u64, u64) -> u64 as stabby::IStable>::REPORT
+        );
+        assert_eq!(
+            mismatch.loaded,
+            <extern "C" fn(u32, u32) -> u32 as stabby::IStable>::REPORT
+        );
+        assert_eq!(error.to_string(), mismatch.to_string());
+
+        let error = lib
+            .get_stabbied::<extern "C" fn()>(b"stabby_tests_unreported")
+            .err()
+            .unwrap();
+        assert!(
+            matches!(&error, LoadError::ReportSymbolMissing { symbol, .. } if symbol == "stabby_tests_unreported_stabbied_v4_report"),
+            "{error:?}"
+        );
+
+        let error = lib
+            .get_canaried::<extern "C" fn(u32) -> u32>(b"stabby_tests_missing")
+            .err()
+            .unwrap();
+        assert!(
+            matches!(&error, LoadError::SymbolNotFound { symbol, .. } if symbol == "stabby_tests_missing"),
+            "{error:?}"
+        );
+
+        let error = lib
+            .get_canaried::<extern "C" fn(u32) -> u32>(b"stabby_tests_uncanaried")
+            .err()
+            .unwrap();
+        assert!(
+            matches!(&error, LoadError::CanaryMissing { symbol, found: None, .. } if symbol == "stabby_tests_uncanaried"),
+            "{error:?}"
+        );
+
+        let error = lib.get_plugin::<u32>().err().unwrap();
+        assert!(
+            matches!(
+                error,
+                LoadError::AbiVersionMismatch { local, loaded }
+                    if local == ABI_VERSION && loaded.major == ABI_VERSION.major.wrapping_add(1)
+            ),
+            "{error:?}"
+        );
+        assert!(std::error::Error::source(&error).is_none());
+    }
+}
+
+/// Exported as if by a library built with another compiler: its rustc canary can't be found.
+#[no_mangle]
+pub const extern "C" fn stabby_tests_foreign(a: u32) -> u32 {
+    a
+}
+#[no_mangle]
+pub extern "C" fn stabby_tests_foreign_canary_descriptor_v1() -> &'static CanaryDescriptor {
+    static DESCRIPTOR: CanaryDescriptor = CanaryDescriptor {
+        rustc: stabby::str::Str::new("0.0.0-foreign"),
+        ..CanaryDescriptor::LOCAL
+    };
+    &DESCRIPTOR
+}
+
+#[test]
+fn canary_descriptor() {
+    let lib = this();
+    unsafe {
+        let descriptor = lib
+            .get::<extern "C" fn() -> &'static CanaryDescriptor>(
+                b"stabby_tests_canaried_canary_descriptor_v1",
+            )
+            .unwrap();
+        assert_eq!(*descriptor(), CanaryDescriptor::LOCAL);
+        assert!(!CanaryDescriptor::LOCAL.rustc.as_str().is_empty());
+        for canary in Canary::ALL {
+            assert!(!canary.value_in(&CanaryDescriptor::LOCAL).is_empty());
+        }
+
+        let error = lib
+            .get_canaried::<extern "C" fn(u32) -> u32>(b"stabby_tests_foreign")
+            .err()
+            .unwrap();
+        let LoadError::CanaryMissing {
+            symbol,
+            canary,
+            found,
+            expected,
+            ..
+        } = &error
+        else {
+            panic!("{error:?}")
+        };
+        assert_eq!(symbol, "stabby_tests_foreign");
+        assert_eq!(*canary, Canary::Rustc);
+        assert_eq!(found.as_deref(), Some("0.0.0-foreign"));
+        assert_eq!(expected, CanaryDescriptor::LOCAL.rustc.as_str());
+        assert_eq!(
+            error.to_string(),
+            std::format!(
+                "Canary stabby_tests_foreign{} not found: library built with rustc 0.0.0-foreign, host is {expected}",
+                Canary::Rustc.suffix()
+            )
+        );
+    }
+}
+
+#[test]
+fn exports() {
+    let lib = this();
+    let exports = unsafe { lib.exports() }.unwrap();
+    let add = exports
+        .iter()
+        .find(|export| export.name == "stabby_tests_add")
+        .unwrap();
+    assert_eq!(
+        add.report,
+        <extern "C" fn(u32, u32) -> u32 as stabby::IStable>::REPORT
+    );
+    assert!(exports
+        .iter()
+        .any(|export| export.name == "STABBY_TESTS_SETTINGS"));
+    // Without a report, a symbol isn't listed.
+    assert!(exports
+        .iter()
+        .all(|export| export.name != "stabby_tests_unreported"));
+}
@@ -0,0 +1,181 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   Pierre Avital, <pierre.avital@me.com>
+//
+
+mod v1 {
+    use crate as stabby;
+    #[stabby::stabby]
+    pub struct Duration {
+        pub secs: u64,
+        pub nanos: u32,
+    }
+    #[stabby::stabby]
+    pub struct Inner {
+        pub timeout: Duration,
+    }
+    #[stabby::stabby]
+    pub struct Config {
+        pub inner: Inner,
+        pub retries: u8,
+    }
+}
+mod v2 {
+    use crate as stabby;
+    #[stabby::stabby]
+    pub struct Duration {
+        pub secs: u32,
+        pub nanos: u32,
+    }
+    #[stabby::stabby]
+    pub struct Inner {
+        pub timeout: Duration,
+    }
+    #[stabby::stabby]
+    pub struct Config {
+        pub inner: Inner,
+        pub attempts: u8,
+        pub verbose: bool,
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn report_diff() {
+    use crate as stabby;
+    use stabby::abi::report::DiffKind;
+    use stabby::IStable;
+    let local = v1::Config::REPORT;
+    let loaded = v2::Config::REPORT;
+    assert!(local.diff(local).is_empty());
+    let diffs = local
+        .diff(loaded)
+        .iter()
+        .map(|d| d.to_string())
+        .collect::<std::vec::Vec<_>>();
+    for expected in [
+        "Config: module changed from `stabby::tests::reports::v1` to `stabby::tests::reports::v2`",
+        "Config.inner.timeout.secs: type changed from `u64` to `u32`",
+        "Config.retries: field renamed from `retries` to `attempts`",
+        "Config.verbose: field added",
+    ] {
+        assert!(
+            diffs.iter().any(|d| d == expected),
+            "{expected} not in {diffs:#?}"
+        );
+    }
+    assert!(local
+        .diff(loaded)
+        .iter()
+        .all(|d| d.kind != DiffKind::FieldRemoved));
+    let mismatch = stabby::abi::checked_import::ReportMismatch { local, loaded };
+    assert!(mismatch
+        .to_string()
+        .contains("\n  - Config.inner.timeout.secs: type changed from `u64` to `u32`"));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn report_layout() {
+    use crate as stabby;
+    use stabby::abi::report::{DiffKind, LayoutReport};
+    use stabby::IStable;
+    let local = v1::Config::REPORT;
+    assert_eq!(local.layout.size, 24);
+    assert_eq!(local.layout.align, 8);
+    assert_eq!(local.layout.padding_bits, 7 * 8 + 4 * 8);
+    let offsets = local
+        .fields()
+        .map(|f| (f.name.as_str(), f.offset))
+        .collect::<std::vec::Vec<_>>();
+    assert_eq!(offsets, [("retries", 16), ("inner", 0)]);
+    assert_eq!(
+        stabby::option::Option::<u8>::REPORT.layout,
+        LayoutReport::of::<stabby::option::Option<u8>>()
+    );
+    assert_eq!(core::num::NonZeroU8::REPORT.layout.forbidden_values, 1);
+    assert_eq!(bool::REPORT.layout.forbidden_values, 254);
+    // Multi-byte forbidden values count once, however many bytes they span.
+    assert_eq!(core::num::NonZeroU32::REPORT.layout.forbidden_values, 1);
+    assert_eq!(<&u64>::REPORT.layout.forbidden_values, 1);
+    let diffs = local.diff(v2::Config::REPORT);
+    assert!(diffs.iter().any(|d| matches!(
+        d.kind,
+        DiffKind::LayoutChanged { local, loaded } if local.size == 24 && loaded.size == 12
+    )));
+    assert_ne!(v1::Config::ID, v2::Config::ID);
+}
+
+mod evolving {
+    pub mod v1 {
+        use crate as stabby;
+        #[stabby::stabby(version = 1, compatible_with = "1..3", module = "evolving")]
+        pub struct State {
+            pub version: u32,
+            pub count: u32,
+        }
+    }
+    pub mod v2 {
+        use crate as stabby;
+        #[stabby::stabby(version = 2, compatible_with = "1..=2", module = "evolving")]
+        pub struct State {
+            pub version: u32,
+            pub count: u32,
+            pub total: u32,
+        }
+    }
+    pub mod strict {
+        use crate as stabby;
+        #[stabby::stabby(version = 2, module = "evolving")]
+        pub struct State {
+            pub version: u32,
+            pub count: u32,
+            pub total: u32,
+        }
+    }
+}
+
+#[test]
+fn report_evolution() {
+    use crate as stabby;
+    use evolving::{strict, v1, v2};
+    use stabby::abi::report::VersionRange;
+    use stabby::IStable;
+    assert_eq!(
+        v1::State::REPORT.compatible_with,
+        VersionRange { start: 1, end: 3 }
+    );
+    assert_eq!(
+        v2::State::REPORT.compatible_with,
+        VersionRange { start: 1, end: 3 }
+    );
+    assert!(strict::State::REPORT.compatible_with.is_empty());
+    assert!(<&v1::State>::REPORT.is_pointer);
+    assert!(<*mut v1::State>::REPORT.is_pointer);
+    assert!(<core::ptr::NonNull<v1::State>>::REPORT.is_pointer);
+    assert!(!v1::State::REPORT.is_pointer);
+    assert!(!<stabby::boxed::Box<v1::State>>::REPORT.is_pointer);
+    // Behind a pointer, either side may have extra trailing fields.
+    let old = <extern "C" fn(&'static v1::State) -> u32 as IStable>::REPORT;
+    let new = <extern "C" fn(&'static v2::State) -> u32 as IStable>::REPORT;
+    assert!(!old.is_compatible(new));
+    assert!(old.is_evolution_compatible(new));
+    assert!(new.is_evolution_compatible(old));
+    // Both sides must opt in.
+    let strict = <extern "C" fn(&'static strict::State) -> u32 as IStable>::REPORT;
+    assert!(!old.is_evolution_compatible(strict));
+    assert!(!strict.is_evolution_compatible(old));
+    // By value, the size of the type matters.
+    let old = <extern "C" fn(v1::State) -> u32 as IStable>::REPORT;
+    let new = <extern "C" fn(v2::State) -> u32 as IStable>::REPORT;
+    assert!(!old.is_evolution_compatible(new));
+}
@@ -0,0 +1,57 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   Pierre Avital, <pierre.avital@me.com>
+//
+
+mod exported {
+    use crate as stabby;
+    #[stabby::stabby]
+    pub struct Settings {
+        pub retries: u8,
+        pub timeout: u32,
+    }
+    #[stabby::export]
+    pub static STABBY_TESTS_SETTINGS: Settings = Settings {
+        retries: 3,
+        timeout: 500,
+    };
+    #[stabby::export]
+    pub static STABBY_TESTS_RETRIES: u32 = 3;
+}
+
+mod imported {
+    use crate as stabby;
+    // Without link arguments, the symbols are resolved within the current binary.
+    #[stabby::import]
+    extern "C" {
+        pub static STABBY_TESTS_SETTINGS: crate::tests::statics::exported::Settings;
+    }
+    #[stabby::import]
+    extern "C" {
+        pub static STABBY_TESTS_RETRIES: u64;
+    }
+}
+
+#[test]
+fn import_exported_static() {
+    let settings = imported::STABBY_TESTS_SETTINGS.as_ref().unwrap();
+    assert!(core::ptr::eq(settings, &exported::STABBY_TESTS_SETTINGS));
+    assert_eq!(imported::STABBY_TESTS_SETTINGS.retries, 3);
+    assert_eq!(imported::STABBY_TESTS_SETTINGS.timeout, 500);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn mismatched_static_is_rejected() {
+    let mismatch = imported::STABBY_TESTS_RETRIES.as_ref().unwrap_err();
+    assert!(mismatch.to_string().contains("u64"), "{mismatch}");
+}